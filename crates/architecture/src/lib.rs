@@ -2,7 +2,7 @@
 //! Dữ liệu được lưu trữ thông qua `repository::Storage` để tăng hiệu suất.
 
 use serde::{Deserialize, Serialize};
-use repository::{now, Entity, Error, Key, Query, Storage};
+use repository::{Decoder, Entity, Error, Key, Query, Storage};
 use shared::{Showable, Filterable};
 use std::convert::TryFrom;
 use repository::Id;
@@ -63,6 +63,16 @@ impl TryFrom<String> for Kind {
     }
 }
 
+impl Kind {
+    /// Mọi loại thành phần, theo thứ tự byte chỉ mục
+    pub fn all() -> [Kind; 11] {
+        [
+            Kind::System, Kind::Context, Kind::Module, Kind::Agent, Kind::Trait, Kind::Entity,
+            Kind::Aggregate, Kind::Value, Kind::Event, Kind::Command, Kind::Other,
+        ]
+    }
+}
+
 impl std::fmt::Display for Kind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -88,6 +98,7 @@ pub struct Entry {
 
 impl Entity for Entry {
     const NAME: &'static str = "architecture";
+    const LAYOUT: u32 = 1; // 1: chuỗi escape thay cho phân tách bằng một byte NUL
     type Key = String;
     type Index = Vec<u8>;
     type Summary = Summary;
@@ -97,14 +108,22 @@ impl Entity for Entry {
     }
 
     fn index(&self) -> Self::Index {
-        let mut index = Vec::new();
-        index.push((&self.r#type).into());
-        index.extend_from_slice(self.context.as_bytes());
-        index.push(0);
-        index.extend_from_slice(self.module.as_bytes());
-        index.push(0);
-        index.extend_from_slice(self.name.as_bytes());
-        index
+        // type + context + module + name, chuỗi được escape nên tên chứa NUL vẫn đúng thứ tự
+        let mut key = Key::reserve(1 + self.context.len() + self.module.len() + self.name.len() + 6);
+        key.byte((&self.r#type).into());
+        key.text(&self.context);
+        key.text(&self.module);
+        key.text(&self.name);
+        key.build()
+    }
+
+    fn locate(index: &[u8]) -> Option<Self::Key> {
+        // Khóa chính dựng lại từ đúng các thành phần của chỉ mục
+        let mut parts = Decoder::new(index);
+        let byte = parts.byte().ok()?;
+        let r#type = Kind::all().into_iter().find(|kind| u8::from(kind) == byte)?;
+        let (context, module, name) = (parts.text().ok()?, parts.text().ok()?, parts.text().ok()?);
+        Some(format!("{}:{}:{}:{}", context, module, r#type, name))
     }
 
     fn summary(&self) -> Self::Summary {
@@ -116,6 +135,22 @@ impl Entity for Entry {
             created: self.created,
         }
     }
+
+    fn text(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.responsibility.clone(),
+            self.dependency.clone(),
+            self.performance.clone(),
+            self.naming.clone(),
+            self.context.clone(),
+            self.module.clone(),
+        ]
+    }
+
+    fn created(&self) -> Option<u128> {
+        Some(self.created)
+    }
 }
 
 impl Filterable for Entry {
@@ -153,9 +188,23 @@ impl Showable for Summary {
     }
 }
 
+/// Tiền tố chỉ mục để liệt kê theo loại, rồi đúng context, rồi module bắt đầu bằng `module`.
+/// `module` chỉ có tác dụng khi đã có `context`.
+pub fn prefix(r#type: &Kind, context: Option<&str>, module: Option<&str>) -> Vec<u8> {
+    let mut key = Key::reserve(32);
+    key.byte(r#type.into());
+    if let Some(context) = context {
+        key.text(context);
+        if let Some(module) = module {
+            key.prefix(module);
+        }
+    }
+    key.build()
+}
+
 /// Thêm một bản ghi kiến trúc mới. Chỉ insert, không upsert.
 pub async fn add<S: Storage>(store: &S, mut entry: Entry) -> Result<Entry, Error> {
-    entry.created = now();
+    entry.created = store.now();
     store.insert(entry.clone()).await?;
     Ok(entry)
 }
@@ -189,6 +238,10 @@ pub async fn query<S: Storage>(store: &S, query: Query<Vec<u8>>)
 mod tests {
     use super::*;
     use repository::sled::Sled; // Sử dụng Sled làm backend test
+    use repository::now;
+    use repository::storage::time::Manual;
+    use std::sync::Arc;
+    use std::time::Duration;
     use tempfile::tempdir;
     use tokio::runtime::Runtime;
 
@@ -257,25 +310,66 @@ mod tests {
     fn list() {
         let rt = Runtime::new().unwrap();
         rt.block_on(async {
-            let store = memory();
+            let clock = Arc::new(Manual::new(1_000_000));
+            let store = memory().clock(clock.clone());
             for i in 0..5 {
+                clock.advance(Duration::from_millis(1));
                 let entry = Entry {
                     id: Id::new_v4(),
                     context: "Test".to_string(), module: format!("Mod{}", i), r#type: Kind::Agent, name: format!("Item{}", i),
                     responsibility: "".to_string(), dependency: "".to_string(), performance: "".to_string(), naming: "".to_string(),
-                    prompt: "".to_string(), created: now() + i as u128,
+                    prompt: "".to_string(), created: 0, // Sẽ được ghi đè bởi add theo clock
                 };
                 add(&store, entry).await.unwrap();
             }
 
-            let results = query(&store, Query { prefix: Vec::new(), after: None, limit: 10 }).await.unwrap();
+            let results = query(&store, Query { prefix: Vec::new(), after: None, limit: 10, filter: None }).await.unwrap();
             let mut summaries: Vec<_> = results.collect::<Result<Vec<_>, _>>().unwrap();
             assert_eq!(summaries.len(), 5);
             // Sắp xếp lại theo created giảm dần
-            summaries.sort_by(|a, b| b.created.cmp(&a.created));
+            summaries.sort_by_key(|s| std::cmp::Reverse(s.created));
             // Kiểm tra thứ tự sắp xếp (mới nhất trước theo created timestamp)
             assert_eq!(summaries[0].module, "Mod4");
             assert_eq!(summaries[4].module, "Mod0");
+            assert_eq!(summaries[4].created, 2_000_000);
+        });
+    }
+
+    #[test]
+    fn index() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let store = memory();
+            let entry = |context: &str, module: &str, name: &str| Entry {
+                id: Id::new_v4(),
+                context: context.to_string(), module: module.to_string(), r#type: Kind::Trait, name: name.to_string(),
+                responsibility: "".to_string(), dependency: "".to_string(), performance: "".to_string(), naming: "".to_string(),
+                prompt: "".to_string(), created: 0,
+            };
+            // Tên chứa NUL không lẫn sang context/module khác
+            let odd = entry("Sys\0Dir", "Mod", "Na\0me");
+            add(&store, odd.clone()).await.unwrap();
+            add(&store, entry("Sys", "Dir\0Mod", "Name")).await.unwrap();
+            add(&store, entry("Sys", "Module", "Other")).await.unwrap();
+
+            let mut parts = repository::Decoder::new(&odd.index());
+            assert_eq!(parts.byte().unwrap(), u8::from(&Kind::Trait));
+            assert_eq!(parts.text().unwrap(), "Sys\0Dir");
+            assert_eq!(parts.text().unwrap(), "Mod");
+            assert_eq!(parts.text().unwrap(), "Na\0me");
+            assert!(parts.done());
+            assert_eq!(Entry::locate(&odd.index()), Some(odd.key()));
+
+            let names = |prefix: Vec<u8>| {
+                let store = store.clone();
+                async move {
+                    query(&store, Query { prefix, after: None, limit: 10, filter: None }).await.unwrap()
+                        .map(|s| s.unwrap().name).collect::<Vec<_>>()
+                }
+            };
+            assert_eq!(names(prefix(&Kind::Trait, Some("Sys"), None)).await, vec!["Name", "Other"]);
+            assert_eq!(names(prefix(&Kind::Trait, Some("Sys"), Some("Mod"))).await, vec!["Other"]);
+            assert_eq!(names(prefix(&Kind::Trait, Some("Sys\0Dir"), None)).await, vec!["Na\0me"]);
         });
     }
 }
\ No newline at end of file
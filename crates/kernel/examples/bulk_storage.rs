@@ -109,6 +109,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: active_key.clone().build(), // Active users only
         after: None,
         limit: 10,
+        filter: None,
     };
     
     let active_users: Vec<_> = storage.query::<User>(active_query).await?
@@ -127,6 +128,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: inactive_key.clone().build(), // Inactive users only
         after: None,
         limit: 5,
+        filter: None,
     };
     
     let inactive_users: Vec<_> = storage.query::<User>(inactive_query).await?
@@ -148,6 +150,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             prefix: Vec::new(), // All users
             after: last_key.clone(),
             limit: page_size,
+            filter: None,
         };
         
         let summaries: Vec<_> = storage.query::<User>(paginated_query).await?
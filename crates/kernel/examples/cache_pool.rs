@@ -14,6 +14,7 @@ use tokio::time::sleep;
 
 /// Simulate database connection
 #[derive(Clone, Debug)]
+#[allow(dead_code)]
 struct DbConnection {
     id: String,
     created: u128,
@@ -34,6 +35,7 @@ impl DbConnection {
 
 /// Simulate expensive computation result
 #[derive(Clone, Debug)]
+#[allow(dead_code)]
 struct ComputationResult {
     input: String,
     result: String,
@@ -3,7 +3,7 @@
 //! Example này minh họa cách sử dụng framework export
 //! để xuất dữ liệu từ storage theo nhiều định dạng khác nhau.
 
-use kernel::storage::{Builder, Config, Export, Exportable, Filter, Format};
+use kernel::storage::{Builder, Compression, Config, Export, Exportable, Filter, Format};
 use kernel::{Entity, Id, Sled, Storage};
 use serde::{Serialize, Deserialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -121,8 +121,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Demo 1: Export cơ bản với Builder
     println!("\n📤 Demo 1: Export cơ bản với Builder");
-    let export = Builder::new()
-        .config(Config { batch: 100, timeout: 30, compress: false })
+    let export: Export<_, User> = Builder::new()
+        .config(Config { batch: 100, timeout: 30, compress: false, ..Default::default() })
         .format(Format::Json)
         .build(storage.clone());
     let stream = export.export(Format::Json).await?;
@@ -135,6 +135,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: b"".to_vec(),
         limit: Some(2),
         offset: Some(0),
+        cursor: None,
+        since: None,
     };
     let stream = export.partial(filter, Format::Json).await?;
     file("export_filter.json", stream)?;
@@ -146,6 +148,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ("JSON", Format::Json, "export.json"),
         ("CSV", Format::Csv, "export.csv"),
         ("Binary", Format::Binary, "export.bin"),
+        ("NDJSON", Format::Ndjson, "export.ndjson"),
+        ("Markdown", Format::Markdown, "export.md"),
+        ("TSV", Format::Tsv, "export.tsv"),
     ];
     for (name, format, path) in formats {
         let stream = export.export(format).await?;
@@ -159,17 +164,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         batch: 50,
         timeout: 60,
         compress: true,
+        codec: Compression::Gzip,
     };
     let stream = export.export(Format::Custom(custom)).await?;
-    file("export_custom.json", stream)?;
-    println!("✅ Export với config custom thành công, đã ghi ra export_custom.json");
+    file("export_custom.json.gz", stream)?;
+    println!("✅ Export với config custom thành công, đã ghi ra export_custom.json.gz");
     
     // Demo 5: Export concurrent
     println!("\n📤 Demo 5: Export concurrent");
     let mut handles = Vec::new();
     
     for i in 0..3 {
-        let export = Export::default(storage.clone());
+        let export = Export::<_, User>::default(storage.clone());
         let handle = tokio::spawn(async move {
             let stream = export.export(Format::Json).await.unwrap();
             println!("✅ Export concurrent {} thành công", i + 1);
@@ -206,6 +212,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: Vec::new(),
         limit: Some(0), // Invalid limit
         offset: Some(0),
+        cursor: None,
+        since: None,
     };
     
     match export.partial(filter, Format::Json).await {
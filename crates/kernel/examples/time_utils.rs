@@ -120,6 +120,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: key_hp.clone().build(),
         after: None,
         limit: 5,
+        filter: None,
     };
     
     let high_priority_events: Vec<_> = storage.query::<Event>(high_priority_query).await?
@@ -137,6 +138,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: key_mp.clone().build(),
         after: None,
         limit: 5,
+        filter: None,
     };
     
     let medium_priority_events: Vec<_> = storage.query::<Event>(medium_priority_query).await?
@@ -155,6 +157,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: Vec::new(), // All priorities
         after: None,
         limit: 10,
+        filter: None,
     };
     
     let recent_events: Vec<_> = storage.query::<Event>(recent_query).await?
@@ -179,6 +182,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         prefix: Vec::new(),
         after: None,
         limit: 20,
+        filter: None,
     };
     
     let all_events: Vec<_> = storage.query::<Event>(time_range_query).await?
@@ -203,6 +207,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             prefix: Vec::new(),
             after: None,
             limit: 5,
+            filter: None,
         };
         
         let _results: Vec<_> = storage.query::<Event>(query).await?
@@ -4,6 +4,7 @@
 //! Tuân thủ nguyên tắc đơn từ và hiệu suất theo thiết kế.
 
 use std::collections::HashMap;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 
 /// Configuration cho Framework
@@ -60,8 +61,20 @@ pub struct Addon {
     pub dir: String,
     /// Auto load plugins
     pub auto: bool,
-    /// Plugin timeout
+    /// Plugin timeout (seconds) for each init/shutdown call, 0 = no limit
     pub timeout: u64,
+    /// Per-plugin timeout overrides (seconds), keyed by plugin name
+    #[serde(default)]
+    pub limits: HashMap<String, u64>,
+}
+
+impl Addon {
+    /// Giới hạn thời gian cho một lần gọi lifecycle của plugin `name`
+    /// Ưu tiên giá trị riêng trong `limits`, sau đó tới `timeout`; 0 nghĩa là không giới hạn
+    pub fn limit(&self, name: &str) -> Option<Duration> {
+        let secs = self.limits.get(name).copied().unwrap_or(self.timeout);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
 }
 
 /// Performance configuration
@@ -157,6 +170,7 @@ impl Default for Addon {
             dir: "./plugins".to_string(),
             auto: false,
             timeout: 30,
+            limits: HashMap::new(),
         }
     }
 }
@@ -213,4 +227,19 @@ mod tests {
         assert_eq!(config1.get("key1"), Some(&"value1".to_string()));
         assert_eq!(config1.get("key2"), Some(&"value2".to_string()));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn limit() {
+        let mut addon = Addon::default();
+        assert_eq!(addon.limit("any"), Some(Duration::from_secs(30)));
+
+        addon.limits.insert("slow".to_string(), 120);
+        addon.limits.insert("free".to_string(), 0);
+        assert_eq!(addon.limit("slow"), Some(Duration::from_secs(120)));
+        assert_eq!(addon.limit("free"), None);
+
+        // File cấu hình cũ không có `limits` vẫn đọc được
+        let addon: Addon = serde_json::from_str(r#"{"dir":"./plugins","auto":false,"timeout":5}"#).unwrap();
+        assert_eq!(addon.limit("any"), Some(Duration::from_secs(5)));
+    }
+}
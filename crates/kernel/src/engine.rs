@@ -28,7 +28,8 @@ use tokio::sync::RwLock;
 use crate::config::Config;
 use crate::logger::Logger;
 use crate::router::Router;
-use crate::plugin::Plugin;
+use crate::plugin::{self, Failure, Plugin};
+use crate::metric::Registry;
 
 /// Core Engine quản lý lifecycle và điều phối framework
 /// 
@@ -46,6 +47,12 @@ pub struct Engine {
     plugins: Arc<RwLock<HashMap<String, Arc<dyn Plugin>>>>,
     /// Trạng thái engine
     state: Arc<RwLock<State>>,
+    /// Registry metric của engine và plugin, được router phục vụ qua `/metrics`
+    metric: Registry,
+    /// Tác vụ nền chụp vòng lịch sử metric, chạy giữa `start` và `stop`
+    watch: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// Các plugin đã khởi tạo, theo thứ tự khởi tạo (dừng theo thứ tự ngược lại)
+    started: RwLock<Vec<String>>,
 }
 
 /// Trạng thái của Engine
@@ -65,6 +72,28 @@ pub enum State {
     Error,
 }
 
+impl State {
+    /// Tên trạng thái dạng chữ thường, dùng làm nhãn metric
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Init => "init",
+            State::Ready => "ready",
+            State::Running => "running",
+            State::Stopping => "stopping",
+            State::Stopped => "stopped",
+            State::Error => "error",
+        }
+    }
+}
+
+/// Kết quả lỗi của một lần gọi lifecycle có giới hạn thời gian (nội bộ)
+enum Guard {
+    /// Plugin trả lỗi
+    Fault(Box<dyn std::error::Error>),
+    /// Hết giới hạn thời gian
+    Timeout(Failure),
+}
+
 impl Engine {
     /// Tạo Engine mới với cấu hình mặc định
     ///
@@ -74,9 +103,11 @@ impl Engine {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let config = Arc::new(Config::default());
         let logger = Arc::new(Logger::new(&config)?);
-        let router = Arc::new(Router::new());
+        let metric = Registry::new();
+        let router = Arc::new(Router::new().registry(metric.clone()));
         let plugins = Arc::new(RwLock::new(HashMap::new()));
         let state = Arc::new(RwLock::new(State::Init));
+        metric.gauge("kernel_engine_state", &[("state", State::Init.name())]).set(1);
 
         Ok(Self {
             config,
@@ -84,32 +115,77 @@ impl Engine {
             router,
             plugins,
             state,
+            metric,
+            watch: std::sync::Mutex::new(None),
+            started: RwLock::new(Vec::new()),
         })
     }
 
+    /// Dùng registry cho trước (ví dụ registry của storage actor) để `/metrics` gom cả thao tác storage
+    ///
+    /// Router được tạo lại với registry mới, nên gọi trước khi đăng ký route.
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.router = Arc::new(Router::new().registry(registry.clone()));
+        self.metric = registry;
+        self.metric.gauge("kernel_engine_state", &[("state", State::Init.name())]).set(1);
+        self
+    }
+
+    /// Dùng cấu hình cho trước thay cho cấu hình mặc định (ví dụ giới hạn thời gian plugin trong `addon`)
+    ///
+    /// Cấu hình được truyền vào `Plugin::init`, nên gọi trước `start`.
+    pub fn configure(mut self, config: Config) -> Self {
+        self.config = Arc::new(config);
+        self
+    }
+
+    /// Registry metric của engine
+    pub fn metrics(&self) -> &Registry {
+        &self.metric
+    }
+
+    /// Chuyển trạng thái và cập nhật gauge `kernel_engine_state` (1 cho trạng thái hiện tại, 0 cho phần còn lại)
+    async fn enter(&self, next: State) {
+        let mut state = self.state.write().await;
+        self.metric.gauge("kernel_engine_state", &[("state", state.name())]).set(0);
+        self.metric.gauge("kernel_engine_state", &[("state", next.name())]).set(1);
+        *state = next;
+    }
+
     /// Khởi động Engine (async)
     ///
     /// - Chuyển trạng thái sang Ready, Running
-    /// - Khởi tạo plugin, router
+    /// - Khởi tạo plugin theo thứ tự phụ thuộc, rồi router
+    /// - Nếu plugin hoặc router lỗi: dừng các plugin đã khởi tạo theo thứ tự ngược lại, chuyển sang `State::Error`
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        {
-            let mut state = self.state.write().await;
-            *state = State::Ready;
-        }
+        self.enter(State::Ready).await;
 
         self.logger.info("Engine đang khởi động...");
 
         // Khởi tạo plugins
-        self.setup().await?;
+        if let Err(e) = self.setup().await {
+            self.logger.error(&format!("Engine khởi động thất bại: {}", e));
+            self.enter(State::Error).await;
+            return Err(e);
+        }
 
-        // Khởi tạo router
-        self.router.init().await?;
+        // Khởi tạo router; lỗi thì dừng các plugin đã khởi tạo như khi plugin lỗi
+        if let Err(e) = self.router.init().await {
+            self.logger.error(&format!("Engine khởi động thất bại: router: {}", e));
+            // Lỗi dừng từng plugin đã được ghi log trong `rollback`; lỗi trả về là lỗi của router
+            let _ = self.shutdown().await;
+            self.enter(State::Error).await;
+            return Err(e);
+        }
 
-        {
-            let mut state = self.state.write().await;
-            *state = State::Running;
+        // Chụp vòng lịch sử metric định kỳ cho các cửa sổ 1m/5m/15m
+        let watch = self.metric.watch();
+        if let Some(old) = self.watch.lock().unwrap_or_else(|e| e.into_inner()).replace(watch) {
+            old.abort();
         }
 
+        self.enter(State::Running).await;
+
         self.logger.info("Engine đã khởi động thành công");
         Ok(())
     }
@@ -117,26 +193,29 @@ impl Engine {
     /// Dừng Engine (async)
     ///
     /// - Chuyển trạng thái sang Stopping, Stopped
-    /// - Dừng plugin, router
+    /// - Dừng plugin (theo thứ tự ngược, có giới hạn thời gian), router
+    /// - Plugin dừng lỗi hoặc quá hạn: engine vẫn dừng hẳn, lỗi đầu tiên được trả về
     pub async fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
-        {
-            let mut state = self.state.write().await;
-            *state = State::Stopping;
-        }
+        self.enter(State::Stopping).await;
 
         self.logger.info("Engine đang dừng...");
 
-        // Dừng plugins
-        self.shutdown().await?;
+        // Dừng plugins; lỗi (kể cả quá hạn) không chặn việc dừng router và tác vụ nền
+        let plugins = self.shutdown().await;
 
         // Dừng router
         self.router.shutdown().await?;
 
-        {
-            let mut state = self.state.write().await;
-            *state = State::Stopped;
+        if let Some(watch) = self.watch.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            watch.abort();
         }
 
+        self.enter(State::Stopped).await;
+
+        if let Err(e) = plugins {
+            self.logger.error(&format!("Engine đã dừng nhưng có plugin lỗi: {}", e));
+            return Err(e);
+        }
         self.logger.info("Engine đã dừng thành công");
         Ok(())
     }
@@ -154,6 +233,7 @@ impl Engine {
     pub async fn add(&self, name: String, plugin: Arc<dyn Plugin>) -> Result<(), Box<dyn std::error::Error>> {
         let mut plugins = self.plugins.write().await;
         plugins.insert(name.clone(), plugin);
+        self.metric.gauge("kernel_engine_plugins", &[]).set(plugins.len() as u64);
         self.logger.info(&format!("Đã thêm plugin: {}", name));
         Ok(())
     }
@@ -162,6 +242,7 @@ impl Engine {
     pub async fn remove(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
         let mut plugins = self.plugins.write().await;
         if plugins.remove(name).is_some() {
+            self.metric.gauge("kernel_engine_plugins", &[]).set(plugins.len() as u64);
             self.logger.info(&format!("Đã xóa plugin: {}", name));
         }
         Ok(())
@@ -179,24 +260,85 @@ impl Engine {
         plugins.keys().cloned().collect()
     }
 
-    /// Khởi tạo tất cả plugin (nội bộ)
+    /// Khởi tạo tất cả plugin theo thứ tự phụ thuộc (nội bộ)
+    /// Thuật toán: `plugin::order` xếp thứ tự (từ chối vòng và phụ thuộc thiếu trước khi gọi `init` nào);
+    /// khi một plugin lỗi hoặc quá hạn, các plugin đã khởi tạo được dừng theo thứ tự ngược lại
     async fn setup(&self) -> Result<(), Box<dyn std::error::Error>> {
         let plugins = self.plugins.read().await;
-        for (name, plugin) in plugins.iter() {
-            plugin.init(&self.config).await?;
+        let order = plugin::order(plugins.iter().map(|(name, plugin)| (name.as_str(), plugin.depends())))?;
+        let mut started = self.started.write().await;
+        for name in order {
+            let failure = match self.guard(&name, "init", plugins[&name].init(&self.config)).await {
+                Ok(()) => None,
+                Err(Guard::Timeout(failure)) => Some(failure),
+                Err(Guard::Fault(e)) => Some(Failure::Init { plugin: name.clone(), message: e.to_string() }),
+            };
+            if let Some(failure) = failure {
+                self.logger.error(&failure.to_string());
+                self.rollback(&plugins, &mut started).await;
+                return Err(failure.into());
+            }
             self.logger.info(&format!("Đã khởi tạo plugin: {}", name));
+            started.push(name);
         }
         Ok(())
     }
 
-    /// Dừng tất cả plugin (nội bộ)
+    /// Dừng các plugin đã khởi tạo theo thứ tự ngược lại, bỏ qua (nhưng ghi log) lỗi từng plugin
+    /// Trả về lỗi đầu tiên; quá hạn giữ nguyên `Failure::Timeout`
+    async fn rollback(&self, plugins: &HashMap<String, Arc<dyn Plugin>>, started: &mut Vec<String>) -> Option<Box<dyn std::error::Error>> {
+        let mut first: Option<Box<dyn std::error::Error>> = None;
+        while let Some(name) = started.pop() {
+            let Some(plugin) = plugins.get(&name) else { continue };
+            match self.guard(&name, "shutdown", plugin.shutdown()).await {
+                Ok(()) => self.logger.info(&format!("Đã dừng plugin: {}", name)),
+                Err(Guard::Timeout(failure)) => {
+                    self.logger.error(&failure.to_string());
+                    first.get_or_insert(failure.into());
+                }
+                Err(Guard::Fault(e)) => {
+                    self.logger.error(&format!("Dừng plugin {} thất bại: {}", name, e));
+                    first.get_or_insert(format!("plugin '{}' dừng thất bại: {}", name, e).into());
+                }
+            }
+        }
+        first
+    }
+
+    /// Dừng tất cả plugin đã khởi tạo, theo thứ tự ngược với lúc khởi tạo (nội bộ)
+    /// Mọi plugin đều được gọi `shutdown`; lỗi đầu tiên (nếu có) được trả về sau cùng
     async fn shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
         let plugins = self.plugins.read().await;
-        for (name, plugin) in plugins.iter() {
-            plugin.shutdown().await?;
-            self.logger.info(&format!("Đã dừng plugin: {}", name));
+        let mut started = self.started.write().await;
+        match self.rollback(&plugins, &mut started).await {
+            Some(e) => Err(e),
+            None => Ok(()),
         }
-        Ok(())
+    }
+
+    /// Chạy một lần gọi lifecycle của plugin trong giới hạn `config.addon.limit(name)` và đếm kết quả
+    /// Quá hạn thì hủy lời gọi và trả `Failure::Timeout`; metric ghi `result="timeout"`
+    async fn guard(
+        &self,
+        name: &str,
+        stage: &'static str,
+        call: impl std::future::Future<Output = Result<(), Box<dyn std::error::Error>>>,
+    ) -> Result<(), Guard> {
+        let metric = if stage == "init" { "kernel_plugin_init_total" } else { "kernel_plugin_shutdown_total" };
+        let res = match self.config.addon.limit(name) {
+            Some(limit) => match tokio::time::timeout(limit, call).await {
+                Ok(res) => res.map_err(Guard::Fault),
+                Err(_) => Err(Guard::Timeout(Failure::Timeout { plugin: name.to_string(), stage, limit })),
+            },
+            None => call.await.map_err(Guard::Fault),
+        };
+        let result = match &res {
+            Ok(()) => "ok",
+            Err(Guard::Timeout(_)) => "timeout",
+            Err(Guard::Fault(_)) => "error",
+        };
+        self.metric.counter(metric, &[("plugin", name), ("result", result)]).add(1);
+        res
     }
 
     /// Lấy reference đến config
@@ -291,4 +433,184 @@ mod tests {
         
         engine.stop().await.unwrap();
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn metrics() {
+        let engine = Engine::new().unwrap();
+        engine.add("mock".to_string(), Arc::new(_Mock::_new())).await.unwrap();
+        engine.start().await.unwrap();
+
+        let request = crate::router::Request {
+            path: "/metrics".to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: vec![],
+        };
+        let response = engine.router().route(request).await.unwrap();
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("kernel_engine_plugins 1\n"));
+        assert!(body.contains("kernel_engine_state{state=\"running\"} 1\n"));
+        assert!(body.contains("kernel_engine_state{state=\"ready\"} 0\n"));
+        assert!(body.contains("kernel_plugin_init_total{plugin=\"mock\",result=\"ok\"} 1\n"));
+
+        engine.stop().await.unwrap();
+    }
+
+    /// Plugin ghi lại thứ tự gọi lifecycle vào nhật ký chung
+    struct Step {
+        name: &'static str,
+        depends: Vec<&'static str>,
+        fail: bool,
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Plugin for Step {
+        async fn init(&self, _config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+            self.log.lock().unwrap().push(format!("init {}", self.name));
+            if self.fail {
+                return Err("hỏng".into());
+            }
+            Ok(())
+        }
+
+        async fn shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
+            self.log.lock().unwrap().push(format!("shutdown {}", self.name));
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn description(&self) -> &str {
+            "Step plugin for testing"
+        }
+
+        fn depends(&self) -> &[&str] {
+            &self.depends
+        }
+    }
+
+    async fn steps(specs: &[(&'static str, &[&'static str], bool)]) -> (Engine, Arc<std::sync::Mutex<Vec<String>>>) {
+        let engine = Engine::new().unwrap();
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        for (name, depends, fail) in specs {
+            let step = Step { name, depends: depends.to_vec(), fail: *fail, log: log.clone() };
+            engine.add(name.to_string(), Arc::new(step)).await.unwrap();
+        }
+        (engine, log)
+    }
+
+    #[tokio::test]
+    async fn order() {
+        let (engine, log) = steps(&[("api", &["store", "cache"], false), ("cache", &["store"], false), ("store", &[], false)]).await;
+        engine.start().await.unwrap();
+        engine.stop().await.unwrap();
+        assert_eq!(*log.lock().unwrap(), vec![
+            "init store", "init cache", "init api",
+            "shutdown api", "shutdown cache", "shutdown store",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn rollback() {
+        let (engine, log) = steps(&[("api", &["cache"], false), ("cache", &["store"], true), ("store", &[], false)]).await;
+        let err = engine.start().await.unwrap_err();
+        assert!(err.to_string().contains("'cache'"));
+        assert_eq!(engine.state().await, State::Error);
+        // api không được khởi tạo; store được dừng lại
+        assert_eq!(*log.lock().unwrap(), vec!["init store", "init cache", "shutdown store"]);
+
+        // Đã dừng hết nên stop không gọi shutdown lần nữa
+        engine.stop().await.unwrap();
+        assert_eq!(log.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn graph() {
+        let (engine, log) = steps(&[("a", &["b"], false), ("b", &["a"], false), ("c", &[], false)]).await;
+        let err = engine.start().await.unwrap_err();
+        assert!(err.to_string().contains("a, b"));
+        assert_eq!(engine.state().await, State::Error);
+        assert!(log.lock().unwrap().is_empty());
+
+        let (engine, log) = steps(&[("api", &["store"], false)]).await;
+        let err = engine.start().await.unwrap_err();
+        assert!(err.to_string().contains("store"));
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    /// Plugin treo vô hạn ở `init` hoặc `shutdown`
+    struct Hang {
+        stage: &'static str,
+    }
+
+    #[async_trait::async_trait]
+    impl Plugin for Hang {
+        async fn init(&self, _config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+            if self.stage == "init" {
+                std::future::pending::<()>().await;
+            }
+            Ok(())
+        }
+
+        async fn shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
+            if self.stage == "shutdown" {
+                std::future::pending::<()>().await;
+            }
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "hang"
+        }
+
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn description(&self) -> &str {
+            "Hanging plugin for testing"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout() {
+        let mut config = Config::default();
+        config.addon.timeout = 5;
+        config.addon.limits.insert("slow".to_string(), 60);
+        let engine = Engine::new().unwrap().configure(config);
+        engine.add("mock".to_string(), Arc::new(_Mock::_new())).await.unwrap();
+        engine.add("slow".to_string(), Arc::new(Hang { stage: "init" })).await.unwrap();
+
+        let begin = tokio::time::Instant::now();
+        let err = engine.start().await.unwrap_err();
+        // Giới hạn riêng của "slow" (60 giây) thay cho mặc định 5 giây
+        assert_eq!(begin.elapsed(), std::time::Duration::from_secs(60));
+        match err.downcast_ref::<Failure>() {
+            Some(Failure::Timeout { plugin, stage, .. }) => assert_eq!((plugin.as_str(), *stage), ("slow", "init")),
+            other => panic!("lỗi không mong đợi: {:?}", other),
+        }
+        assert_eq!(engine.state().await, State::Error);
+        let body = engine.metrics().render();
+        assert!(body.contains("kernel_plugin_init_total{plugin=\"slow\",result=\"timeout\"} 1\n"));
+        assert!(body.contains("kernel_plugin_shutdown_total{plugin=\"mock\",result=\"ok\"} 1\n"));
+
+        // Quá hạn khi dừng vẫn dừng hết engine và trả lỗi nêu tên plugin
+        let mut config = Config::default();
+        config.addon.timeout = 5;
+        let engine = Engine::new().unwrap().configure(config);
+        engine.add("stuck".to_string(), Arc::new(Hang { stage: "shutdown" })).await.unwrap();
+        engine.start().await.unwrap();
+        let err = engine.stop().await.unwrap_err();
+        assert!(err.to_string().contains("'stuck'"));
+        assert!(matches!(err.downcast_ref::<Failure>(), Some(Failure::Timeout { stage: "shutdown", .. })));
+        assert_eq!(engine.state().await, State::Stopped);
+        assert!(engine.metrics().render().contains("kernel_plugin_shutdown_total{plugin=\"stuck\",result=\"timeout\"} 1\n"));
+    }
+}
@@ -48,6 +48,11 @@ pub enum Error {
     #[error("giao dịch bị hủy bỏ")]
     Aborted,
 
+    /// Được trả về khi dữ liệu đã bị thay đổi bởi một thao tác ghi khác giữa lúc đọc và lúc ghi.
+    /// Mục đích: Cho phép caller thử lại thay vì ghi đè và làm lệch chỉ mục.
+    #[error("xung đột ghi đồng thời")]
+    Conflict,
+
     /// Lỗi từ tác vụ bất đồng bộ (tokio join error).
     /// Mục đích: Bọc lỗi khi join các task bất đồng bộ thất bại.
     #[error("lỗi tác vụ bất đồng bộ: {0}")]
@@ -91,4 +96,34 @@ pub enum Error {
     /// Lỗi khi phân tích cú pháp.
     #[error("lỗi phân tích cú pháp: {0}")]
     Parse(String),
-}
\ No newline at end of file
+}
+
+impl Error {
+    /// Tên loại của mọi biến thể, theo thứ tự khai báo
+    /// Mục đích: Cho phép metric đếm lỗi theo loại bằng mảng cố định, không cần khóa
+    pub const KINDS: [&'static str; 15] = [
+        "missing", "validation", "store", "format", "aborted", "conflict", "join", "timeout",
+        "pool", "cache", "metric", "io", "csv", "json", "parse",
+    ];
+
+    /// Tên loại lỗi ngắn gọn, dùng làm nhãn khi thống kê
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Missing => "missing",
+            Error::Validation(_) => "validation",
+            Error::Store(_) => "store",
+            Error::Format(_) => "format",
+            Error::Aborted => "aborted",
+            Error::Conflict => "conflict",
+            Error::Join(_) => "join",
+            Error::Timeout => "timeout",
+            Error::Pool => "pool",
+            Error::Cache => "cache",
+            Error::Metric => "metric",
+            Error::Io(_) => "io",
+            Error::Csv(_) => "csv",
+            Error::Json(_) => "json",
+            Error::Parse(_) => "parse",
+        }
+    }
+}
@@ -48,7 +48,8 @@ pub use config::Config;
 // Thành tựu: Đảm bảo mọi định danh public đều là một từ tiếng Anh, không lộ chi tiết nội bộ
 pub use error::Error; // Enum lỗi chuẩn hóa, một từ duy nhất
 pub use extension::Extension; // Trait mở rộng lỗi, một từ duy nhất
-pub use storage::entity::{Entity, Query, Key}; // Trait thực thể, struct truy vấn, builder khóa
+pub use storage::entity::{Entity, Query, Key, Decoder}; // Trait thực thể, struct truy vấn, builder và decoder khóa
+pub use storage::predicate::Predicate; // Biểu thức lọc trên Summary
 pub use uuid::Uuid as Id; // Định danh duy nhất, tái xuất với tên Id (một từ)
 pub use storage::pool::Pool; // Struct pool kết nối, một từ duy nhất
 pub use storage::cache::Cache; // Struct cache, một từ duy nhất
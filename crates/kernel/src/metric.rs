@@ -6,24 +6,150 @@
 // ---
 // Import các thư viện cần thiết cho việc đo lường, đồng bộ hóa và lưu trữ trạng thái metric
 use std::sync::atomic::{AtomicU64, Ordering}; // AtomicU64: Đếm an toàn đồng thời, Ordering: Kiểm soát thứ tự bộ nhớ
-use std::collections::HashMap; // HashMap: Lưu trữ các metric theo tên thao tác
-use tokio::sync::RwLock; // RwLock: Cho phép nhiều luồng đọc/ghi metrics đồng thời
-use std::time::Instant; // Instant: Đo thời gian thực thi thao tác
+use std::collections::{BTreeMap, HashMap}; // HashMap: Lưu trữ các metric theo tên thao tác, BTreeMap: bộ đếm có thứ tự
+use std::sync::{Mutex, RwLock}; // RwLock: Nhiều luồng đọc đồng thời, chỉ khóa ghi khi đăng ký tên mới; Mutex: vòng lịch sử
+use std::hash::{Hash, Hasher}; // Hash: Chọn shard theo tên thao tác
+use std::time::{Duration, Instant}; // Instant, Duration: Đo thời gian thực thi thao tác
 use std::sync::Arc; // Arc: Chia sẻ ownership an toàn giữa các thread
+use crate::error::Error; // Error: Phân loại lỗi khi đếm thất bại
+
+pub mod prometheus; // Xuất metric theo định dạng văn bản Prometheus
+pub mod snapshot; // Ảnh chụp metric có cấu trúc, so sánh được
+pub mod window; // Cửa sổ thời gian trượt và vòng lịch sử
+
+pub use snapshot::{Sample, Snapshot};
+pub use window::{Frame, Pace, Window};
+use crate::storage::time::{Clock, System}; // Clock: Nguồn thời gian cho vòng lịch sử
+use window::History;
+
+/// Số bit phần định trị của mỗi bucket: 16 bucket con cho mỗi lũy thừa của 2 (sai số tương đối ≤ 6,25%)
+const BITS: u32 = 4;
+/// Số bucket con trong một nhóm lũy thừa
+const SUB: u64 = 1 << BITS;
+/// Tổng số bucket phủ toàn dải `u64` nano giây
+const BUCKETS: usize = ((64 - BITS + 1) as usize) * SUB as usize;
+/// Nhãn cho loại lỗi không có trong `Error::KINDS`, đếm ở ô cuối của `Metric::faults`
+const OTHER: &str = "other";
+
+/// Histogram độ trễ kiểu HDR, không khóa
+/// Mục đích: Ghi nhận phân phối thời gian thực thi để tính phân vị (p50/p90/p99) mà không cần khóa
+/// Thuật toán: Giá trị < 16 nằm ở bucket tuyến tính; giá trị lớn hơn được chia theo lũy thừa của 2,
+/// mỗi nhóm lũy thừa chia tiếp thành 16 bucket con đều nhau. Mỗi bucket là một `AtomicU64`.
+pub struct Histogram {
+    /// Số mẫu trong từng bucket
+    buckets: Box<[AtomicU64]>,
+    /// Tổng số mẫu
+    count: AtomicU64,
+    /// Tổng giá trị (nano giây)
+    sum: AtomicU64,
+    /// Giá trị lớn nhất đã gặp
+    max: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    /// Tạo histogram rỗng
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    /// Chỉ số bucket chứa giá trị
+    fn bucket(value: u64) -> usize {
+        if value < SUB {
+            return value as usize;
+        }
+        let shift = 63 - value.leading_zeros() - BITS; // Số bit bị bỏ đi của phần định trị
+        (shift as usize + 1) * SUB as usize + ((value >> shift) - SUB) as usize
+    }
+
+    /// Giá trị lớn nhất thuộc bucket (cận trên)
+    fn bound(index: usize) -> u64 {
+        if index < SUB as usize {
+            return index as u64;
+        }
+        let shift = (index / SUB as usize - 1) as u32;
+        let low = (SUB + (index % SUB as usize) as u64) << shift;
+        low + ((1u64 << shift) - 1)
+    }
+
+    /// Ghi một mẫu (nano giây)
+    pub fn record(&self, value: u64) {
+        self.buckets[Self::bucket(value)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    /// Tổng số mẫu
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Tổng giá trị các mẫu (nano giây)
+    pub fn sum(&self) -> u64 {
+        self.sum.load(Ordering::Relaxed)
+    }
+
+    /// Giá trị lớn nhất (nano giây)
+    pub fn max(&self) -> u64 {
+        self.max.load(Ordering::Relaxed)
+    }
+
+    /// Phân vị `q` (0.0..=1.0), trả về cận trên của bucket chứa mẫu thứ `⌈q·n⌉`, không vượt quá `max`
+    pub fn quantile(&self, q: f64) -> u64 {
+        Self::rank(self.buckets(), self.max(), q)
+    }
+
+    /// Các bucket khác rỗng dạng (chỉ số, số mẫu), theo thứ tự tăng dần
+    pub fn buckets(&self) -> Vec<(usize, u64)> {
+        self.buckets.iter()
+            .enumerate()
+            .map(|(index, bucket)| (index, bucket.load(Ordering::Relaxed)))
+            .filter(|(_, n)| *n > 0)
+            .collect()
+    }
+
+    /// Phân vị `q` trên danh sách bucket thưa (dùng chung cho histogram sống và snapshot)
+    fn rank(buckets: Vec<(usize, u64)>, max: u64, q: f64) -> u64 {
+        let count: u64 = buckets.iter().map(|(_, n)| n).sum();
+        if count == 0 {
+            return 0;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, n) in buckets {
+            seen += n;
+            if seen >= rank {
+                return Self::bound(index).min(max);
+            }
+        }
+        max
+    }
+}
 
 /// Metric cho một loại thao tác
 /// Mục đích: Lưu trữ số liệu thống kê cho từng loại thao tác (ví dụ: insert, fetch, update)
 #[derive(Clone)]
 pub struct Metric {
-    /// Tổng thời gian thực thi (nano giây)
-    /// Thành tựu: Cho phép tính thời gian trung bình mỗi thao tác
-    time: Arc<AtomicU64>,
+    /// Phân phối thời gian thực thi của mọi lần gọi
+    /// Thành tựu: Cho phép tính trung bình và các phân vị p50/p90/p99/max
+    latency: Arc<Histogram>,
     /// Số lần thực thi thành công
     /// Thành tựu: Đo lường tần suất thành công
     count: Arc<AtomicU64>,
-    /// Số lần thực thi thất bại
-    /// Thành tựu: Đo lường tần suất lỗi
-    fail: Arc<AtomicU64>,
+    /// Số lần thực thi thất bại, theo thứ tự `Error::KINDS`, ô cuối cho loại lạ (`OTHER`)
+    /// Thành tựu: Biết lỗi thuộc loại nào (conflict, timeout, store, ...)
+    faults: Arc<[AtomicU64; Error::KINDS.len() + 1]>,
 }
 
 impl Default for Metric {
@@ -32,13 +158,62 @@ impl Default for Metric {
     }
 }
 
+/// Số shard của bảng metric
+const SHARDS: usize = 16;
+
+/// Một shard của bảng metric theo tên
+type Shard = RwLock<HashMap<String, Metric>>;
+
 /// Registry quản lý tất cả metrics
 /// Mục đích: Gom nhóm và quản lý nhiều metric theo tên thao tác
+/// Thuật toán: Tên được băm vào một trong `SHARDS` shard; tra cứu chỉ cần khóa đọc của shard đó,
+/// khóa ghi chỉ khi đăng ký tên mới. Mọi lần ghi đều được đếm, không có nhánh bỏ mẫu.
 #[derive(Clone)]
 pub struct Registry {
-    /// Map lưu trữ các metric, key là tên thao tác
-    /// Thành tựu: Cho phép truy xuất metric theo tên thao tác một cách hiệu quả
-    map: Arc<RwLock<HashMap<String, Metric>>>,
+    /// Các shard lưu metric, key là tên thao tác
+    /// Thành tựu: Luồng ghi các thao tác khác nhau hiếm khi tranh cùng một khóa
+    map: Arc<[Shard; SHARDS]>,
+    /// Bộ đếm và gauge có nhãn (cache, pool, engine, plugin), key là (tên, nhãn đã mã hóa)
+    /// Thành tựu: Thành phần giữ `Counter` và tăng trực tiếp, không tra cứu theo chuỗi mỗi lần
+    counters: Arc<RwLock<Counters>>,
+    /// Vòng ảnh chụp theo thời gian, nguồn của các cửa sổ 1m/5m/15m
+    history: Arc<Mutex<History>>,
+    /// Nguồn thời gian cho `tick` và cửa sổ
+    clock: Arc<dyn Clock>,
+}
+
+/// Bảng bộ đếm có nhãn: (tên, nhãn đã mã hóa) -> (loại, handle)
+type Counters = BTreeMap<(String, String), (Kind, Counter)>;
+
+/// Loại giá trị của một `Counter` khi xuất ra (Prometheus `# TYPE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Chỉ tăng (tổng số lần)
+    Counter,
+    /// Giá trị tức thời, có thể đặt lại (số entry, trạng thái, ...)
+    Gauge,
+}
+
+/// Handle rẻ tới một bộ đếm trong `Registry`
+/// Mục đích: Cho phép hot path giữ sẵn handle thay vì tra tên mỗi lần
+#[derive(Clone, Default, Debug)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    /// Cộng thêm `n`
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Đặt giá trị (dùng cho gauge)
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    /// Giá trị hiện tại
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 impl Default for Registry {
@@ -52,54 +227,106 @@ impl Metric {
     /// Mục đích: Đảm bảo mọi metric bắt đầu từ trạng thái sạch
     pub fn new() -> Self {
         Self {
-            time: Arc::new(AtomicU64::new(0)), // Thời gian tích lũy = 0
+            latency: Arc::new(Histogram::new()), // Histogram rỗng
             count: Arc::new(AtomicU64::new(0)), // Số lần thành công = 0
-            fail: Arc::new(AtomicU64::new(0)), // Số lần thất bại = 0
+            faults: Arc::new(std::array::from_fn(|_| AtomicU64::new(0))), // Không lỗi nào
         }
     }
-    
-    /// Ghi lại thời gian thực thi và trạng thái thành công/thất bại
+
+    /// Ghi lại kết quả một thao tác bắt đầu tại `start`
     /// Mục đích: Cập nhật số liệu cho mỗi lần thao tác được thực hiện
-    /// Thuật toán: Tính thời gian đã trôi qua, tăng bộ đếm tương ứng
-    pub fn record(&self, start: Instant, failed: bool) {
-        let elapsed = start.elapsed().as_nanos() as u64; // Đo thời gian thực thi (nano giây)
-        self.time.fetch_add(elapsed, Ordering::Relaxed); // Cộng dồn thời gian
-        if failed {
-            self.fail.fetch_add(1, Ordering::Relaxed); // Tăng số lần thất bại
-        } else {
-            self.count.fetch_add(1, Ordering::Relaxed); // Tăng số lần thành công
+    /// Thuật toán: Đo thời gian đã trôi qua rồi chuyển cho `observe`
+    pub fn record<T>(&self, start: Instant, res: &Result<T, Error>) {
+        self.observe(start.elapsed(), res.as_ref().err().map(Error::kind));
+    }
+
+    /// Ghi lại một lần thực thi đã đo sẵn thời gian
+    /// Mục đích: Dùng khi thời gian được đo ở nơi khác; `fault` là `Error::kind` nếu thất bại
+    pub fn observe(&self, elapsed: Duration, fault: Option<&'static str>) {
+        self.latency.record(u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX));
+        match fault {
+            Some(kind) => {
+                let index = Error::KINDS.iter().position(|k| *k == kind).unwrap_or(Error::KINDS.len());
+                self.faults[index].fetch_add(1, Ordering::Relaxed); // Tăng số lần thất bại theo loại
+            }
+            None => {
+                self.count.fetch_add(1, Ordering::Relaxed); // Tăng số lần thành công
+            }
         }
     }
-    
-    /// Lấy thống kê dạng chuỗi mô tả
-    /// Mục đích: Trả về tổng số lần, số lần thành công/thất bại, thời gian trung bình
+
+    /// Ảnh chụp số liệu hiện tại
+    /// Mục đích: Cho phép tuần tự hóa, so sánh giữa hai thời điểm thay vì ghép chuỗi
+    pub fn snapshot(&self) -> Sample {
+        let faults = self.faults().into_iter().map(|(kind, n)| (kind.to_string(), n)).collect();
+        Sample::new(self.count(), faults, self.latency.sum(), self.max(), self.latency.buckets())
+    }
+
+    /// Lấy thống kê dạng chuỗi mô tả (dạng hiển thị của `snapshot`)
     /// Thành tựu: Hỗ trợ quan sát hiệu năng và độ tin cậy
     pub fn stats(&self) -> String {
-        let time = self.time.load(Ordering::Relaxed); // Tổng thời gian
-        let count = self.count.load(Ordering::Relaxed); // Số lần thành công
-        let fail = self.fail.load(Ordering::Relaxed); // Số lần thất bại
-        let total = count + fail; // Tổng số lần thực thi
-        
-        if total == 0 {
-            return "Chưa có dữ liệu".to_string(); // Không có dữ liệu để thống kê
-        }
-        
-        let avg = if count > 0 { time / count } else { 0 }; // Thời gian trung bình mỗi lần thành công
-        format!(
-            "Tổng: {} lần ({} thành công, {} thất bại), Thời gian trung bình: {}ns",
-            total, count, fail, avg
-        )
+        self.snapshot().to_string()
     }
-    
+
+    /// Số lần thực thi thành công
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Số lần thực thi thất bại (mọi loại lỗi)
+    pub fn fail(&self) -> u64 {
+        self.faults.iter().map(|f| f.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Số lần thất bại theo loại lỗi (`Error::kind`), chỉ gồm các loại đã xảy ra; loại lạ gộp vào `"other"`
+    pub fn faults(&self) -> Vec<(&'static str, u64)> {
+        Error::KINDS.iter()
+            .chain(std::iter::once(&OTHER))
+            .zip(self.faults.iter())
+            .map(|(kind, f)| (*kind, f.load(Ordering::Relaxed)))
+            .filter(|(_, n)| *n > 0)
+            .collect()
+    }
+
+    /// Thời gian trung bình mỗi lần thực thi (nano giây)
+    pub fn average(&self) -> u64 {
+        self.latency.sum().checked_div(self.latency.count()).unwrap_or(0)
+    }
+
+    /// Histogram độ trễ bên dưới
+    pub fn latency(&self) -> &Histogram {
+        &self.latency
+    }
+
+    /// Trung vị độ trễ (nano giây)
+    pub fn p50(&self) -> u64 {
+        self.latency.quantile(0.50)
+    }
+
+    /// Phân vị 90 của độ trễ (nano giây)
+    pub fn p90(&self) -> u64 {
+        self.latency.quantile(0.90)
+    }
+
+    /// Phân vị 99 của độ trễ (nano giây)
+    pub fn p99(&self) -> u64 {
+        self.latency.quantile(0.99)
+    }
+
+    /// Độ trễ lớn nhất (nano giây)
+    pub fn max(&self) -> u64 {
+        self.latency.max()
+    }
+
     /// Lấy tỷ lệ lỗi (fail/success)
     /// Mục đích: Đánh giá độ tin cậy của thao tác
     /// Thành tựu: Cho phép phát hiện thao tác có tỷ lệ lỗi cao
     pub fn rate(&self) -> f64 {
-        let count = self.count.load(Ordering::Relaxed); // Số lần thành công
+        let count = self.count(); // Số lần thành công
         if count == 0 {
             return 0.0; // Tránh chia cho 0
         }
-        self.fail.load(Ordering::Relaxed) as f64 / count as f64 // Tỷ lệ lỗi
+        self.fail() as f64 / count as f64 // Tỷ lệ lỗi
     }
 }
 
@@ -108,57 +335,164 @@ impl Registry {
     /// Mục đích: Quản lý tập hợp các metric cho toàn hệ thống
     pub fn new() -> Self {
         Self {
-            map: Arc::new(RwLock::new(HashMap::new())), // Map rỗng, thread-safe
+            map: Arc::new(std::array::from_fn(|_| RwLock::new(HashMap::new()))), // Các shard rỗng
+            counters: Arc::new(RwLock::new(BTreeMap::new())), // Chưa có bộ đếm nào
+            history: Arc::new(Mutex::new(History::new(System.now()))), // Mốc gốc tại lúc tạo
+            clock: Arc::new(System),
         }
     }
-    
-    /// Ghi lại metric đồng bộ cho một thao tác
-    /// Mục đích: Cho phép Actor thread ghi metric mà không cần async
-    /// Thuật toán: Sử dụng try_write để tránh deadlock, fallback về async nếu cần
-    pub fn record(&self, name: &str, failed: bool) {
-        let start = Instant::now();
-        
-        // Thử sử dụng try_write trước để tránh deadlock
-        if let Ok(mut map) = self.map.try_write() {
-            let metric = map.entry(name.to_string())
-                .or_insert_with(Metric::new)
-                .clone();
-            drop(map); // Giải phóng lock trước khi gọi record
-            metric.record(start, failed);
-        } else {
-            // Fallback: tạo metric mới nếu không thể acquire lock
-            let metric = Metric::new();
-            metric.record(start, failed);
+
+    /// Dùng clock cho trước (mặc định `time::System`); vòng lịch sử bắt đầu lại tại thời điểm của clock
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.history = Arc::new(Mutex::new(History::new(clock.now())));
+        self.clock = clock;
+        self
+    }
+
+    /// Chụp trạng thái hiện tại vào vòng lịch sử
+    /// Mục đích: Gọi định kỳ (mỗi `window::INTERVAL`) để các cửa sổ có mốc so sánh
+    /// Lần gọi cách ảnh trước chưa đủ `window::INTERVAL` bị bỏ qua: vòng có sức chứa cố định
+    /// nên chỉ phủ đủ cửa sổ dài nhất khi các ảnh cách nhau ít nhất một INTERVAL.
+    pub fn tick(&self) {
+        let time = self.clock.now();
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        if time.saturating_sub(history.last()) < window::INTERVAL.as_nanos() {
+            return;
         }
+        history.push(time, self.snapshot());
+    }
+
+    /// Chạy `tick` định kỳ trên runtime hiện tại; hủy bằng `abort` trên handle trả về
+    pub fn watch(&self) -> tokio::task::JoinHandle<()> {
+        let registry = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(window::INTERVAL);
+            interval.tick().await; // Lần đầu trả về ngay, mốc gốc đã có sẵn
+            loop {
+                interval.tick().await;
+                registry.tick();
+            }
+        })
+    }
+
+    /// Số liệu trong cửa sổ `span` tính tới hiện tại
+    pub fn window(&self, span: Duration) -> Window {
+        let later = self.snapshot();
+        let now = self.clock.now();
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        let since = now.saturating_sub(span.as_nanos());
+        Window::new(span, now, history.base(since), &later)
+    }
+
+    /// Các cửa sổ chuẩn 1m/5m/15m
+    pub fn windows(&self) -> Vec<Window> {
+        window::SPANS.iter().map(|span| self.window(*span)).collect()
+    }
+
+    /// Vòng lịch sử: số liệu của từng khoảng giữa hai lần `tick`, cũ nhất trước
+    pub fn history(&self) -> Vec<Frame> {
+        self.history.lock().unwrap_or_else(|e| e.into_inner()).frames()
+    }
+
+    /// Lấy (hoặc đăng ký) bộ đếm chỉ tăng theo tên và nhãn
+    pub fn counter(&self, name: &str, pairs: &[(&str, &str)]) -> Counter {
+        self.handle(Kind::Counter, name, pairs)
+    }
+
+    /// Lấy (hoặc đăng ký) gauge theo tên và nhãn
+    pub fn gauge(&self, name: &str, pairs: &[(&str, &str)]) -> Counter {
+        self.handle(Kind::Gauge, name, pairs)
+    }
+
+    /// Tra handle theo (tên, nhãn); khóa ghi chỉ cần khi đăng ký lần đầu
+    fn handle(&self, kind: Kind, name: &str, pairs: &[(&str, &str)]) -> Counter {
+        let key = (name.to_string(), prometheus::labels(pairs));
+        if let Some((_, counter)) = self.counters.read().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return counter.clone();
+        }
+        let mut counters = self.counters.write().unwrap_or_else(|e| e.into_inner());
+        counters.entry(key).or_insert_with(|| (kind, Counter::default())).1.clone()
+    }
+
+    /// Toàn bộ bộ đếm có nhãn, sắp theo tên rồi nhãn: (tên, nhãn đã mã hóa, loại, giá trị)
+    pub fn counters(&self) -> Vec<(String, String, Kind, u64)> {
+        self.counters.read().unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|((name, pairs), (kind, counter))| (name.clone(), pairs.clone(), *kind, counter.get()))
+            .collect()
+    }
+
+    /// Xuất toàn bộ metric theo định dạng văn bản Prometheus/OpenMetrics
+    pub fn render(&self) -> String {
+        prometheus::render(self)
     }
     
-    /// Lấy metric cho một thao tác, tạo mới nếu chưa có
-    /// Mục đích: Đảm bảo mọi thao tác đều có metric riêng biệt
-    /// Thuật toán: Sử dụng entry API để lấy hoặc chèn metric mới
-    pub async fn get(&self, name: &str) -> Metric {
-        let mut map = self.map.write().await; // Ghi lock để có thể thêm mới
-        map.entry(name.to_string())
-            .or_insert_with(Metric::new)
+    /// Ghi lại metric cho một thao tác bắt đầu tại `start`
+    /// Mục đích: Ghi nhanh theo tên khi không giữ sẵn handle; hot path nên giữ `Metric` từ `get`
+    pub fn record<T>(&self, name: &str, start: Instant, res: &Result<T, Error>) {
+        let elapsed = start.elapsed();
+        self.get(name).observe(elapsed, res.as_ref().err().map(Error::kind));
+    }
+
+    /// Shard chứa tên
+    fn shard(&self, name: &str) -> &Shard {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        name.hash(&mut hasher);
+        &self.map[hasher.finish() as usize % SHARDS]
+    }
+
+    /// Lấy handle metric cho một thao tác, đăng ký mới nếu chưa có
+    /// Mục đích: Đảm bảo mọi thao tác đều có metric riêng biệt; handle rẻ, giữ được lâu dài
+    /// Thuật toán: Khóa đọc shard trước, chỉ khóa ghi khi tên chưa có
+    pub fn get(&self, name: &str) -> Metric {
+        let shard = self.shard(name);
+        if let Some(metric) = shard.read().unwrap_or_else(|e| e.into_inner()).get(name) {
+            return metric.clone();
+        }
+        shard.write().unwrap_or_else(|e| e.into_inner())
+            .entry(name.to_string())
+            .or_default()
             .clone() // Trả về bản sao để dùng ngoài lock
     }
-    
-    /// Lấy thống kê cho tất cả metrics dưới dạng chuỗi
-    /// Mục đích: Tổng hợp toàn bộ số liệu cho các thao tác
-    /// Thành tựu: Hỗ trợ giám sát tổng thể hệ thống
-    pub async fn stats(&self) -> String {
-        let map = self.map.read().await; // Đọc lock để duyệt map
-        let mut stats = Vec::new(); // Gom các chuỗi thống kê
-        for (name, metric) in map.iter() {
-            stats.push(format!("{}: {}", name, metric.stats())); // Thêm thống kê từng metric
+
+    /// Lấy toàn bộ metric hiện có, sắp theo tên
+    /// Mục đích: Cho phép hiển thị có cấu trúc (bảng, JSON) thay vì chuỗi ghép sẵn
+    pub fn all(&self) -> Vec<(String, Metric)> {
+        let mut all: Vec<_> = self.map.iter()
+            .flat_map(|shard| {
+                let shard = shard.read().unwrap_or_else(|e| e.into_inner());
+                shard.iter().map(|(name, metric)| (name.clone(), metric.clone())).collect::<Vec<_>>()
+            })
+            .collect();
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        all
+    }
+
+    /// Ảnh chụp toàn bộ thao tác và bộ đếm
+    /// Mục đích: Số liệu có cấu trúc cho JSON, so sánh (`Snapshot::diff`) và hiển thị
+    pub fn snapshot(&self) -> Snapshot {
+        let operations = self.all().into_iter().map(|(name, metric)| (name, metric.snapshot())).collect();
+        let mut snapshot = Snapshot { operations, ..Default::default() };
+        for (name, pairs, kind, value) in self.counters() {
+            let key = format!("{}{}", name, pairs);
+            match kind {
+                Kind::Counter => snapshot.counters.insert(key, value),
+                Kind::Gauge => snapshot.gauges.insert(key, value),
+            };
         }
-        stats.join("\n") // Ghép thành một chuỗi duy nhất
+        snapshot
+    }
+
+    /// Lấy thống kê cho tất cả metrics dưới dạng chuỗi (dạng hiển thị của `snapshot`)
+    /// Thành tựu: Hỗ trợ giám sát tổng thể hệ thống
+    pub fn stats(&self) -> String {
+        self.snapshot().to_string()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
     use std::thread;
 
     #[test]
@@ -168,18 +502,62 @@ mod tests {
         // Test ghi nhận thành công
         let start = Instant::now();
         thread::sleep(Duration::from_millis(10)); // Tạo độ trễ nhỏ
-        metric.record(start, false);
+        metric.record(start, &Ok::<(), Error>(()));
         
         // Test ghi nhận thất bại
         let start = Instant::now();
         thread::sleep(Duration::from_millis(5));
-        metric.record(start, true);
+        metric.record(start, &Err::<(), Error>(Error::Timeout));
         
         let stats = metric.stats();
         assert!(stats.contains("Tổng: 2 lần"));
         assert!(stats.contains("1 thành công"));
         assert!(stats.contains("1 thất bại"));
         assert!(stats.contains("Thời gian trung bình:"));
+        assert!(metric.max() >= 10_000_000); // Lần gọi chậm nhất ≥ 10ms
+        assert!(metric.p50() >= 5_000_000);
+        assert_eq!(metric.faults(), vec![("timeout", 1)]);
+    }
+
+    #[test]
+    fn histogram() {
+        let histogram = Histogram::new();
+        for value in 1..=1000u64 {
+            histogram.record(value * 1000); // 1µs..1ms
+        }
+        assert_eq!(histogram.count(), 1000);
+        assert_eq!(histogram.max(), 1_000_000);
+        // Sai số tương đối của bucket ≤ 6,25%, luôn làm tròn lên
+        for (q, exact) in [(0.5, 500_000u64), (0.9, 900_000), (0.99, 990_000)] {
+            let value = histogram.quantile(q);
+            assert!(value >= exact && value <= exact + exact / 16, "q={} → {}", q, value);
+        }
+        assert_eq!(histogram.quantile(1.0), 1_000_000);
+
+        // Các bucket liên tiếp, không chồng lấn, phủ hết dải u64
+        for value in [0u64, 15, 16, 17, 31, 32, 1 << 40, u64::MAX] {
+            let index = Histogram::bucket(value);
+            assert!(Histogram::bound(index) >= value);
+            assert!(index == 0 || Histogram::bound(index - 1) < value);
+        }
+        assert_eq!(Histogram::bucket(u64::MAX), BUCKETS - 1);
+    }
+
+    #[test]
+    fn faults() {
+        let metric = Metric::new();
+        metric.observe(Duration::from_micros(5), None);
+        metric.observe(Duration::from_micros(7), Some(Error::Conflict.kind()));
+        metric.observe(Duration::from_micros(9), Some(Error::Conflict.kind()));
+        metric.observe(Duration::from_micros(3), Some(Error::Missing.kind()));
+        assert_eq!((metric.count(), metric.fail()), (1, 3));
+        assert_eq!(metric.faults(), vec![("missing", 1), ("conflict", 2)]);
+        assert_eq!(metric.max(), 9_000);
+        assert_eq!(metric.average(), 6_000);
+
+        // Loại không có trong `Error::KINDS` không bị gộp vào "missing"
+        metric.observe(Duration::from_micros(1), Some("bogus"));
+        assert_eq!(metric.faults(), vec![("missing", 1), ("conflict", 2), ("other", 1)]);
     }
 
     #[test]
@@ -189,10 +567,10 @@ mod tests {
         // 3 thành công, 1 thất bại
         for _ in 0..3 {
             let start = Instant::now();
-            metric.record(start, false);
+            metric.record(start, &Ok::<(), Error>(()));
         }
         let start = Instant::now();
-        metric.record(start, true);
+        metric.record(start, &Err::<(), Error>(Error::Timeout));
         
         let rate = metric.rate();
         assert!((rate - 0.333333).abs() < 0.001); // 1/3 ≈ 0.333333
@@ -203,21 +581,24 @@ mod tests {
         let registry = Registry::new();
         
         // Test ghi nhận các loại thao tác khác nhau
-        registry.record("insert", false);
-        registry.record("fetch", false);
-        registry.record("update", true);
-        registry.record("delete", false);
+        registry.record("insert", Instant::now(), &Ok::<(), Error>(()));
+        registry.record("fetch", Instant::now(), &Ok::<(), Error>(()));
+        registry.record("update", Instant::now(), &Err::<(), Error>(Error::Conflict));
+        registry.record("delete", Instant::now(), &Ok::<(), Error>(()));
         
-        // Test thống kê async
-        tokio::runtime::Runtime::new().unwrap().block_on(async {
-            let stats = registry.stats().await;
-            assert!(stats.contains("insert"));
-            assert!(stats.contains("fetch"));
-            assert!(stats.contains("update"));
-            assert!(stats.contains("delete"));
-            assert!(stats.contains("1 thành công"));
-            assert!(stats.contains("1 thất bại"));
-        });
+        // Test thống kê
+        let stats = registry.stats();
+        assert!(stats.contains("insert"));
+        assert!(stats.contains("fetch"));
+        assert!(stats.contains("update"));
+        assert!(stats.contains("delete"));
+        assert!(stats.contains("1 thành công"));
+        assert!(stats.contains("1 thất bại"));
+
+        let all = registry.all();
+        let names: Vec<_> = all.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["delete", "fetch", "insert", "update"]);
+        assert_eq!((all[3].1.count(), all[3].1.fail()), (0, 1));
     }
 
     #[test]
@@ -231,8 +612,8 @@ mod tests {
             let handle = std::thread::spawn(move || {
                 for j in 0..100 {
                     let operation = if j % 3 == 0 { "insert" } else if j % 3 == 1 { "fetch" } else { "update" };
-                    let failed = j % 10 == 0; // 10% thất bại
-                    clone.record(operation, failed);
+                    let res = if j % 10 == 0 { Err(Error::Conflict) } else { Ok(()) }; // 10% thất bại
+                    clone.record(operation, Instant::now(), &res);
                 }
             });
             handles.push(handle);
@@ -242,42 +623,46 @@ mod tests {
         for handle in handles {
             handle.join().unwrap();
         }
-        // Đợi thêm để đảm bảo atomic cập nhật xong
-        std::thread::sleep(std::time::Duration::from_millis(200));
-        
-        // Kiểm tra thống kê
-        let stats = std::thread::spawn({
+
+        // Kiểm tra thống kê: mọi lần ghi đều được đếm
+        let snapshot = registry.snapshot();
+
+        let names: Vec<_> = snapshot.operations.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["fetch", "insert", "update"]);
+        let total: u64 = snapshot.operations.values().map(|s| s.total()).sum();
+        assert_eq!(total, 1000);
+        let fail: u64 = snapshot.operations.values().map(|s| s.fail).sum();
+        assert_eq!(fail, 100);
+    }
+
+    /// Ghi từ nhiều task trong runtime cùng lúc với việc đăng ký tên mới và đọc snapshot:
+    /// không mẫu nào bị bỏ
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn contention() {
+        let registry = Registry::new();
+        let handle = registry.get("hot"); // Handle giữ sẵn, không tra tên
+        let mut tasks = Vec::new();
+        for _ in 0..8 {
             let registry = registry.clone();
-            move || {
-                let rt = tokio::runtime::Runtime::new().unwrap();
-                rt.block_on(async move {
-                    registry.stats().await
-                })
-            }
-        }).join().unwrap();
-        
-        println!("Registry concurrent stats: {}", stats);
-        
-        // Kiểm tra từng loại metric có tổng > 0
-        assert!(stats.contains("insert: Tổng:"));
-        assert!(stats.contains("fetch: Tổng:"));
-        assert!(stats.contains("update: Tổng:"));
-        
-        // Kiểm tra có ít nhất một loại có tổng > 0
-        let found = stats.lines().any(|line| {
-            if let Some(idx) = line.find(": Tổng: ") {
-                let rest = &line[idx+8..]; // Bỏ qua ": Tổng: "
-                // Tìm số đầu tiên trong phần còn lại
-                for word in rest.split_whitespace() {
-                    if let Ok(count) = word.parse::<usize>() {
-                        return count > 0;
+            let handle = handle.clone();
+            tasks.push(tokio::spawn(async move {
+                for j in 0..500 {
+                    registry.record(&format!("op{}", j % 20), Instant::now(), &Ok::<(), Error>(()));
+                    handle.observe(Duration::from_nanos(j), None);
+                    if j % 50 == 0 {
+                        let _ = registry.snapshot(); // Đọc xen kẽ
                     }
                 }
-            }
-            false
-        });
-        
-        assert!(found, "Phải có ít nhất một loại metric có tổng > 0");
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let snapshot = registry.snapshot();
+        assert_eq!(snapshot.get("hot").unwrap().count, 4000);
+        let total: u64 = snapshot.operations.iter().filter(|(name, _)| name.starts_with("op")).map(|(_, s)| s.count).sum();
+        assert_eq!(total, 4000);
+        assert_eq!(snapshot.operations.len(), 21);
     }
 
     #[test]
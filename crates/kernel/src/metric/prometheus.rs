@@ -0,0 +1,126 @@
+//! Xuất `Registry` theo định dạng văn bản Prometheus (tương thích OpenMetrics).
+//!
+//! Thao tác storage xuất thành summary độ trễ (giây, phân vị 0.5/0.9/0.99/1) và bộ đếm
+//! kết quả theo loại lỗi; tên `<thực thể>.<thao tác>` tách thành nhãn `entity` và `op`.
+//! Bộ đếm có nhãn (cache, pool, engine, plugin) xuất nguyên tên và nhãn đã đăng ký.
+
+use std::fmt::Write;
+use super::{Kind, Registry};
+
+/// Content-Type của phản hồi `/metrics`
+pub const TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Tên summary độ trễ thao tác
+const DURATION: &str = "kernel_operation_duration_seconds";
+
+/// Tên bộ đếm kết quả thao tác
+const TOTAL: &str = "kernel_operations_total";
+
+/// Mã hóa nhãn theo cú pháp Prometheus: `{a="1",b="2"}`, chuỗi rỗng nếu không có nhãn
+/// Thuật toán: Thoát `\`, `"` và xuống dòng trong giá trị
+pub(crate) fn labels(pairs: &[(&str, &str)]) -> String {
+    if pairs.is_empty() {
+        return String::new();
+    }
+    let body: Vec<String> = pairs.iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")))
+        .collect();
+    format!("{{{}}}", body.join(","))
+}
+
+/// Nhãn của một thao tác: `tasks.insert` → entity="tasks", op="insert"; `insert` → op="insert"
+fn operation(name: &str) -> Vec<(&str, &str)> {
+    match name.rsplit_once('.') {
+        Some((entity, op)) => vec![("entity", entity), ("op", op)],
+        None => vec![("op", name)],
+    }
+}
+
+/// Nano giây sang giây
+fn seconds(nanos: u64) -> f64 {
+    nanos as f64 / 1e9
+}
+
+/// Dựng toàn bộ văn bản xuất của registry
+pub fn render(registry: &Registry) -> String {
+    let mut out = String::new();
+    let all = registry.all();
+
+    if !all.is_empty() {
+        let _ = writeln!(out, "# HELP {} Thời gian thực thi thao tác storage.", DURATION);
+        let _ = writeln!(out, "# TYPE {} summary", DURATION);
+        for (name, metric) in &all {
+            let base = operation(name);
+            for (quantile, value) in [("0.5", metric.p50()), ("0.9", metric.p90()), ("0.99", metric.p99()), ("1", metric.max())] {
+                let mut pairs = base.clone();
+                pairs.push(("quantile", quantile));
+                let _ = writeln!(out, "{}{} {}", DURATION, labels(&pairs), seconds(value));
+            }
+            let latency = metric.latency();
+            let _ = writeln!(out, "{}_sum{} {}", DURATION, labels(&base), seconds(latency.sum()));
+            let _ = writeln!(out, "{}_count{} {}", DURATION, labels(&base), latency.count());
+        }
+
+        let _ = writeln!(out, "# HELP {} Số thao tác storage theo kết quả và loại lỗi.", TOTAL);
+        let _ = writeln!(out, "# TYPE {} counter", TOTAL);
+        for (name, metric) in &all {
+            let mut pairs = operation(name);
+            pairs.push(("result", "ok"));
+            let _ = writeln!(out, "{}{} {}", TOTAL, labels(&pairs), metric.count());
+            for (kind, count) in metric.faults() {
+                let mut pairs = operation(name);
+                pairs.push(("result", "error"));
+                pairs.push(("kind", kind));
+                let _ = writeln!(out, "{}{} {}", TOTAL, labels(&pairs), count);
+            }
+        }
+    }
+
+    let mut last: Option<String> = None;
+    for (name, pairs, kind, value) in registry.counters() {
+        if last.as_deref() != Some(name.as_str()) {
+            let kind = match kind {
+                Kind::Counter => "counter",
+                Kind::Gauge => "gauge",
+            };
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            last = Some(name.clone());
+        }
+        let _ = writeln!(out, "{}{} {}", name, pairs, value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::time::Duration;
+
+    #[test]
+    fn text() {
+        let registry = Registry::new();
+        let metric = registry.get("tasks.insert");
+        metric.observe(Duration::from_millis(2), None);
+        metric.observe(Duration::from_millis(4), Some(Error::Conflict.kind()));
+        registry.get("fetch").observe(Duration::from_micros(500), None);
+        registry.counter("kernel_cache_hits_total", &[("cache", "store")]).add(3);
+        registry.gauge("kernel_pool_free", &[("pool", "a\"b")]).set(7);
+
+        let text = render(&registry);
+        assert!(text.contains("# TYPE kernel_operation_duration_seconds summary\n"));
+        assert!(text.contains("kernel_operation_duration_seconds{entity=\"tasks\",op=\"insert\",quantile=\"1\"} 0.004\n"));
+        assert!(text.contains("kernel_operation_duration_seconds_sum{entity=\"tasks\",op=\"insert\"} 0.006\n"));
+        assert!(text.contains("kernel_operation_duration_seconds_count{entity=\"tasks\",op=\"insert\"} 2\n"));
+        assert!(text.contains("kernel_operations_total{op=\"fetch\",result=\"ok\"} 1\n"));
+        assert!(text.contains("kernel_operations_total{entity=\"tasks\",op=\"insert\",result=\"error\",kind=\"conflict\"} 1\n"));
+        assert!(text.contains("# TYPE kernel_cache_hits_total counter\nkernel_cache_hits_total{cache=\"store\"} 3\n"));
+        assert!(text.contains("# TYPE kernel_pool_free gauge\nkernel_pool_free{pool=\"a\\\"b\"} 7\n"));
+
+        // Mỗi dòng không phải chú thích có dạng `tên{nhãn} giá_trị`
+        for line in text.lines().filter(|l| !l.starts_with('#')) {
+            let (_, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Ảnh chụp metric có cấu trúc, tuần tự hóa được và so sánh được giữa hai thời điểm.
+//!
+//! `Registry::snapshot` chụp toàn bộ thao tác và bộ đếm tại một thời điểm. Hai ảnh chụp
+//! trừ nhau bằng `diff` cho ra số liệu của riêng khoảng giữa chúng; phân vị được tính lại
+//! từ các bucket histogram đi kèm nên vẫn đúng cho khoảng đó.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use super::Histogram;
+
+/// Số liệu của một thao tác tại một thời điểm
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Số lần thành công
+    pub count: u64,
+    /// Số lần thất bại (mọi loại lỗi)
+    pub fail: u64,
+    /// Số lần thất bại theo `Error::kind`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub faults: BTreeMap<String, u64>,
+    /// Tổng thời gian thực thi (nano giây)
+    pub sum: u64,
+    /// Thời gian trung bình mỗi lần (nano giây)
+    pub average: u64,
+    /// Tỷ lệ lỗi (fail/count)
+    pub rate: f64,
+    /// Trung vị độ trễ (nano giây)
+    pub p50: u64,
+    /// Phân vị 90 (nano giây)
+    pub p90: u64,
+    /// Phân vị 99 (nano giây)
+    pub p99: u64,
+    /// Độ trễ lớn nhất (nano giây)
+    pub max: u64,
+    /// Bucket histogram khác rỗng (chỉ số, số mẫu), để trừ và tính lại phân vị
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buckets: Vec<(usize, u64)>,
+}
+
+impl Sample {
+    /// Dựng mẫu từ số liệu gốc, tính các trường dẫn xuất (trung bình, tỷ lệ, phân vị)
+    pub(crate) fn new(count: u64, faults: BTreeMap<String, u64>, sum: u64, max: u64, buckets: Vec<(usize, u64)>) -> Self {
+        let fail = faults.values().sum();
+        let samples: u64 = buckets.iter().map(|(_, n)| n).sum();
+        Self {
+            count,
+            fail,
+            sum,
+            average: sum.checked_div(samples).unwrap_or(0),
+            rate: if count == 0 { 0.0 } else { fail as f64 / count as f64 },
+            p50: Histogram::rank(buckets.clone(), max, 0.50),
+            p90: Histogram::rank(buckets.clone(), max, 0.90),
+            p99: Histogram::rank(buckets.clone(), max, 0.99),
+            max,
+            faults,
+            buckets,
+        }
+    }
+
+    /// Tổng số lần thực thi
+    pub fn total(&self) -> u64 {
+        self.count + self.fail
+    }
+
+    /// Số liệu của riêng khoảng từ `earlier` tới `self`
+    /// Thuật toán: Trừ bộ đếm, tổng và từng bucket; `max` của khoảng lấy cận trên của bucket cao nhất còn mẫu
+    pub fn diff(&self, earlier: &Sample) -> Sample {
+        let mut faults = self.faults.clone();
+        for (kind, n) in faults.iter_mut() {
+            *n = n.saturating_sub(earlier.faults.get(kind).copied().unwrap_or(0));
+        }
+        faults.retain(|_, n| *n > 0);
+        let before: BTreeMap<usize, u64> = earlier.buckets.iter().copied().collect();
+        let buckets: Vec<(usize, u64)> = self.buckets.iter()
+            .map(|(index, n)| (*index, n.saturating_sub(before.get(index).copied().unwrap_or(0))))
+            .filter(|(_, n)| *n > 0)
+            .collect();
+        let max = buckets.last().map(|(index, _)| Histogram::bound(*index).min(self.max)).unwrap_or(0);
+        Sample::new(self.count.saturating_sub(earlier.count), faults, self.sum.saturating_sub(earlier.sum), max, buckets)
+    }
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total() == 0 {
+            return write!(f, "Chưa có dữ liệu"); // Không có dữ liệu để thống kê
+        }
+        write!(
+            f,
+            "Tổng: {} lần ({} thành công, {} thất bại), Thời gian trung bình: {}ns, p50: {}ns, p90: {}ns, p99: {}ns, max: {}ns",
+            self.total(), self.count, self.fail, self.average, self.p50, self.p90, self.p99, self.max
+        )
+    }
+}
+
+/// Ảnh chụp toàn bộ registry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Số liệu từng thao tác, theo tên
+    pub operations: BTreeMap<String, Sample>,
+    /// Bộ đếm chỉ tăng, key là `tên{nhãn}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub counters: BTreeMap<String, u64>,
+    /// Gauge, key là `tên{nhãn}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub gauges: BTreeMap<String, u64>,
+}
+
+impl Snapshot {
+    /// Số liệu của riêng khoảng từ `earlier` tới `self`
+    /// Thao tác và bộ đếm bị trừ; gauge giữ giá trị mới nhất. Thao tác không đổi bị lược bỏ.
+    pub fn diff(&self, earlier: &Snapshot) -> Snapshot {
+        let empty = Sample::default();
+        let operations = self.operations.iter()
+            .map(|(name, sample)| (name.clone(), sample.diff(earlier.operations.get(name).unwrap_or(&empty))))
+            .filter(|(_, sample)| sample.total() > 0)
+            .collect();
+        let counters = self.counters.iter()
+            .map(|(name, n)| (name.clone(), n.saturating_sub(earlier.counters.get(name).copied().unwrap_or(0))))
+            .collect();
+        Snapshot { operations, counters, gauges: self.gauges.clone() }
+    }
+
+    /// Số liệu một thao tác theo tên
+    pub fn get(&self, name: &str) -> Option<&Sample> {
+        self.operations.get(name)
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.operations.iter().map(|(name, sample)| format!("{}: {}", name, sample)).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, Registry};
+    use std::time::Duration;
+
+    #[test]
+    fn diff() {
+        let registry = Registry::new();
+        let metric = registry.get("insert");
+        let hits = registry.counter("kernel_cache_hits_total", &[("cache", "store")]);
+        for _ in 0..10 {
+            metric.observe(Duration::from_micros(10), None);
+        }
+        hits.add(4);
+        let earlier = registry.snapshot();
+
+        metric.observe(Duration::from_millis(5), None);
+        metric.observe(Duration::from_millis(7), Some(Error::Timeout.kind()));
+        registry.get("fetch").observe(Duration::from_micros(1), None);
+        hits.add(1);
+        let later = registry.snapshot();
+
+        let insert = later.get("insert").unwrap();
+        assert_eq!((insert.count, insert.fail, insert.total()), (11, 1, 12));
+        assert!(insert.p50 >= 10_000 && insert.p50 < 10_700); // cận trên bucket, sai số ≤ 6,25%
+        assert_eq!(insert.max, 7_000_000);
+
+        // Khoảng giữa hai ảnh chụp chỉ còn hai lần gọi chậm
+        let delta = later.diff(&earlier);
+        let insert = delta.get("insert").unwrap();
+        assert_eq!((insert.count, insert.fail), (1, 1));
+        assert_eq!(insert.faults.get("timeout"), Some(&1));
+        assert_eq!(insert.sum, 12_000_000);
+        assert!(insert.p50 >= 5_000_000 && insert.p50 < 5_400_000);
+        assert_eq!(insert.max, 7_000_000);
+        assert_eq!(delta.get("fetch").unwrap().count, 1);
+        assert_eq!(delta.counters["kernel_cache_hits_total{cache=\"store\"}"], 1);
+
+        // Không có thay đổi thì diff rỗng
+        assert!(later.diff(&later).operations.is_empty());
+
+        // Tuần tự hóa hai chiều
+        let json = serde_json::to_string(&later).unwrap();
+        let back: super::Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.counters, later.counters);
+        assert_eq!(back.get("insert").unwrap().buckets, later.get("insert").unwrap().buckets);
+        assert!(later.to_string().contains("insert: Tổng: 12 lần (11 thành công, 1 thất bại)"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Cửa sổ thời gian trượt (1m/5m/15m) và vòng lịch sử metric.
+//!
+//! `Registry` giữ một vòng `Snapshot` có đánh dấu thời gian, chụp mỗi `INTERVAL` (qua `tick`
+//! hoặc tác vụ nền `watch`). Số liệu của một cửa sổ là hiệu giữa ảnh chụp hiện tại và ảnh chụp
+//! cũ nhất còn nằm trong cửa sổ, nên có đủ thông lượng, tỷ lệ lỗi và phân vị độ trễ của riêng
+//! khoảng đó thay vì cộng dồn từ lúc khởi động.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use super::{Sample, Snapshot};
+
+/// Khoảng cách giữa hai lần chụp vào vòng lịch sử
+pub const INTERVAL: Duration = Duration::from_secs(10);
+
+/// Các cửa sổ chuẩn: 1, 5 và 15 phút
+pub const SPANS: [Duration; 3] = [Duration::from_secs(60), Duration::from_secs(300), Duration::from_secs(900)];
+
+/// Sức chứa vòng lịch sử: đủ phủ cửa sổ dài nhất cộng một mốc gốc
+pub const CAPACITY: usize = (900 / 10) + 1;
+
+/// Vòng ảnh chụp có đánh dấu thời gian (nano giây Unix), cũ nhất ở đầu
+pub(crate) struct History {
+    frames: VecDeque<(u128, Snapshot)>,
+    capacity: usize,
+}
+
+impl History {
+    /// Vòng rỗng với mốc gốc tại `time`
+    pub(crate) fn new(time: u128) -> Self {
+        let mut frames = VecDeque::with_capacity(CAPACITY);
+        frames.push_back((time, Snapshot::default()));
+        Self { frames, capacity: CAPACITY }
+    }
+
+    /// Thời điểm của ảnh chụp mới nhất
+    pub(crate) fn last(&self) -> u128 {
+        self.frames.back().map(|(time, _)| *time).unwrap_or_default()
+    }
+
+    /// Thêm ảnh chụp, bỏ ảnh cũ nhất khi đầy
+    pub(crate) fn push(&mut self, time: u128, snapshot: Snapshot) {
+        self.frames.push_back((time, snapshot));
+        while self.frames.len() > self.capacity {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Mốc so sánh cho cửa sổ bắt đầu từ `since`: ảnh cũ nhất không sớm hơn `since`,
+    /// hoặc ảnh mới nhất nếu mọi ảnh đều sớm hơn (chưa chụp lại từ lâu)
+    pub(crate) fn base(&self, since: u128) -> &(u128, Snapshot) {
+        self.frames.iter()
+            .find(|(time, _)| *time >= since)
+            .or(self.frames.back())
+            .expect("vòng lịch sử luôn có mốc gốc")
+    }
+
+    /// Hiệu giữa từng cặp ảnh chụp liên tiếp
+    pub(crate) fn frames(&self) -> Vec<Frame> {
+        self.frames.iter()
+            .zip(self.frames.iter().skip(1))
+            .map(|((_, before), (time, after))| Frame { time: *time, snapshot: after.diff(before) })
+            .collect()
+    }
+}
+
+/// Một khoảng trong vòng lịch sử: số liệu phát sinh giữa ảnh chụp trước và `time`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    /// Thời điểm chụp (nano giây Unix)
+    pub time: u128,
+    /// Số liệu của riêng khoảng này
+    pub snapshot: Snapshot,
+}
+
+/// Số liệu một thao tác trong một cửa sổ
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pace {
+    /// Tổng số lần thực thi
+    pub total: u64,
+    /// Số lần thất bại
+    pub fail: u64,
+    /// Thông lượng (lần/giây)
+    pub throughput: f64,
+    /// Tỷ lệ lỗi trên tổng số lần (0..=1)
+    pub errors: f64,
+    /// Độ trễ trung bình (nano giây)
+    pub average: u64,
+    /// Trung vị độ trễ (nano giây)
+    pub p50: u64,
+    /// Phân vị 90 (nano giây)
+    pub p90: u64,
+    /// Phân vị 99 (nano giây)
+    pub p99: u64,
+    /// Độ trễ lớn nhất (nano giây, theo cận trên bucket)
+    pub max: u64,
+}
+
+impl Pace {
+    /// Dựng từ mẫu của cửa sổ và độ dài thực của nó (giây)
+    fn new(sample: &Sample, elapsed: f64) -> Self {
+        let total = sample.total();
+        Self {
+            total,
+            fail: sample.fail,
+            throughput: if elapsed > 0.0 { total as f64 / elapsed } else { 0.0 },
+            errors: if total == 0 { 0.0 } else { sample.fail as f64 / total as f64 },
+            average: sample.average,
+            p50: sample.p50,
+            p90: sample.p90,
+            p99: sample.p99,
+            max: sample.max,
+        }
+    }
+}
+
+/// Số liệu trong một cửa sổ thời gian
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Window {
+    /// Độ dài danh nghĩa (giây)
+    pub span: u64,
+    /// Độ dài thực giữa mốc so sánh và hiện tại (giây); ngắn hơn `span` khi lịch sử chưa đủ
+    pub elapsed: f64,
+    /// Số liệu từng thao tác có phát sinh trong cửa sổ
+    pub operations: BTreeMap<String, Pace>,
+}
+
+impl Window {
+    /// Cửa sổ từ mốc `(time, earlier)` tới ảnh chụp `later` tại `now`
+    pub(crate) fn new(span: Duration, now: u128, base: &(u128, Snapshot), later: &Snapshot) -> Self {
+        let elapsed = now.saturating_sub(base.0) as f64 / 1e9;
+        let operations = later.diff(&base.1).operations.iter()
+            .map(|(name, sample)| (name.clone(), Pace::new(sample, elapsed)))
+            .collect();
+        Self { span: span.as_secs(), elapsed, operations }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::time::Manual;
+    use crate::{Error, Registry};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn windows() {
+        let clock = Arc::new(Manual::new(0));
+        let registry = Registry::new().clock(clock.clone());
+        let metric = registry.get("insert");
+
+        // 15 phút đầu: 1 lần/giây, nhanh, không lỗi
+        for _ in 0..90 {
+            for _ in 0..10 {
+                metric.observe(Duration::from_micros(10), None);
+            }
+            clock.advance(super::INTERVAL);
+            registry.tick();
+        }
+        // Phút cuối: chậm và một nửa lỗi
+        for _ in 0..6 {
+            for i in 0..10 {
+                let fault = if i % 2 == 0 { Some(Error::Timeout.kind()) } else { None };
+                metric.observe(Duration::from_millis(3), fault);
+            }
+            clock.advance(super::INTERVAL);
+            registry.tick();
+        }
+
+        let windows = registry.windows();
+        assert_eq!(windows.iter().map(|w| w.span).collect::<Vec<_>>(), vec![60, 300, 900]);
+
+        let minute = &windows[0].operations["insert"];
+        assert_eq!(windows[0].elapsed, 60.0);
+        assert_eq!((minute.total, minute.fail), (60, 30));
+        assert_eq!(minute.throughput, 1.0);
+        assert_eq!(minute.errors, 0.5);
+        assert!(minute.p50 >= 3_000_000);
+
+        let five = &windows[1].operations["insert"];
+        assert_eq!((five.total, five.fail), (300, 30));
+        assert_eq!(five.errors, 0.1);
+        assert!(five.p50 < 11_000); // Phần lớn vẫn nhanh
+
+        // Vòng lịch sử giữ đủ 15 phút; mỗi khung là số liệu của riêng 10 giây
+        let history = registry.history();
+        assert_eq!(history.len(), super::CAPACITY - 1);
+        assert_eq!(history.last().unwrap().snapshot.get("insert").unwrap().total(), 10);
+        assert_eq!(history.last().unwrap().time, 960 * 1_000_000_000);
+
+        // Không có gì mới: cửa sổ 1 phút vẫn tính từ ảnh chụp trong cửa sổ
+        clock.advance(Duration::from_secs(30));
+        let minute = &registry.window(Duration::from_secs(60)).operations["insert"];
+        assert_eq!(minute.total, 30);
+    }
+
+    #[test]
+    fn dense() {
+        let clock = Arc::new(Manual::new(0));
+        let registry = Registry::new().clock(clock.clone());
+        let metric = registry.get("insert");
+
+        // tick mỗi giây trong 20 phút: chỉ một ảnh mỗi INTERVAL được giữ, cửa sổ 15 phút vẫn đủ dài
+        for _ in 0..1200 {
+            metric.observe(Duration::from_micros(10), None);
+            clock.advance(Duration::from_secs(1));
+            registry.tick();
+        }
+        let history = registry.history();
+        assert_eq!(history.len(), super::CAPACITY - 1);
+        assert_eq!(history.last().unwrap().snapshot.get("insert").unwrap().total(), 10);
+
+        let fifteen = &registry.window(Duration::from_secs(900));
+        assert_eq!(fifteen.elapsed, 900.0);
+        assert_eq!(fifteen.operations["insert"].total, 900);
+    }
+}
@@ -19,6 +19,8 @@
 //! ```
 //! 
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 use crate::config::Config;
 
 /// Trait cho Plugin system
@@ -40,6 +42,85 @@ pub trait Plugin: Send + Sync {
     
     /// Lấy description plugin
     fn description(&self) -> &str;
+
+    /// Tên (theo tên đăng ký trong engine) các plugin phải được khởi tạo trước plugin này
+    fn depends(&self) -> &[&str] {
+        &[]
+    }
+}
+
+/// Lỗi vòng đời plugin
+#[derive(Debug, thiserror::Error)]
+pub enum Failure {
+    /// Plugin phụ thuộc vào plugin chưa được đăng ký
+    #[error("plugin '{plugin}' phụ thuộc '{dependency}' nhưng '{dependency}' chưa được đăng ký")]
+    Missing { plugin: String, dependency: String },
+
+    /// Các plugin phụ thuộc vòng lẫn nhau
+    #[error("phụ thuộc vòng giữa các plugin: {}", .0.join(", "))]
+    Cycle(Vec<String>),
+
+    /// `init` của plugin trả về lỗi
+    #[error("plugin '{plugin}' khởi tạo thất bại: {message}")]
+    Init { plugin: String, message: String },
+
+    /// `init` hoặc `shutdown` của plugin không xong trong giới hạn `Config.addon`
+    #[error("plugin '{plugin}' quá hạn {limit:?} khi {stage}")]
+    Timeout { plugin: String, stage: &'static str, limit: Duration },
+}
+
+/// Thứ tự khởi tạo theo đồ thị phụ thuộc: mỗi plugin đứng sau mọi plugin nó phụ thuộc
+/// Thuật toán: Kahn trên `BTreeMap`, các plugin cùng mức theo thứ tự tên để kết quả ổn định.
+/// Thiếu phụ thuộc trả `Failure::Missing`; còn nút chưa xếp được nghĩa là có vòng, trả `Failure::Cycle`
+/// chỉ gồm các nút nằm trên vòng, không kèm các nút chỉ phụ thuộc vào vòng.
+pub fn order<'a>(graph: impl IntoIterator<Item = (&'a str, &'a [&'a str])>) -> Result<Vec<String>, Failure> {
+    let graph: BTreeMap<&str, &[&str]> = graph.into_iter().collect();
+    let mut pending: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut users: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (name, deps) in &graph {
+        let deps: BTreeSet<&str> = deps.iter().copied().collect();
+        for dep in &deps {
+            if !graph.contains_key(dep) {
+                return Err(Failure::Missing { plugin: name.to_string(), dependency: dep.to_string() });
+            }
+            users.entry(*dep).or_default().push(name);
+        }
+        pending.insert(name, deps.len());
+    }
+    let mut ready: BTreeSet<&str> = pending.iter().filter(|(_, n)| **n == 0).map(|(name, _)| *name).collect();
+    let mut order = Vec::with_capacity(graph.len());
+    while let Some(name) = ready.pop_first() {
+        pending.remove(name);
+        for user in users.get(name).into_iter().flatten() {
+            if let Some(n) = pending.get_mut(user) {
+                *n -= 1;
+                if *n == 0 {
+                    ready.insert(user);
+                }
+            }
+        }
+        order.push(name.to_string());
+    }
+    if !pending.is_empty() {
+        let cycle = pending.keys().filter(|name| cyclic(&graph, name)).map(|name| name.to_string()).collect();
+        return Err(Failure::Cycle(cycle));
+    }
+    Ok(order)
+}
+
+/// `start` có quay lại được chính nó theo cạnh phụ thuộc hay không
+fn cyclic(graph: &BTreeMap<&str, &[&str]>, start: &str) -> bool {
+    let mut seen = BTreeSet::new();
+    let mut stack: Vec<&str> = graph[start].to_vec();
+    while let Some(name) = stack.pop() {
+        if name == start {
+            return true;
+        }
+        if seen.insert(name) {
+            stack.extend(graph[name].iter().copied());
+        }
+    }
+    false
 }
 
 /// Plugin Registry quản lý tất cả plugins
@@ -149,4 +230,28 @@ mod tests {
         assert!(plugin.is_some());
         assert_eq!(registry.count(), 0);
     }
+
+    #[test]
+    fn graph() {
+        // db <- cache <- api, db <- log; cùng mức thì theo tên
+        let graph: Vec<(&str, &[&str])> = vec![("api", &["cache", "log"]), ("cache", &["db"]), ("db", &[]), ("log", &["db"])];
+        assert_eq!(order(graph).unwrap(), vec!["db", "cache", "log", "api"]);
+
+        let graph: Vec<(&str, &[&str])> = vec![("api", &["auth"])];
+        let error = order(graph).unwrap_err();
+        assert!(matches!(&error, Failure::Missing { plugin, dependency } if plugin == "api" && dependency == "auth"));
+
+        let graph: Vec<(&str, &[&str])> = vec![("a", &["b"]), ("b", &["c"]), ("c", &["a"]), ("d", &[])];
+        let error = order(graph).unwrap_err();
+        assert!(matches!(&error, Failure::Cycle(names) if names == &["a", "b", "c"]));
+        assert_eq!(error.to_string(), "phụ thuộc vòng giữa các plugin: a, b, c");
+
+        // Nút phụ thuộc vào vòng (e, f) và nút nằm giữa hai vòng (g) không bị báo là nằm trên vòng
+        let graph: Vec<(&str, &[&str])> = vec![
+            ("a", &["b"]), ("b", &["a"]), ("e", &["a"]), ("f", &["e"]),
+            ("g", &["b"]), ("h", &["g", "i"]), ("i", &["h"]), ("s", &["s"]),
+        ];
+        let error = order(graph).unwrap_err();
+        assert!(matches!(&error, Failure::Cycle(names) if names == &["a", "b", "h", "i", "s"]));
+    }
 } 
\ No newline at end of file
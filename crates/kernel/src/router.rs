@@ -30,6 +30,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use async_trait::async_trait;
+use crate::metric::{prometheus, Registry};
 
 /// Định nghĩa trait Handler cho router
 #[async_trait]
@@ -71,6 +72,8 @@ pub struct Response {
 pub struct Router {
     /// Route registry
     routes: Arc<RwLock<HashMap<String, Arc<dyn Handler>>>>,
+    /// Registry metric phục vụ route `/metrics`
+    metric: Registry,
 }
 
 impl Router {
@@ -78,8 +81,20 @@ impl Router {
     pub fn new() -> Self {
         Self {
             routes: Arc::new(RwLock::new(HashMap::new())),
+            metric: Registry::new(),
         }
     }
+
+    /// Dùng registry cho trước cho route `/metrics` (ví dụ registry của storage actor)
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.metric = registry;
+        self
+    }
+
+    /// Registry đang phục vụ route `/metrics`
+    pub fn metrics(&self) -> &Registry {
+        &self.metric
+    }
     
     /// Đăng ký route
     pub async fn register(&self, path: String, handler: Arc<dyn Handler>) {
@@ -137,8 +152,13 @@ impl Router {
         self.register("/health".to_string(), health).await;
         
         // Metrics route
-        let metrics = Arc::new(Metrics);
+        let metrics = Arc::new(Metrics { registry: self.metric.clone() });
         self.register("/metrics".to_string(), metrics).await;
+
+        // Cửa sổ 1m/5m/15m và vòng lịch sử metric (JSON)
+        let trend = Arc::new(Trend { registry: self.metric.clone() });
+        self.register("/metrics/windows".to_string(), trend.clone()).await;
+        self.register("/metrics/history".to_string(), trend).await;
     }
 }
 
@@ -162,16 +182,40 @@ impl Handler for Health {
     }
 }
 
-/// Metrics handler
-struct Metrics;
+/// Metrics handler: xuất registry theo định dạng văn bản Prometheus
+struct Metrics {
+    registry: Registry,
+}
 
 #[async_trait::async_trait]
 impl Handler for Metrics {
     async fn handle(&self, _request: Request) -> Result<Response, Box<dyn std::error::Error>> {
+        let body = self.registry.render();
         Ok(Response {
             status: 200,
-            headers: HashMap::new(),
-            body: b"metrics".to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), prometheus::TYPE.to_string())]),
+            body: body.into_bytes(),
+        })
+    }
+}
+
+/// Trend handler: cửa sổ trượt (`/metrics/windows`) hoặc vòng lịch sử (`/metrics/history`) dạng JSON
+struct Trend {
+    registry: Registry,
+}
+
+#[async_trait::async_trait]
+impl Handler for Trend {
+    async fn handle(&self, request: Request) -> Result<Response, Box<dyn std::error::Error>> {
+        let body = if request.path.ends_with("/history") {
+            serde_json::to_vec(&self.registry.history())?
+        } else {
+            serde_json::to_vec(&self.registry.windows())?
+        };
+        Ok(Response {
+            status: 200,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body,
         })
     }
 }
@@ -250,4 +294,53 @@ mod tests {
         assert_eq!(response.status, 200);
         assert_eq!(response.body, b"OK");
     }
+
+    #[tokio::test]
+    async fn metrics() {
+        let registry = Registry::new();
+        registry.record("insert", std::time::Instant::now(), &Ok::<(), crate::Error>(()));
+        let router = Router::new().registry(registry);
+        router.init().await.unwrap();
+
+        let request = Request {
+            path: "/metrics".to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: vec![],
+        };
+
+        let response = router.route(request).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.headers["Content-Type"], prometheus::TYPE);
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("kernel_operations_total{op=\"insert\",result=\"ok\"} 1\n"));
+    }
+
+    #[tokio::test]
+    async fn trend() {
+        let clock = Arc::new(crate::storage::time::Manual::new(0));
+        let registry = Registry::new().clock(clock.clone());
+        registry.record("insert", std::time::Instant::now(), &Ok::<(), crate::Error>(()));
+        clock.advance(std::time::Duration::from_secs(10));
+        registry.tick();
+        let router = Router::new().registry(registry);
+        router.init().await.unwrap();
+
+        let get = |path: &str| Request {
+            path: path.to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: vec![],
+        };
+        let response = router.route(get("/metrics/windows")).await.unwrap();
+        let windows: Vec<crate::metric::Window> = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].operations["insert"].total, 1);
+        assert_eq!(windows[0].operations["insert"].throughput, 0.1);
+
+        let response = router.route(get("/metrics/history")).await.unwrap();
+        let history: Vec<crate::metric::Frame> = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].snapshot.get("insert").unwrap().count, 1);
+    }
 } 
\ No newline at end of file
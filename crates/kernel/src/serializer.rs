@@ -17,8 +17,13 @@
 //! let parsed: Data = system.parse(&json).unwrap();
 //! assert_eq!(data, parsed);
 //! ```
+//!
+//! `Codec` mở rộng `Serializer` cho backend lưu trữ: một codec dùng chung cho mọi thực thể,
+//! cho phép chọn Bincode (gọn, nhanh) hoặc JSON (đọc được bằng tay) theo từng store.
+//! Một thực thể có thể ghi đè codec của store qua `Entity::CODEC` (xem `Encoding`).
 
-use serde::{Serialize, Deserialize};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use crate::error::Error;
 
 /// Trait tuần tự hóa generic
 pub trait Serializer<T> {
@@ -29,7 +34,36 @@ pub trait Serializer<T> {
     fn deserialize(&self, bytes: &[u8]) -> Result<T, Box<dyn std::error::Error>>;
 }
 
+/// Codec giá trị cho backend lưu trữ
+///
+/// Khác với `Serializer<T>` (gắn với một kiểu), `Codec` mã hóa được mọi kiểu serde,
+/// nên backend chỉ cần giữ một codec cho toàn bộ thực thể. Các triển khai tái sử dụng
+/// `Serializer` và chuẩn hóa lỗi về `Error` của hệ thống.
+pub trait Codec: Clone + Send + Sync + 'static {
+    /// Tên codec, dùng cho log và chẩn đoán
+    fn name(&self) -> &'static str;
+
+    /// Mã hóa giá trị thành bytes
+    fn encode<T: Serialize + DeserializeOwned>(&self, data: &T) -> Result<Vec<u8>, Error>;
+
+    /// Giải mã bytes thành giá trị
+    fn decode<T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error>;
+}
+
+/// Chuyển lỗi động từ `Serializer` về `Error`, giữ nguyên loại lỗi khi nhận diện được
+fn fault(err: Box<dyn std::error::Error>) -> Error {
+    let err = match err.downcast::<serde_json::Error>() {
+        Ok(e) => return Error::Json(*e),
+        Err(e) => e,
+    };
+    match err.downcast::<bincode::Error>() {
+        Ok(e) => Error::Format(*e),
+        Err(e) => Error::Parse(e.to_string()),
+    }
+}
+
 /// Serializer cho JSON
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Json;
 
 impl<T: Serialize + for<'de> Deserialize<'de>> Serializer<T> for Json {
@@ -45,7 +79,22 @@ impl<T: Serialize + for<'de> Deserialize<'de>> Serializer<T> for Json {
     }
 }
 
+impl Codec for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn encode<T: Serialize + DeserializeOwned>(&self, data: &T) -> Result<Vec<u8>, Error> {
+        Serializer::<T>::serialize(self, data).map_err(fault)
+    }
+
+    fn decode<T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        Serializer::<T>::deserialize(self, bytes).map_err(fault)
+    }
+}
+
 /// Serializer cho Bincode
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Bincode;
 
 impl<T: Serialize + for<'de> Deserialize<'de>> Serializer<T> for Bincode {
@@ -60,6 +109,52 @@ impl<T: Serialize + for<'de> Deserialize<'de>> Serializer<T> for Bincode {
     }
 }
 
+impl Codec for Bincode {
+    fn name(&self) -> &'static str {
+        "bincode"
+    }
+
+    fn encode<T: Serialize + DeserializeOwned>(&self, data: &T) -> Result<Vec<u8>, Error> {
+        Serializer::<T>::serialize(self, data).map_err(fault)
+    }
+
+    fn decode<T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        Serializer::<T>::deserialize(self, bytes).map_err(fault)
+    }
+}
+
+/// Codec chọn theo thực thể, ghi đè codec của store qua `Entity::CODEC`
+///
+/// Là enum thay vì tham số kiểu để `Entity` khai báo được bằng hằng số.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    Bincode,
+}
+
+impl Codec for Encoding {
+    fn name(&self) -> &'static str {
+        match self {
+            Encoding::Json => Json.name(),
+            Encoding::Bincode => Bincode.name(),
+        }
+    }
+
+    fn encode<T: Serialize + DeserializeOwned>(&self, data: &T) -> Result<Vec<u8>, Error> {
+        match self {
+            Encoding::Json => Json.encode(data),
+            Encoding::Bincode => Bincode.encode(data),
+        }
+    }
+
+    fn decode<T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        match self {
+            Encoding::Json => Json.decode(bytes),
+            Encoding::Bincode => Bincode.decode(bytes),
+        }
+    }
+}
+
 /// Hệ thống tuần tự hóa cho framework
 ///
 /// Hỗ trợ encode/decode JSON, Bincode động.
@@ -149,6 +244,26 @@ mod test {
         assert_eq!(data, parsed);
     }
 
+    #[test]
+    fn codec() {
+        let data = Data {
+            name: "test".to_string(),
+            value: 42,
+        };
+
+        // Cùng một giá trị đi qua cả hai codec
+        let bytes = Json.encode(&data).unwrap();
+        assert_eq!(Json.decode::<Data>(&bytes).unwrap(), data);
+        assert!(String::from_utf8(bytes).unwrap().contains("\"name\":\"test\""));
+
+        let bytes = Bincode.encode(&data).unwrap();
+        assert_eq!(Bincode.decode::<Data>(&bytes).unwrap(), data);
+
+        // Lỗi giải mã giữ đúng loại
+        assert!(matches!(Json.decode::<Data>(b"{"), Err(Error::Json(_))));
+        assert!(matches!(Bincode.decode::<Data>(&[1]), Err(Error::Format(_))));
+    }
+
     #[test]
     fn system() {
         let system = System::new();
@@ -6,7 +6,8 @@ use crate::error::Error;
 use crate::storage::sled::Inner;
 use tokio::sync::{mpsc, oneshot};
 use async_trait::async_trait;
-use crate::storage::actor::message::Message;
+use crate::storage::actor::message::{Batch, Message, Pair, Plan};
+use crate::storage::stats::Stats;
 use crate::storage::actor::state::{Cell, State};
 pub mod message;
 pub mod handler;
@@ -23,13 +24,13 @@ impl Actor {
     pub(crate) fn new(inner: Inner) -> Self {
         let (tx, mut rx) = mpsc::channel::<message::Message>(128);
         let metric = inner.metric.clone();
-        let shared = metric.clone();
+        let meters = handler::Meters::new(&metric);
         let state = Cell::new(State::Idle);
         let cell = state.clone();
         thread::spawn(move || {
             cell.set(State::Running);
             while let Some(msg) = rx.blocking_recv() {
-                handler::handle(msg, &inner, &shared);
+                handler::handle(msg, &inner, &meters);
             }
             cell.set(State::Stopped);
         });
@@ -72,6 +73,24 @@ pub trait Actorable: Send + Sync + Clone + 'static {
     async fn query(&self) -> Result<Vec<Vec<u8>>, Error>;
     async fn mass(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), Error>;
     async fn keys(&self) -> Result<Vec<Vec<u8>>, Error>;
+    /// Đọc một khóa trong tree được đặt tên
+    async fn get(&self, tree: &str, key: Vec<u8>) -> Result<Option<Vec<u8>>, Error>;
+    /// Đọc nhiều khóa trong một tree, giữ nguyên thứ tự
+    async fn many(&self, tree: &str, keys: Vec<Vec<u8>>) -> Result<Vec<Option<Vec<u8>>>, Error>;
+    /// Áp dụng nguyên tử một lô ghi, trả `Error::Conflict` nếu điều kiện không thỏa
+    async fn apply(&self, batch: Batch) -> Result<(), Error>;
+    /// Đọc rồi ghi một khóa trong cùng một message: `plan` nhận giá trị hiện hành và dựng lô ghi
+    async fn modify(&self, tree: &str, key: Vec<u8>, plan: Plan) -> Result<Vec<u8>, Error>;
+    /// Quét tree theo tiền tố, bắt đầu sau `after`, tối đa `limit` cặp
+    async fn scan(&self, tree: &str, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<Pair>, Error>;
+    /// Bắt đầu dựng tree vật lý `target` thay cho tree logic `tree`, ghi kép cho tới khi swap
+    async fn begin(&self, tree: &str, target: &str) -> Result<(), Error>;
+    /// Chốt tree đang dựng, trả về tên tree cũ đã bị xóa
+    async fn swap(&self, tree: &str) -> Result<String, Error>;
+    /// Hủy việc dựng tree
+    async fn cancel(&self, tree: &str) -> Result<(), Error>;
+    /// Thống kê dung lượng mọi tree thực thể
+    async fn stats(&self) -> Result<Stats, Error>;
 }
 
 #[async_trait]
@@ -118,6 +137,60 @@ impl Actorable for Handle {
         self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
         rx.await.map_err(|_| Error::Aborted)?
     }
+    async fn get(&self, tree: &str, key: Vec<u8>) -> Result<Option<Vec<u8>>, Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Get { tree: tree.to_string(), key, respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn many(&self, tree: &str, keys: Vec<Vec<u8>>) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Many { tree: tree.to_string(), keys, respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn apply(&self, batch: Batch) -> Result<(), Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Apply { batch, respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn modify(&self, tree: &str, key: Vec<u8>, plan: Plan) -> Result<Vec<u8>, Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Modify { tree: tree.to_string(), key, plan, respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn scan(&self, tree: &str, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<Pair>, Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Scan { tree: tree.to_string(), prefix, after, limit, respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn begin(&self, tree: &str, target: &str) -> Result<(), Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Begin { tree: tree.to_string(), target: target.to_string(), respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn swap(&self, tree: &str) -> Result<String, Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Swap { tree: tree.to_string(), respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn cancel(&self, tree: &str) -> Result<(), Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Cancel { tree: tree.to_string(), respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
+    async fn stats(&self) -> Result<Stats, Error> {
+        let (tx, rx) = oneshot::channel();
+        let msg = Message::Stats { respond: tx };
+        self.sender.send(msg).await.map_err(|_| Error::Aborted)?;
+        rx.await.map_err(|_| Error::Aborted)?
+    }
 }
 
 // TODO: Triển khai các hàm gửi message bất đồng bộ cho Handle 
@@ -163,7 +236,7 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
         // Kiểm tra metrics
-        let stats = handle.metrics().stats().await;
+        let stats = handle.metrics().stats();
         println!("Metrics stats: {}", stats);
         
         assert!(stats.contains("insert"));
@@ -171,6 +244,11 @@ mod tests {
         assert!(stats.contains("update"));
         assert!(stats.contains("delete"));
         assert!(stats.contains("1 thành công")); // Mỗi operation thành công
+
+        // Thời gian được đo từ lúc actor nhận message, không còn ≈ 0ns
+        let insert = handle.metrics().get("insert");
+        assert!(insert.max() > 0);
+        assert!(insert.p99() >= insert.p50());
     }
 
     #[tokio::test]
@@ -197,7 +275,7 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
         // Kiểm tra metrics
-        let stats = handle.metrics().stats().await;
+        let stats = handle.metrics().stats();
         println!("Error handling metrics: {}", stats);
         
         assert!(stats.contains("insert"));
@@ -235,7 +313,7 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
         // Kiểm tra metrics
-        let stats = handle.metrics().stats().await;
+        let stats = handle.metrics().stats();
         println!("Bulk operations metrics: {}", stats);
         
         assert!(stats.contains("mass"));
@@ -281,7 +359,7 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
 
         // Kiểm tra metrics
-        let stats = handle.metrics().stats().await;
+        let stats = handle.metrics().stats();
         println!("Concurrent metrics: {}", stats);
         
         // Kiểm tra từng loại metric có tổng > 0
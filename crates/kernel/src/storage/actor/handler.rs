@@ -1,9 +1,60 @@
-use crate::storage::actor::message::Message;
-use crate::storage::sled::Inner;
-use crate::metric::Registry;
+use crate::storage::actor::message::{Batch, Message, Pair, Op, Plan};
+use crate::storage::change;
+use crate::storage::sled::{index, Inner, ROUTES};
+use crate::storage::search::terms;
+use crate::storage::stats::{Stats, Tree};
+use crate::metric::{Metric, Registry};
 use crate::error::Error;
+use sled::transaction::{ConflictableTransactionError, TransactionError, Transactional};
+use std::ops::Bound;
+use std::time::Instant;
 
-pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
+/// Handle metric đăng ký sẵn cho từng thao tác của actor, tránh tra tên mỗi message
+pub(crate) struct Meters {
+    insert: Metric,
+    fetch: Metric,
+    update: Metric,
+    delete: Metric,
+    query: Metric,
+    mass: Metric,
+    keys: Metric,
+    get: Metric,
+    many: Metric,
+    apply: Metric,
+    modify: Metric,
+    scan: Metric,
+    begin: Metric,
+    swap: Metric,
+    cancel: Metric,
+    stats: Metric,
+}
+
+impl Meters {
+    pub(crate) fn new(registry: &Registry) -> Self {
+        Self {
+            insert: registry.get("insert"),
+            fetch: registry.get("fetch"),
+            update: registry.get("update"),
+            delete: registry.get("delete"),
+            query: registry.get("query"),
+            mass: registry.get("mass"),
+            keys: registry.get("keys"),
+            get: registry.get("get"),
+            many: registry.get("many"),
+            apply: registry.get("apply"),
+            modify: registry.get("modify"),
+            scan: registry.get("scan"),
+            begin: registry.get("begin"),
+            swap: registry.get("swap"),
+            cancel: registry.get("cancel"),
+            stats: registry.get("stats"),
+        }
+    }
+}
+
+/// Xử lý một message; thời gian mỗi thao tác được đo từ lúc actor nhận message
+pub(crate) fn handle(msg: Message, inner: &Inner, meters: &Meters) {
+    let start = Instant::now();
     match msg {
         Message::Insert { key, value, respond } => {
             let res = inner.db.insert(&key[..], &value[..])
@@ -12,7 +63,7 @@ pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
             if let Err(ref e) = res {
                 tracing::error!(?e, "Lỗi khi insert vào db");
             }
-            metric.record("insert", res.is_err());
+            meters.insert.record(start, &res);
             if respond.send(res).is_err() {
                 tracing::error!("Lỗi gửi kết quả insert qua channel oneshot");
             }
@@ -24,7 +75,7 @@ pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
             if let Err(ref e) = res {
                 tracing::error!(?e, "Lỗi khi fetch từ db");
             }
-            metric.record("fetch", res.is_err());
+            meters.fetch.record(start, &res);
             if respond.send(res).is_err() {
                 tracing::error!("Lỗi gửi kết quả fetch qua channel oneshot");
             }
@@ -36,7 +87,7 @@ pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
             if let Err(ref e) = res {
                 tracing::error!(?e, "Lỗi khi update vào db");
             }
-            metric.record("update", res.is_err());
+            meters.update.record(start, &res);
             if respond.send(res).is_err() {
                 tracing::error!("Lỗi gửi kết quả update qua channel oneshot");
             }
@@ -48,7 +99,7 @@ pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
             if let Err(ref e) = res {
                 tracing::error!(?e, "Lỗi khi delete từ db");
             }
-            metric.record("delete", res.is_err());
+            meters.delete.record(start, &res);
             if respond.send(res).is_err() {
                 tracing::error!("Lỗi gửi kết quả delete qua channel oneshot");
             }
@@ -85,7 +136,7 @@ pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
                 tracing::debug!("Query thành công, trả về {} items", result.len());
                 Ok(result)
             };
-            metric.record("query", res.is_err());
+            meters.query.record(start, &res);
             if respond.send(res).is_err() {
                 tracing::error!("Lỗi gửi kết quả query qua channel oneshot");
             }
@@ -100,7 +151,7 @@ pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
                 }
             }
             let res = if ok { Ok(()) } else { Err(Error::Aborted) };
-            metric.record("mass", res.is_err());
+            meters.mass.record(start, &res);
             if respond.send(res).is_err() {
                 tracing::error!("Lỗi gửi kết quả mass qua channel oneshot");
             }
@@ -120,10 +171,306 @@ pub(crate) fn handle(msg: Message, inner: &Inner, metric: &Registry) {
             } else {
                 Ok(result)
             };
-            metric.record("keys", res.is_err());
+            meters.keys.record(start, &res);
             if respond.send(res).is_err() {
                 tracing::error!("Lỗi gửi kết quả keys qua channel oneshot");
             }
         }
+        Message::Get { tree, key, respond } => {
+            let res = inner.db.open_tree(inner.routes.read(&tree))
+                .and_then(|t| t.get(&key[..]))
+                .map(|opt| opt.map(|v| v.to_vec()))
+                .map_err(Error::Store);
+            if let Err(ref e) = res {
+                tracing::error!(?e, tree, "Lỗi khi get từ tree");
+            }
+            meters.get.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả get qua channel oneshot");
+            }
+        }
+        Message::Many { tree, keys, respond } => {
+            let res = inner.db.open_tree(inner.routes.read(&tree))
+                .and_then(|t| keys.iter().map(|k| t.get(&k[..]).map(|opt| opt.map(|v| v.to_vec()))).collect())
+                .map_err(Error::Store);
+            if let Err(ref e) = res {
+                tracing::error!(?e, tree, "Lỗi khi đọc nhiều khóa từ tree");
+            }
+            meters.many.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả many qua channel oneshot");
+            }
+        }
+        Message::Apply { batch, respond } => {
+            let res = apply(inner, &batch);
+            if let Err(ref e) = res {
+                tracing::warn!(?e, "Lô ghi không được áp dụng");
+            }
+            meters.apply.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả apply qua channel oneshot");
+            }
+        }
+        Message::Modify { tree, key, plan, respond } => {
+            let res = modify(inner, &tree, key, plan);
+            if let Err(ref e) = res {
+                tracing::warn!(?e, tree, "Không thể sửa bản ghi");
+            }
+            meters.modify.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả modify qua channel oneshot");
+            }
+        }
+        Message::Scan { tree, prefix, after, limit, respond } => {
+            let res = scan(inner, &tree, &prefix, after, limit);
+            if let Err(ref e) = res {
+                tracing::error!(?e, tree, "Lỗi khi quét tree");
+            }
+            meters.scan.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả scan qua channel oneshot");
+            }
+        }
+        Message::Begin { tree, target, respond } => {
+            let res = begin(inner, &tree, target);
+            if let Err(ref e) = res {
+                tracing::error!(?e, tree, "Không thể bắt đầu dựng tree");
+            }
+            meters.begin.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả begin qua channel oneshot");
+            }
+        }
+        Message::Swap { tree, respond } => {
+            let res = swap(inner, &tree);
+            if let Err(ref e) = res {
+                tracing::error!(?e, tree, "Không thể chuyển tree");
+            }
+            meters.swap.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả swap qua channel oneshot");
+            }
+        }
+        Message::Cancel { tree, respond } => {
+            let res = cancel(inner, &tree);
+            if let Err(ref e) = res {
+                tracing::error!(?e, tree, "Không thể hủy dựng tree");
+            }
+            meters.cancel.record(start, &res);
+            if respond.send(res).is_err() {
+                tracing::error!("Lỗi gửi kết quả cancel qua channel oneshot");
+            }
+        }
+        Message::Stats { respond } => {
+            // Actor chỉ mở các tree cần đếm; việc duyệt O(số bản ghi) chạy trên thread riêng,
+            // nên các message khác không phải chờ thống kê xong
+            let metric = meters.stats.clone();
+            let report = move |res: Result<Stats, Error>| {
+                if let Err(ref e) = res {
+                    tracing::error!(?e, "Lỗi khi thống kê store");
+                }
+                metric.record(start, &res);
+                if respond.send(res).is_err() {
+                    tracing::error!("Lỗi gửi kết quả stats qua channel oneshot");
+                }
+            };
+            match open(inner) {
+                Ok((trees, db)) => {
+                    let spawned = std::thread::Builder::new()
+                        .name("stats".to_string())
+                        .spawn(move || report(stats(trees, &db)));
+                    if let Err(e) = spawned {
+                        tracing::error!(?e, "Không thể tạo thread thống kê");
+                    }
+                }
+                Err(e) => report(Err(e)),
+            }
+        }
+    }
+}
+
+/// Áp dụng lô ghi trong một giao dịch trải trên mọi tree liên quan.
+/// Điều kiện đọc tree vật lý hiện hành; thao tác ghi đi tới mọi tree vật lý của tuyến.
+fn apply(inner: &Inner, batch: &Batch) -> Result<(), Error> {
+    if batch.empty() {
+        return Ok(());
+    }
+    let mut names: Vec<String> = batch.trees().iter()
+        .flat_map(|tree| inner.routes.write(tree))
+        .collect();
+    names.sort();
+    names.dedup();
+    let trees = names.iter()
+        .map(|name| inner.db.open_tree(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let refs: Vec<&sled::Tree> = trees.iter().collect();
+    let slot = |tree: &str| names.iter().position(|n| n == tree).unwrap_or_default();
+    let expects: Vec<_> = batch.expect.iter()
+        .map(|e| (slot(&inner.routes.read(&e.tree)), e))
+        .collect();
+    let ops: Vec<_> = batch.ops.iter()
+        .flat_map(|op| {
+            let tree = match op { Op::Put { tree, .. } | Op::Remove { tree, .. } => tree };
+            inner.routes.write(tree).into_iter().map(move |name| (slot(&name), op))
+        })
+        .collect();
+    // Mục nhật ký: tree dấu để đọc, các tree dấu và tree nhật ký để ghi
+    let writes = |tree: &str| inner.routes.write(tree).iter().map(|name| slot(name)).collect::<Vec<_>>();
+    let logs: Vec<_> = batch.log.iter()
+        .map(|log| {
+            let stamps = change::stamps(&log.name);
+            (slot(&inner.routes.read(&stamps)), writes(&stamps), writes(&change::journal(&log.name)), log)
+        })
+        .collect();
+    let counter = slot(change::COUNTER);
+    let res = refs[..].transaction(|views| {
+        for (i, expect) in &expects {
+            let current = views[*i].get(&expect.key[..])?;
+            if current.as_deref() != expect.value.as_deref() {
+                return Err(ConflictableTransactionError::Abort(()));
+            }
+        }
+        for (i, op) in &ops {
+            match op {
+                Op::Put { key, value, .. } => { views[*i].insert(&key[..], &value[..])?; }
+                Op::Remove { key, .. } => { views[*i].remove(&key[..])?; }
+            }
+        }
+        if logs.is_empty() {
+            return Ok(());
+        }
+        // Vị trí cấp ngay trong giao dịch: tăng ngặt theo thứ tự commit, không nhỏ hơn đồng hồ hệ thống
+        let last = views[counter].get(change::LAST)?.as_deref().and_then(change::time).unwrap_or(0);
+        let position = (last + 1).max(crate::storage::time::now());
+        views[counter].insert(change::LAST, change::position(position))?;
+        for (read, stamps, journals, log) in &logs {
+            let at = log.at.unwrap_or(position);
+            // Mục cũ của khóa đã bị thay thế: gỡ luôn, nhật ký chỉ giữ thay đổi cuối của mỗi khóa
+            let old = views[*read].get(&log.key[..])?.as_deref().and_then(change::time);
+            for i in journals {
+                if let Some(old) = old.filter(|old| *old != at) {
+                    views[*i].remove(change::entry(old, &log.key))?;
+                }
+                views[*i].insert(change::entry(at, &log.key), change::value(log))?;
+            }
+            for i in stamps {
+                views[*i].insert(&log.key[..], change::stamp(at, log.deleted))?;
+            }
+        }
+        Ok(())
+    });
+    match res {
+        Ok(()) => Ok(()),
+        Err(TransactionError::Abort(())) => Err(Error::Conflict),
+        Err(TransactionError::Storage(e)) => Err(Error::Store(e)),
+    }
+}
+
+/// Đọc giá trị hiện hành của khóa, dựng lô từ nó và áp dụng; trả về giá trị mới
+fn modify(inner: &Inner, tree: &str, key: Vec<u8>, plan: Plan) -> Result<Vec<u8>, Error> {
+    let old = inner.db.open_tree(inner.routes.read(tree))?.get(&key[..])?.map(|v| v.to_vec());
+    let (batch, value) = plan(old)?;
+    apply(inner, &batch)?;
+    Ok(value)
+}
+
+/// Bắt đầu dựng tree mới cho một tuyến; tree đích được làm trống trước
+fn begin(inner: &Inner, tree: &str, target: String) -> Result<(), Error> {
+    let mut route = inner.routes.get(tree);
+    if route.next.is_some() {
+        return Err(Error::Conflict);
+    }
+    inner.db.open_tree(&target)?.clear()?;
+    route.next = Some(target);
+    inner.routes.set(tree, route);
+    Ok(())
+}
+
+/// Chốt tree đang dựng làm tree hiện hành, lưu tuyến rồi xóa tree cũ
+fn swap(inner: &Inner, tree: &str) -> Result<String, Error> {
+    let mut route = inner.routes.get(tree);
+    let next = route.next.take().ok_or(Error::Missing)?;
+    inner.db.open_tree(ROUTES)?.insert(tree.as_bytes(), next.as_bytes())?;
+    let old = std::mem::replace(&mut route.current, next);
+    inner.routes.set(tree, route);
+    inner.db.drop_tree(&old)?;
+    Ok(old)
+}
+
+/// Bỏ tree đang dựng, tuyến giữ nguyên tree hiện hành
+fn cancel(inner: &Inner, tree: &str) -> Result<(), Error> {
+    let mut route = inner.routes.get(tree);
+    if let Some(next) = route.next.take() {
+        inner.routes.set(tree, route);
+        inner.db.drop_tree(&next)?;
+    }
+    Ok(())
+}
+
+/// Tree dữ liệu cần thống kê: tên, tree dữ liệu và tree chỉ mục hiện hành (nếu có)
+type Counted = Vec<(String, sled::Tree, Option<sled::Tree>)>;
+
+/// Mở mọi tree thực thể cùng tree chỉ mục hiện hành của nó (chạy trên actor, không duyệt dữ liệu).
+/// Tree nội bộ `__*` và tree phụ của một thực thể có mặt (`index`, `terms` cùng tree đang dựng của chúng,
+/// `journal`, `stamps`) bị bỏ qua theo đúng tên, nên thực thể tên `my.terms.log` vẫn được đếm.
+fn open(inner: &Inner) -> Result<(Counted, sled::Db), Error> {
+    let names: Vec<String> = inner.db.tree_names().iter()
+        .map(|n| String::from_utf8_lossy(n).into_owned())
+        .collect();
+    let mut auxiliary = std::collections::HashSet::new();
+    for name in &names {
+        auxiliary.extend(inner.routes.write(&index(name)));
+        auxiliary.extend(inner.routes.write(&terms(name)));
+        auxiliary.extend([index(name), terms(name), change::journal(name), change::stamps(name)]);
+    }
+    let mut trees = Vec::new();
+    for name in names.iter().filter(|n| !n.starts_with("__") && !auxiliary.contains(*n)) {
+        let physical = inner.routes.read(&index(name));
+        let index = match names.contains(&physical) {
+            true => Some(inner.db.open_tree(&physical)?),
+            false => None,
+        };
+        trees.push((name.clone(), inner.db.open_tree(name)?, index));
+    }
+    Ok((trees, inner.db.clone()))
+}
+
+/// Duyệt các tree đã mở, đếm bản ghi và byte (chạy ngoài actor)
+fn stats(counted: Counted, db: &sled::Db) -> Result<Stats, Error> {
+    let mut trees = Vec::new();
+    for (name, data, index) in counted {
+        let mut tree = Tree { name, ..Default::default() };
+        for kv in data.iter() {
+            let (k, v) = kv?;
+            tree.records += 1;
+            tree.keys += k.len() as u64;
+            tree.values += v.len() as u64;
+        }
+        for kv in index.iter().flat_map(|index| index.iter()) {
+            let (k, v) = kv?;
+            tree.entries += 1;
+            tree.index += (k.len() + v.len()) as u64;
+        }
+        trees.push(tree);
+    }
+    trees.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Stats { trees, disk: db.size_on_disk()? })
+}
+
+/// Quét tối đa `limit` cặp khóa/giá trị có tiền tố `prefix`, bắt đầu ngay sau `after`
+fn scan(inner: &Inner, tree: &str, prefix: &[u8], after: Option<Vec<u8>>, limit: usize) -> Result<Vec<Pair>, Error> {
+    let tree = inner.db.open_tree(inner.routes.read(tree))?;
+    let start = match after {
+        Some(after) if after.as_slice() >= prefix => Bound::Excluded(after),
+        _ => Bound::Included(prefix.to_vec()),
+    };
+    let mut result = Vec::new();
+    for kv in tree.range((start, Bound::Unbounded)) {
+        let (k, v) = kv?;
+        if !k.starts_with(prefix) || result.len() >= limit {
+            break;
+        }
+        result.push((k.to_vec(), v.to_vec()));
     }
-} 
\ No newline at end of file
+    Ok(result)
+}
@@ -1,7 +1,15 @@
 //! Enum đại diện cho các message gửi tới actor lưu trữ
 use crate::error::Error;
+use crate::storage::change;
+use crate::storage::stats::Stats;
 use tokio::sync::oneshot;
 
+/// Một cặp khóa/giá trị thô đọc từ tree
+pub type Pair = (Vec<u8>, Vec<u8>);
+
+/// Hàm dựng lô ghi từ giá trị hiện hành của một khóa, trả kèm giá trị mới để phản hồi
+pub type Plan = Box<dyn FnOnce(Option<Vec<u8>>) -> Result<(Batch, Vec<u8>), Error> + Send>;
+
 pub enum Message {
     Insert {
         key: Vec<u8>,
@@ -31,4 +39,143 @@ pub enum Message {
     Keys {
         respond: oneshot::Sender<Result<Vec<Vec<u8>>, Error>>,
     },
-} 
\ No newline at end of file
+    /// Đọc một khóa trong tree được đặt tên
+    Get {
+        tree: String,
+        key: Vec<u8>,
+        respond: oneshot::Sender<Result<Option<Vec<u8>>, Error>>,
+    },
+    /// Đọc nhiều khóa trong cùng một tree bằng một message
+    Many {
+        tree: String,
+        keys: Vec<Vec<u8>>,
+        respond: oneshot::Sender<Result<Vec<Option<Vec<u8>>>, Error>>,
+    },
+    /// Áp dụng nguyên tử một lô ghi trên nhiều tree
+    Apply {
+        batch: Batch,
+        respond: oneshot::Sender<Result<(), Error>>,
+    },
+    /// Đọc giá trị hiện hành rồi áp dụng lô do `plan` dựng ngay trên thread actor.
+    /// Không có thao tác ghi nào chen giữa lúc đọc và lúc ghi nên không thể xung đột.
+    Modify {
+        tree: String,
+        key: Vec<u8>,
+        plan: Plan,
+        respond: oneshot::Sender<Result<Vec<u8>, Error>>,
+    },
+    /// Quét tree theo thứ tự khóa, lọc theo tiền tố, bắt đầu sau `after`
+    Scan {
+        tree: String,
+        prefix: Vec<u8>,
+        after: Option<Vec<u8>>,
+        limit: usize,
+        respond: oneshot::Sender<Result<Vec<Pair>, Error>>,
+    },
+    /// Bắt đầu dựng tree vật lý `target` cho tree logic: mọi thao tác ghi được ghi kép vào cả hai
+    Begin {
+        tree: String,
+        target: String,
+        respond: oneshot::Sender<Result<(), Error>>,
+    },
+    /// Chuyển tree logic sang tree đang dựng và xóa tree cũ, trả về tên tree cũ
+    Swap {
+        tree: String,
+        respond: oneshot::Sender<Result<String, Error>>,
+    },
+    /// Hủy việc dựng và xóa tree đang dựng
+    Cancel {
+        tree: String,
+        respond: oneshot::Sender<Result<(), Error>>,
+    },
+    /// Thống kê dung lượng mọi tree thực thể
+    Stats {
+        respond: oneshot::Sender<Result<Stats, Error>>,
+    },
+}
+
+/// Một thao tác ghi thô trên một tree
+#[derive(Debug, Clone)]
+pub enum Op {
+    Put { tree: String, key: Vec<u8>, value: Vec<u8> },
+    Remove { tree: String, key: Vec<u8> },
+}
+
+/// Điều kiện: giá trị hiện tại của khóa phải đúng bằng `value` (None = chưa tồn tại)
+#[derive(Debug, Clone)]
+pub struct Expect {
+    pub tree: String,
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
+}
+
+/// Một mục nhật ký thay đổi (xem `storage::change`), được đóng dấu vị trí lúc commit
+#[derive(Debug, Clone)]
+pub struct Log {
+    /// Tên thực thể (`Entity::NAME`)
+    pub name: String,
+    /// Khóa bản ghi đã tuần tự hóa
+    pub key: Vec<u8>,
+    /// Khóa chỉ mục của bản ghi lúc thay đổi
+    pub index: Vec<u8>,
+    /// Thay đổi là một lần xóa
+    pub deleted: bool,
+    /// Vị trí cho trước (dựng lại nhật ký cho dữ liệu cũ); None thì lấy vị trí kế tiếp lúc commit
+    pub at: Option<u128>,
+}
+
+/// Lô ghi được actor áp dụng trong một giao dịch sled duy nhất.
+///
+/// Mọi điều kiện `expect` được kiểm tra trước khi ghi; chỉ cần một điều kiện sai
+/// là cả lô bị hủy với `Error::Conflict`, nên dữ liệu và chỉ mục không bao giờ lệch nhau.
+/// Các mục `log` nhận vị trí nhật ký ngay trong giao dịch đó, nên thứ tự vị trí là thứ tự commit.
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    pub ops: Vec<Op>,
+    pub expect: Vec<Expect>,
+    pub log: Vec<Log>,
+}
+
+impl Batch {
+    /// Ghi một cặp khóa/giá trị
+    pub fn put(&mut self, tree: &str, key: Vec<u8>, value: Vec<u8>) -> &mut Self {
+        self.ops.push(Op::Put { tree: tree.to_string(), key, value });
+        self
+    }
+
+    /// Xóa một khóa
+    pub fn remove(&mut self, tree: &str, key: Vec<u8>) -> &mut Self {
+        self.ops.push(Op::Remove { tree: tree.to_string(), key });
+        self
+    }
+
+    /// Thêm điều kiện về giá trị hiện tại của một khóa
+    pub fn expect(&mut self, tree: &str, key: Vec<u8>, value: Option<Vec<u8>>) -> &mut Self {
+        self.expect.push(Expect { tree: tree.to_string(), key, value });
+        self
+    }
+
+    /// Ghi một mục nhật ký thay đổi
+    pub fn log(&mut self, log: Log) -> &mut Self {
+        self.log.push(log);
+        self
+    }
+
+    /// Lô không có gì để làm
+    pub fn empty(&self) -> bool {
+        self.ops.is_empty() && self.expect.is_empty() && self.log.is_empty()
+    }
+
+    /// Tên các tree mà lô chạm tới, không trùng lặp
+    pub fn trees(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.ops.iter()
+            .map(|op| match op { Op::Put { tree, .. } | Op::Remove { tree, .. } => tree.clone() })
+            .chain(self.expect.iter().map(|e| e.tree.clone()))
+            .chain(self.log.iter().flat_map(|log| [change::stamps(&log.name), change::journal(&log.name)]))
+            .chain((!self.log.is_empty()).then(|| change::COUNTER.to_string()))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
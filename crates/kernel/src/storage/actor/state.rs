@@ -1,7 +1,9 @@
 //! Module quản lý trạng thái nội bộ cho Actor
 //! - State: Idle, Running, Stopped, Error
 //! - Cell: Arc<Mutex<State>> để chia sẻ trạng thái giữa thread và API
+//! - Routes: ánh xạ tên tree logic sang tree vật lý, phục vụ reindex trực tuyến
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 /// Trạng thái động của Actor
@@ -32,4 +34,49 @@ impl Cell {
     }
 }
 
-// Hiện tại Actor không có state riêng biệt ngoài state của Sled/Handle, file này để chuẩn bị cho việc mở rộng stateful actor nếu cần. 
\ No newline at end of file
+/// Tuyến của một tree logic: tree vật lý đang phục vụ và tree đang được dựng (nếu có)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub current: String,
+    pub next: Option<String>,
+}
+
+/// Bảng tuyến của actor. Chỉ thread actor đọc/ghi nên Mutex không bao giờ tranh chấp.
+///
+/// Tree không có tuyến được dùng trực tiếp với chính tên của nó.
+#[derive(Default)]
+pub struct Routes(Mutex<HashMap<String, Route>>);
+
+impl Routes {
+    /// Tạo bảng tuyến từ các cặp (logic, vật lý) đã lưu
+    pub fn new(saved: impl IntoIterator<Item = (String, String)>) -> Self {
+        let map = saved.into_iter()
+            .map(|(name, current)| (name, Route { current, next: None }))
+            .collect();
+        Self(Mutex::new(map))
+    }
+
+    /// Tree vật lý dùng để đọc
+    pub fn read(&self, tree: &str) -> String {
+        self.0.lock().unwrap().get(tree).map(|r| r.current.clone()).unwrap_or_else(|| tree.to_string())
+    }
+
+    /// Các tree vật lý phải nhận một thao tác ghi (tree hiện hành và tree đang dựng)
+    pub fn write(&self, tree: &str) -> Vec<String> {
+        match self.0.lock().unwrap().get(tree) {
+            Some(route) => std::iter::once(route.current.clone()).chain(route.next.clone()).collect(),
+            None => vec![tree.to_string()],
+        }
+    }
+
+    /// Tuyến hiện tại của một tree logic
+    pub fn get(&self, tree: &str) -> Route {
+        self.0.lock().unwrap().get(tree).cloned()
+            .unwrap_or_else(|| Route { current: tree.to_string(), next: None })
+    }
+
+    /// Đặt tuyến cho một tree logic
+    pub fn set(&self, tree: &str, route: Route) {
+        self.0.lock().unwrap().insert(tree.to_string(), route);
+    }
+} 
\ No newline at end of file
@@ -10,6 +10,7 @@ use std::sync::Arc; // Arc: Chia sẻ ownership map giữa các thread
 use tokio::sync::RwLock; // RwLock: Đảm bảo thread-safe cho map
 use std::time::{Duration, Instant}; // Duration, Instant: Quản lý TTL và thời điểm hết hạn
 use std::hash::Hash; // Hash: Đảm bảo key có thể dùng cho HashMap
+use crate::metric::{Counter, Registry}; // Counter: Đếm hit/miss, số entry khi được gắn registry
 
 /// Cache entry với thời gian hết hạn
 /// Mục đích: Lưu trữ dữ liệu và thời điểm hết hạn cho từng entry
@@ -32,6 +33,12 @@ where
     /// Thời gian sống mặc định (Time-To-Live)
     /// Mục đích: Xác định thời gian dữ liệu tồn tại trong cache
     ttl: Duration,
+    /// Số lần đọc trúng cache
+    hit: Counter,
+    /// Số lần đọc trượt (không có hoặc đã hết hạn)
+    miss: Counter,
+    /// Số entry hiện có (gauge)
+    size: Counter,
 }
 
 impl<K, V> Cache<K, V>
@@ -45,15 +52,30 @@ where
         Self {
             map: Arc::new(RwLock::new(HashMap::new())), // Map rỗng, thread-safe
             ttl,
+            hit: Counter::default(),
+            miss: Counter::default(),
+            size: Counter::default(),
         }
     }
+
+    /// Gắn bộ đếm hit/miss và gauge số entry vào registry với nhãn `cache="<name>"`
+    /// Mục đích: Cho phép `/metrics` quan sát hiệu quả cache
+    pub fn metric(mut self, registry: &Registry, name: &str) -> Self {
+        let pairs = [("cache", name)];
+        self.hit = registry.counter("kernel_cache_hits_total", &pairs);
+        self.miss = registry.counter("kernel_cache_misses_total", &pairs);
+        self.size = registry.gauge("kernel_cache_entries", &pairs);
+        self
+    }
     
     /// Lưu dữ liệu vào cache với key và TTL mặc định
     /// Thuật toán: Ghi đè entry cũ nếu key đã tồn tại, cập nhật thời điểm hết hạn mới
     pub async fn set(&self, key: K, data: V) {
         let exp = Instant::now() + self.ttl; // Tính thời điểm hết hạn
         let entry = Entry { data, exp };
-        self.map.write().await.insert(key, entry); // Ghi entry vào map
+        let mut map = self.map.write().await;
+        map.insert(key, entry); // Ghi entry vào map
+        self.size.set(map.len() as u64);
     }
     
     /// Lấy dữ liệu từ cache nếu chưa hết hạn
@@ -62,23 +84,30 @@ where
         let mut map = self.map.write().await; // Ghi lock để có thể xóa nếu hết hạn
         if let Some(entry) = map.get(key) {
             if entry.exp > Instant::now() {
+                self.hit.add(1);
                 return Some(entry.data.clone()); // Trả về bản sao dữ liệu nếu còn hạn
             }
             map.remove(key); // Xóa entry hết hạn
+            self.size.set(map.len() as u64);
         }
+        self.miss.add(1);
         None
     }
     
     /// Xóa dữ liệu khỏi cache theo key
     /// Mục đích: Cho phép chủ động loại bỏ entry khỏi cache
     pub async fn del(&self, key: &K) {
-        self.map.write().await.remove(key);
+        let mut map = self.map.write().await;
+        map.remove(key);
+        self.size.set(map.len() as u64);
     }
     
     /// Dọn dẹp các entry đã hết hạn khỏi cache
     /// Thuật toán: Duyệt toàn bộ map, chỉ giữ lại các entry còn hạn
     pub async fn clean(&self) {
         let now = Instant::now();
-        self.map.write().await.retain(|_, entry| entry.exp > now);
+        let mut map = self.map.write().await;
+        map.retain(|_, entry| entry.exp > now);
+        self.size.set(map.len() as u64);
     }
 } 
\ No newline at end of file
@@ -0,0 +1,299 @@
+//! Kiểm tra tính nhất quán giữa tree dữ liệu và tree chỉ mục (fsck).
+//!
+//! Ghi chỉ mục, xóa và ghi hàng loạt có thể bị áp dụng dở dang (crash giữa chừng, dữ liệu
+//! cũ từ phiên bản trước, codec thay đổi...). Tree dữ liệu xếp theo khóa chính còn chỉ mục xếp
+//! theo khóa chỉ mục, nên trước hết chỉ mục đúng được dựng vào một tree tạm như `reindex`
+//! (từng lô, có `expect` trên bản ghi nguồn, ghi kép trong lúc dựng). Sau đó chỉ mục hiện hành
+//! và tree tạm được duyệt song song theo thứ tự khóa, từng trang một: bộ nhớ không phụ thuộc số bản ghi.
+//! `check` xóa tree tạm; `repair` chuyển tuyến sang tree tạm trong một message của actor.
+//!
+//! ```rust,ignore
+//! let report = store.check::<task::Entry>().await?;
+//! if !report.clean() {
+//!     store.repair::<task::Entry>().await?;
+//! }
+//! ```
+//!
+//! Khi có ghi đồng thời, báo cáo có thể kèm mục vừa đổi giữa hai lần quét trang, nhưng chỉ mục
+//! sau `repair` luôn đúng vì tree tạm nhận mọi thao tác ghi trong lúc dựng.
+//! Không chạy đồng thời với `reindex` của cùng thực thể: tree chỉ mục chỉ có một tree đang dựng,
+//! lần gọi sau nhận `Error::Conflict`.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::serializer::Codec;
+use crate::storage::actor::message::Pair;
+use crate::storage::actor::Actorable;
+use crate::storage::entity::Entity;
+use crate::storage::sled::{index, Sled};
+use crate::storage::time::now;
+use crate::Error;
+
+/// Số cặp khóa/giá trị đọc mỗi lần quét
+const PAGE: usize = 1024;
+
+/// Kết quả kiểm tra một loại thực thể
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Tên thực thể (`Entity::NAME`)
+    pub entity: &'static str,
+    /// Số bản ghi trong tree dữ liệu
+    pub records: usize,
+    /// Số mục trong tree chỉ mục
+    pub entries: usize,
+    /// Khóa chỉ mục không ứng với bản ghi nào
+    pub orphans: Vec<Vec<u8>>,
+    /// Khóa chỉ mục tính từ bản ghi nhưng không có trong tree chỉ mục
+    pub missing: Vec<Vec<u8>>,
+    /// Khóa bản ghi không giải mã được
+    pub broken: Vec<Vec<u8>>,
+    /// Khóa chỉ mục có tóm tắt khác `Entity::summary()`
+    pub stale: Vec<Vec<u8>>,
+    /// Số mục chỉ mục đã sửa (chỉ khác 0 sau `repair`)
+    pub fixed: usize,
+}
+
+impl Report {
+    /// Không phát hiện lỗi nào
+    pub fn clean(&self) -> bool {
+        self.orphans.is_empty() && self.missing.is_empty() && self.broken.is_empty() && self.stale.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} bản ghi, {} mục chỉ mục, {} mồ côi, {} thiếu chỉ mục, {} không giải mã được, {} lệch tóm tắt",
+            self.entity, self.records, self.entries, self.orphans.len(), self.missing.len(), self.broken.len(), self.stale.len()
+        )?;
+        if self.fixed > 0 {
+            write!(f, ", đã sửa {}", self.fixed)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: Codec> Sled<C> {
+    /// Kiểm tra tree dữ liệu và tree chỉ mục của `E`; chỉ mục hiện hành không bị thay đổi.
+    pub async fn check<E: Entity>(&self) -> Result<Report, Error> {
+        let tree = index(E::NAME);
+        let report = self.rebuild::<E>(&tree).await?;
+        self.handle.cancel(&tree).await?;
+        Ok(report)
+    }
+
+    /// Kiểm tra rồi dựng lại chỉ mục của `E`: chỉ mục đúng vừa dựng thay cho chỉ mục hiện hành,
+    /// nên mục mồ côi biến mất và mục thiếu hoặc lệch được ghi lại.
+    /// Bản ghi không giải mã được chỉ được báo cáo, không bị xóa.
+    pub async fn repair<E: Entity>(&self) -> Result<Report, Error> {
+        let tree = index(E::NAME);
+        let mut report = self.rebuild::<E>(&tree).await?;
+        report.fixed = report.orphans.len() + report.missing.len() + report.stale.len();
+        if report.fixed == 0 {
+            self.handle.cancel(&tree).await?;
+        } else {
+            self.handle.swap(&tree).await?;
+        }
+        tracing::info!(entity = E::NAME, fixed = report.fixed, "Đã dựng lại chỉ mục");
+        Ok(report)
+    }
+
+    /// Dựng chỉ mục đúng của `E` vào tree tạm rồi so với chỉ mục hiện hành.
+    /// Khi trả về `Ok`, tree tạm vẫn đang nhận ghi kép: caller phải `swap` hoặc `cancel`.
+    async fn rebuild<E: Entity>(&self, tree: &str) -> Result<Report, Error> {
+        let target = format!("{}.{}", tree, now());
+        self.handle.begin(tree, &target).await?;
+        match self.compare::<E>(tree, &target).await {
+            Ok(report) => Ok(report),
+            Err(e) => {
+                self.handle.cancel(tree).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Ghi chỉ mục đúng vào `target`, sau đó duyệt song song `tree` và `target` theo thứ tự khóa
+    async fn compare<E: Entity>(&self, tree: &str, target: &str) -> Result<Report, Error> {
+        let (written, broken) = self.backfill::<E>(target).await?;
+        let mut report = Report { entity: E::NAME, records: written + broken.len(), broken, ..Default::default() };
+
+        let mut actual = Walk::new(tree);
+        let mut wanted = Walk::new(target);
+        let mut left = actual.next(self).await?;
+        let mut right = wanted.next(self).await?;
+        loop {
+            let order = match (&left, &right) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((key, _)), Some((expected, _))) => key.cmp(expected),
+            };
+            match order {
+                // Mục chỉ mục không bản ghi nào tạo ra
+                Ordering::Less => {
+                    report.entries += 1;
+                    report.orphans.extend(left.map(|(key, _)| key));
+                    left = actual.next(self).await?;
+                }
+                // Bản ghi tạo ra mục chỉ mục chưa có
+                Ordering::Greater => {
+                    report.missing.extend(right.map(|(key, _)| key));
+                    right = wanted.next(self).await?;
+                }
+                Ordering::Equal => {
+                    report.entries += 1;
+                    if let (Some((key, value)), Some((_, summary))) = (left, right) {
+                        if value != summary {
+                            report.stale.push(key);
+                        }
+                    }
+                    left = actual.next(self).await?;
+                    right = wanted.next(self).await?;
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Đọc lần lượt từng cặp của một tree theo thứ tự khóa, mỗi lần quét một trang
+pub(crate) struct Walk<'a> {
+    tree: &'a str,
+    after: Option<Vec<u8>>,
+    page: VecDeque<Pair>,
+    done: bool,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(tree: &'a str) -> Self {
+        Self { tree, after: None, page: VecDeque::new(), done: false }
+    }
+
+    /// Cặp kế tiếp, hoặc `None` khi hết tree
+    pub(crate) async fn next<C: Codec>(&mut self, store: &Sled<C>) -> Result<Option<Pair>, Error> {
+        if self.page.is_empty() && !self.done {
+            let page = store.handle.scan(self.tree, Vec::new(), self.after.take(), PAGE).await?;
+            self.done = page.len() < PAGE;
+            self.after = page.last().map(|(key, _)| key.clone());
+            self.page = page.into();
+        }
+        Ok(self.page.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::actor::message::Batch;
+    use crate::{Id, Storage};
+    use serde::{Deserialize, Serialize};
+    use tempfile::tempdir;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Thing {
+        id: Id,
+        name: String,
+    }
+
+    impl Entity for Thing {
+        const NAME: &'static str = "things";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index {
+            let mut index = self.name.clone().into_bytes();
+            index.extend_from_slice(self.id.as_bytes());
+            index
+        }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    #[tokio::test]
+    async fn clean() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let things: Vec<_> = (0..50).map(|i| Thing { id: Id::new_v4(), name: format!("t{}", i) }).collect();
+        store.mass(Box::new(things.clone().into_iter())).await.unwrap();
+        store.update::<Thing, _>(things[0].id, |mut t| { t.name = "changed".to_string(); t }).await.unwrap();
+        store.delete::<Thing>(things[1].id).await.unwrap();
+
+        let report = store.check::<Thing>().await.unwrap();
+        assert!(report.clean(), "{}", report);
+        assert_eq!(report.records, 49);
+        assert_eq!(report.entries, 49);
+    }
+
+    #[tokio::test]
+    async fn repair() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let a = Thing { id: Id::new_v4(), name: "a".to_string() };
+        let b = Thing { id: Id::new_v4(), name: "b".to_string() };
+        store.insert(a.clone()).await.unwrap();
+        store.insert(b.clone()).await.unwrap();
+
+        // Làm hỏng có chủ đích: mục mồ côi, mục thiếu, tóm tắt lệch, bản ghi hỏng
+        let tree = index(Thing::NAME);
+        let mut batch = Batch::default();
+        batch.put(&tree, b"ghost".to_vec(), bincode::serialize("ghost").unwrap());
+        batch.remove(&tree, a.index());
+        batch.put(&tree, b.index(), bincode::serialize("sai").unwrap());
+        batch.put(Thing::NAME, b"junk".to_vec(), vec![0xff]);
+        store.handle.apply(batch).await.unwrap();
+
+        let report = store.check::<Thing>().await.unwrap();
+        assert_eq!(report.orphans, vec![b"ghost".to_vec()]);
+        assert_eq!(report.missing, vec![a.index()]);
+        assert_eq!(report.stale, vec![b.index()]);
+        assert_eq!(report.broken, vec![b"junk".to_vec()]);
+
+        let report = store.repair::<Thing>().await.unwrap();
+        assert_eq!(report.fixed, 3);
+        let report = store.check::<Thing>().await.unwrap();
+        assert!(report.orphans.is_empty() && report.missing.is_empty() && report.stale.is_empty());
+        assert_eq!(report.broken.len(), 1);
+
+        let mut names: Vec<_> = store.query::<Thing>(crate::Query::default()).await.unwrap().map(|s| s.unwrap()).collect();
+        names.sort();
+        assert_eq!(names, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn concurrent() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let things: Vec<_> = (0..3000).map(|i| Thing { id: Id::new_v4(), name: format!("t{:04}", i) }).collect();
+        store.mass(Box::new(things.clone().into_iter())).await.unwrap();
+        let mut batch = Batch::default();
+        batch.put(&index(Thing::NAME), b"ghost".to_vec(), bincode::serialize("ghost").unwrap());
+        store.handle.apply(batch).await.unwrap();
+
+        // Ghi đồng thời trong lúc sửa không bị ghi đè bằng tóm tắt cũ
+        let writer = {
+            let store = store.clone();
+            let things = things.clone();
+            tokio::spawn(async move {
+                for thing in things.iter().step_by(10) {
+                    store.update::<Thing, _>(thing.id, |mut t| { t.name.push_str("-new"); t }).await.unwrap();
+                }
+            })
+        };
+        let report = store.repair::<Thing>().await.unwrap();
+        writer.await.unwrap();
+        // Báo cáo có thể kèm mục vừa đổi giữa hai trang; chỉ mục sau khi sửa thì luôn đúng
+        assert!(report.orphans.contains(&b"ghost".to_vec()));
+
+        let report = store.check::<Thing>().await.unwrap();
+        assert!(report.clean(), "{}", report);
+        assert_eq!((report.records, report.entries), (3000, 3000));
+        let renamed = store.query::<Thing>(crate::Query { limit: usize::MAX, ..Default::default() }).await.unwrap()
+            .filter(|s| s.as_ref().unwrap().ends_with("-new"))
+            .count();
+        assert_eq!(renamed, 300);
+    }
+}
@@ -7,8 +7,11 @@
 // ---
 // Import các trait và kiểu dữ liệu cần thiết cho serialization, định danh, và debug
 use serde::{de::DeserializeOwned, Serialize}; // Serialize/DeserializeOwned: Cho phép tuần tự hóa mọi thực thể
-use crate::Id; // Id: Định danh duy nhất cho thực thể
+use crate::{Error, Id}; // Id: Định danh duy nhất cho thực thể
 use std::fmt::Debug; // Debug: Hỗ trợ debug các khóa/chỉ mục
+use crate::storage::predicate::Predicate; // Predicate: Biểu thức lọc trên Summary
+use crate::storage::id::Scheme; // Scheme: Cách sinh khóa của thực thể
+use crate::serializer::Encoding; // Encoding: Codec riêng của thực thể
 
 /// Một "hợp đồng" cho bất kỳ loại dữ liệu nào có thể được lưu trữ và lập chỉ mục.
 ///
@@ -38,10 +41,52 @@ pub trait Entity: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {
 
     /// Trả về khóa chỉ mục của thực thể.
     /// Logic tạo khóa (ví dụ: đảo ngược timestamp) được gói gọn tại đây.
+    /// Khóa phải duy nhất giữa các thực thể (thường kết thúc bằng id), vì mỗi khóa chỉ giữ một tóm tắt.
     fn index(&self) -> Self::Index;
     
     /// Trả về một bản tóm tắt của thực thể để lưu vào chỉ mục.
     fn summary(&self) -> Self::Summary;
+
+    /// Suy ra tiền tố chỉ mục từ biểu thức lọc (ví dụ `status == Open` thành byte trạng thái).
+    /// Mặc định không suy ra được gì: toàn bộ biểu thức được áp dụng như bộ lọc dư khi quét.
+    fn prefix(_predicate: &Predicate) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Suy ra khóa chính từ khóa chỉ mục (ví dụ id ở cuối khóa), để xuất thực thể đầy đủ theo
+    /// tiền tố chỉ đọc những bản ghi khớp. Mặc định không suy ra được: phải quét và lọc toàn bộ dữ liệu.
+    fn locate(_index: &[u8]) -> Option<Self::Key> {
+        None
+    }
+
+    /// Các trường văn bản được đưa vào chỉ mục ngược để tìm kiếm toàn văn.
+    /// Mặc định rỗng: thực thể không tham gia tìm kiếm và không tốn thêm thao tác ghi.
+    fn text(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Cách sinh khóa cho thực thể mới (`generate`). Mặc định UUIDv4 ngẫu nhiên;
+    /// `Scheme::Ordered` cho khóa tăng dần theo thời gian tạo.
+    const SCHEME: Scheme = Scheme::Random;
+
+    /// Phiên bản bố cục của chỉ mục. Tăng mỗi khi đổi cách dựng khóa `index()` hoặc các trường của `Summary`:
+    /// `Sled::upgrade` thấy phiên bản đã ghi khác thì tự `reindex` chỉ mục cũ.
+    const LAYOUT: u32 = 0;
+
+    /// Codec giá trị riêng cho bản ghi và Summary của thực thể này.
+    /// Mặc định `None`: dùng codec của store. Đổi codec của thực thể đã có dữ liệu cần ghi lại dữ liệu cũ.
+    const CODEC: Option<Encoding> = None;
+
+    /// Sinh id cho một thực thể mới tạo lúc `time` (nano giây Unix) theo `SCHEME`
+    fn generate(time: u128) -> Id {
+        crate::storage::id::generate(Self::SCHEME, time)
+    }
+
+    /// Thời điểm tạo (nano giây Unix), dùng làm mốc thay đổi cho bản ghi có trước nhật ký thay đổi.
+    /// Mặc định không có: bản ghi cũ được coi là thay đổi lúc dựng lại nhật ký.
+    fn created(&self) -> Option<u128> {
+        None
+    }
 }
 
 /// Cấu trúc tham số truy vấn cho các thao tác truy vấn.
@@ -57,8 +102,19 @@ pub struct Query<I: AsRef<[u8]> + Clone> {
     /// Khóa chỉ mục để bắt đầu sau đó (dùng cho phân trang)
     pub after: Option<I>,
     
-    /// Số lượng kết quả tối đa
+    /// Số lượng kết quả tối đa (tính sau khi lọc)
     pub limit: usize,
+
+    /// Biểu thức lọc trên Summary, áp dụng trong lúc quét
+    pub filter: Option<Predicate>,
+}
+
+impl<I: AsRef<[u8]> + Clone> Query<I> {
+    /// Gắn biểu thức lọc vào truy vấn
+    pub fn filter(mut self, predicate: Predicate) -> Self {
+        self.filter = Some(predicate);
+        self
+    }
 }
 
 impl<I: AsRef<[u8]> + Clone> Default for Query<I> {
@@ -67,6 +123,7 @@ impl<I: AsRef<[u8]> + Clone> Default for Query<I> {
             prefix: Vec::new(),
             after: None,
             limit: 10, // Giá trị mặc định hợp lý
+            filter: None,
         }
     }
 }
@@ -76,9 +133,19 @@ impl<I: AsRef<[u8]> + Clone> Default for Query<I> {
 /// Struct này giúp tạo ra các khóa chỉ mục đa thành phần một cách nhất quán,
 /// đảm bảo tính thống nhất giữa các thực thể khác nhau.
 /// Mục đích: Đảm bảo mọi index đều có cấu trúc rõ ràng, dễ mở rộng, và tối ưu hóa truy vấn.
+///
+/// Mọi thành phần giữ thứ tự: so sánh byte của khóa trùng với so sánh lần lượt từng thành phần.
+/// Chuỗi có độ dài thay đổi được escape (`0x00` thành `0x00 0xFF`) và kết thúc bằng `0x00 0x00`,
+/// nên chứa được mọi byte, kể cả NUL. Đọc lại bằng `Decoder` theo đúng thứ tự đã ghi.
 #[derive(Clone)]
 pub struct Key(Vec<u8>);
 
+/// Byte thoát trong chuỗi đã escape
+const ESCAPE: u8 = 0x00;
+
+/// Byte theo sau `ESCAPE` để biểu diễn chính byte `0x00`
+const LITERAL: u8 = 0xFF;
+
 impl Key {
     /// Thay đổi: `with_capacity` thành `reserve` để tuân thủ quy tắc một từ.
     /// Tạo một builder mới với dung lượng đã cấp phát sẵn.
@@ -118,6 +185,73 @@ impl Key {
         self.0.push(value);
         self
     }
+
+    /// Thêm một u16 (big-endian, giữ thứ tự).
+    pub fn u16(&mut self, value: u16) -> &mut Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Thêm một u32 (big-endian, giữ thứ tự).
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Thêm một u64 (big-endian, giữ thứ tự).
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Thêm một i64: lật bit dấu để số âm đứng trước số dương.
+    pub fn i64(&mut self, value: i64) -> &mut Self {
+        self.0.extend_from_slice(&((value as u64) ^ (1 << 63)).to_be_bytes());
+        self
+    }
+
+    /// Thêm một timestamp tăng dần (cũ nhất trước), ngược với `time`.
+    pub fn stamp(&mut self, value: u128) -> &mut Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Thêm một chuỗi byte độ dài thay đổi: escape rồi kết thúc bằng `0x00 0x00`.
+    pub fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.escape(value);
+        self.0.extend_from_slice(&[ESCAPE, ESCAPE]);
+        self
+    }
+
+    /// Thêm một chuỗi độ dài thay đổi (xem `bytes`).
+    pub fn text(&mut self, value: &str) -> &mut Self {
+        self.bytes(value.as_bytes())
+    }
+
+    /// Thêm phần đầu của một chuỗi, không có dấu kết thúc: dùng làm tiền tố truy vấn
+    /// để khớp mọi chuỗi bắt đầu bằng `value` ở vị trí này.
+    pub fn prefix(&mut self, value: &str) -> &mut Self {
+        self.escape(value.as_bytes());
+        self
+    }
+
+    /// Thêm một bộ lồng nhau: khóa con dựng bởi `build` được ghi như một chuỗi byte,
+    /// nên bộ ngắn hơn đứng trước bộ dài hơn cùng tiền tố.
+    pub fn nest(&mut self, build: impl FnOnce(&mut Key)) -> &mut Self {
+        let mut inner = Key::reserve(16);
+        build(&mut inner);
+        self.bytes(&inner.0)
+    }
+
+    /// Ghi các byte, thay `0x00` bằng `0x00 0xFF`
+    fn escape(&mut self, value: &[u8]) {
+        for &b in value {
+            self.0.push(b);
+            if b == ESCAPE {
+                self.0.push(LITERAL);
+            }
+        }
+    }
     
     /// Hoàn thành và lấy khóa dưới dạng Vec<u8>.
     /// Mục đích: Kết thúc quá trình build, trả về index hoàn chỉnh.
@@ -126,6 +260,135 @@ impl Key {
     }
 }
 
+/// Đọc lại các thành phần của một khóa dựng bởi `Key`, theo đúng thứ tự đã ghi.
+///
+/// Dùng để gỡ lỗi chỉ mục, giải mã cursor hoặc kiểm tra tính nhất quán. Đọc thiếu byte
+/// hoặc chuỗi escape sai trả về `Error::Parse`.
+///
+/// ```rust,ignore
+/// let mut parts = Decoder::new(&entry.index());
+/// let status = parts.byte()?;
+/// let created = parts.time()?;
+/// let id = parts.id()?;
+/// assert!(parts.done());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl Decoder {
+    /// Tạo decoder trên các byte của khóa
+    pub fn new(data: &[u8]) -> Self {
+        Self { data: data.to_vec(), pos: 0 }
+    }
+
+    /// Đã đọc hết khóa
+    pub fn done(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    /// Phần chưa đọc
+    pub fn rest(&self) -> &[u8] {
+        &self.data[self.pos..]
+    }
+
+    /// Đọc một boolean (`Key::flag`)
+    pub fn flag(&mut self) -> Result<bool, Error> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(Error::Parse(format!("cờ không hợp lệ {} tại vị trí {}", other, self.pos - 1))),
+        }
+    }
+
+    /// Đọc một byte (`Key::byte`)
+    pub fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    /// Đọc một u16 (`Key::u16`)
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes(self.take()?))
+    }
+
+    /// Đọc một u32 (`Key::u32`)
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.take()?))
+    }
+
+    /// Đọc một u64 (`Key::u64`)
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_be_bytes(self.take()?))
+    }
+
+    /// Đọc một i64 (`Key::i64`)
+    pub fn i64(&mut self) -> Result<i64, Error> {
+        Ok((u64::from_be_bytes(self.take()?) ^ (1 << 63)) as i64)
+    }
+
+    /// Đọc một timestamp đảo ngược (`Key::time`)
+    pub fn time(&mut self) -> Result<u128, Error> {
+        Ok(u128::MAX - u128::from_be_bytes(self.take()?))
+    }
+
+    /// Đọc một timestamp tăng dần (`Key::stamp`)
+    pub fn stamp(&mut self) -> Result<u128, Error> {
+        Ok(u128::from_be_bytes(self.take()?))
+    }
+
+    /// Đọc một ID (`Key::id`)
+    pub fn id(&mut self) -> Result<Id, Error> {
+        Ok(Id::from_bytes(self.take()?))
+    }
+
+    /// Đọc một chuỗi byte (`Key::bytes`)
+    pub fn bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let start = self.pos;
+        let mut out = Vec::new();
+        loop {
+            let b = *self.data.get(self.pos)
+                .ok_or_else(|| Error::Parse(format!("chuỗi bắt đầu tại vị trí {} không có dấu kết thúc", start)))?;
+            self.pos += 1;
+            if b != ESCAPE {
+                out.push(b);
+                continue;
+            }
+            match self.data.get(self.pos) {
+                Some(&ESCAPE) => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(&LITERAL) => {
+                    self.pos += 1;
+                    out.push(ESCAPE);
+                }
+                _ => return Err(Error::Parse(format!("escape không hợp lệ tại vị trí {}", self.pos - 1))),
+            }
+        }
+    }
+
+    /// Đọc một chuỗi (`Key::text`)
+    pub fn text(&mut self) -> Result<String, Error> {
+        String::from_utf8(self.bytes()?).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Đọc một bộ lồng nhau (`Key::nest`), trả về decoder cho các thành phần của nó
+    pub fn nest(&mut self) -> Result<Decoder, Error> {
+        Ok(Decoder { data: self.bytes()?, pos: 0 })
+    }
+
+    /// Lấy `N` byte kế tiếp
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let bytes = self.data.get(self.pos..self.pos + N)
+            .and_then(|b| <[u8; N]>::try_from(b).ok())
+            .ok_or_else(|| Error::Parse(format!("khóa thiếu {} byte tại vị trí {}", N, self.pos)))?;
+        self.pos += N;
+        Ok(bytes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,4 +409,74 @@ mod tests {
         assert_eq!(key[0], 1); // true -> 1
         assert_eq!(key.len(), 1 + 16 + 16); // bool + u128 + uuid
     }
+
+    /// Dựng khóa một thành phần
+    fn one(build: impl FnOnce(&mut Key)) -> Vec<u8> {
+        let mut key = Key::reserve(16);
+        build(&mut key);
+        key.build()
+    }
+
+    #[test]
+    fn order() {
+        // Mỗi dãy đã sắp tăng dần theo giá trị thì khóa cũng tăng dần
+        let ints = [i64::MIN, -300, -1, 0, 1, 255, 256, i64::MAX];
+        assert!(ints.windows(2).all(|w| one(|k| { k.i64(w[0]); }) < one(|k| { k.i64(w[1]); })));
+        let longs = [0u64, 1, 255, 256, 1 << 40, u64::MAX];
+        assert!(longs.windows(2).all(|w| one(|k| { k.u64(w[0]); }) < one(|k| { k.u64(w[1]); })));
+        assert!(one(|k| { k.u16(255); }) < one(|k| { k.u16(256); }));
+        assert!(one(|k| { k.u32(65535); }) < one(|k| { k.u32(65536); }));
+        assert!(one(|k| { k.stamp(1); }) < one(|k| { k.stamp(2); }));
+        assert!(one(|k| { k.time(2); }) < one(|k| { k.time(1); }));
+
+        // Chuỗi: chuỗi ngắn hơn đứng trước, NUL không phá thứ tự thành phần sau
+        let texts = ["", "\0", "\0\0", "a", "a\0", "a\0b", "ab", "b"];
+        let keys: Vec<Vec<u8>> = texts.iter().map(|t| one(|k| { k.text(t).u32(0); })).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        assert!(one(|k| { k.text("a").u32(9); }) < one(|k| { k.text("a\0").u32(0); }));
+
+        // Bộ lồng nhau so sánh theo thành phần
+        let a = one(|k| { k.nest(|n| { n.text("x").u16(1); }).byte(9); });
+        let b = one(|k| { k.nest(|n| { n.text("x").u16(2); }).byte(0); });
+        let c = one(|k| { k.nest(|n| { n.text("x\0").u16(0); }).byte(0); });
+        assert!(a < b && b < c);
+
+        // Tiền tố chuỗi khớp mọi chuỗi bắt đầu bằng nó
+        let prefix = one(|k| { k.byte(1).prefix("ab"); });
+        assert!(one(|k| { k.byte(1).text("abc"); }).starts_with(&prefix));
+        assert!(!one(|k| { k.byte(1).text("a"); }).starts_with(&prefix));
+    }
+
+    #[test]
+    fn decode() {
+        let id = Id::new_v4();
+        let key = one(|k| {
+            k.flag(true).byte(7).u16(513).u32(70000).u64(1 << 40).i64(-42)
+                .time(12345).stamp(678).id(id)
+                .text("tên\0lạ").bytes(&[0, 255, 0])
+                .nest(|n| { n.text("trong").i64(i64::MIN); });
+        });
+        let mut parts = Decoder::new(&key);
+        assert!(parts.flag().unwrap());
+        assert_eq!(parts.byte().unwrap(), 7);
+        assert_eq!(parts.u16().unwrap(), 513);
+        assert_eq!(parts.u32().unwrap(), 70000);
+        assert_eq!(parts.u64().unwrap(), 1 << 40);
+        assert_eq!(parts.i64().unwrap(), -42);
+        assert_eq!(parts.time().unwrap(), 12345);
+        assert_eq!(parts.stamp().unwrap(), 678);
+        assert_eq!(parts.id().unwrap(), id);
+        assert_eq!(parts.text().unwrap(), "tên\0lạ");
+        assert_eq!(parts.bytes().unwrap(), vec![0, 255, 0]);
+        let mut inner = parts.nest().unwrap();
+        assert_eq!(inner.text().unwrap(), "trong");
+        assert_eq!(inner.i64().unwrap(), i64::MIN);
+        assert!(inner.done() && parts.done());
+
+        // Khóa cụt hoặc escape sai
+        assert!(matches!(Decoder::new(&[1, 2]).u32(), Err(Error::Parse(_))));
+        assert!(matches!(Decoder::new(b"abc").text(), Err(Error::Parse(_))));
+        assert!(matches!(Decoder::new(&[b'a', 0, 7]).text(), Err(Error::Parse(_))));
+        assert!(matches!(Decoder::new(&[2]).flag(), Err(Error::Parse(_))));
+    }
 }
@@ -8,44 +8,21 @@
 use async_trait::async_trait;
 use serde::{Serialize, Deserialize, de::DeserializeOwned};
 use std::fmt::Debug;
-use std::task::{Context, Poll};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
 use std::collections::VecDeque;
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::mpsc;
+use std::marker::PhantomData;
+use crate::storage::entity::Entity;
+use crate::storage::change;
+use crate::error::Fault;
 use crate::Error;
 use serde_json;
 
-// Định nghĩa thực thể Item dùng cho export và test
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct Item {
-    pub id: crate::Id,
-    pub name: String,
-    pub value: u32,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct Brief {
-    pub id: crate::Id,
-    pub name: String,
-}
+pub mod import; // Khôi phục từ tệp export
 
-impl crate::Entity for Item {
-    const NAME: &'static str = "test_items";
-    type Key = crate::Id;
-    type Index = Vec<u8>;
-    type Summary = Brief;
-
-    fn key(&self) -> Self::Key {
-        self.id
-    }
-    fn index(&self) -> Self::Index {
-        format!("idx_{}", self.value).into_bytes()
-    }
-    fn summary(&self) -> Self::Summary {
-        Brief {
-            id: self.id,
-            name: self.name.clone(),
-        }
-    }
-}
+pub use import::{Import, Policy, Report};
 
 /// Trait định nghĩa khả năng export cho storage.
 /// Sử dụng associated type generics để đảm bảo type safety.
@@ -99,6 +76,19 @@ pub struct Config {
     pub timeout: u64,
     /// Có compress dữ liệu không
     pub compress: bool,
+    /// Thuật toán nén khi `compress` bật
+    #[serde(default)]
+    pub codec: Compression,
+}
+
+/// Thuật toán nén luồng export. Import nhận ra cả hai qua magic bytes, không cần khai báo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Compression {
+    /// Gzip: đọc được bằng mọi công cụ (mặc định)
+    #[default]
+    Gzip,
+    /// Zstd: nén tốt và nhanh hơn
+    Zstd,
 }
 
 impl Default for Config {
@@ -107,6 +97,7 @@ impl Default for Config {
             batch: 1000,
             timeout: 30,
             compress: false,
+            codec: Compression::default(),
         }
     }
 }
@@ -118,8 +109,116 @@ pub struct Filter {
     pub prefix: Vec<u8>,
     /// Giới hạn số lượng
     pub limit: Option<usize>,
-    /// Offset để phân trang
+    /// Số mục khớp tiền tố bỏ qua trước khi xuất (tính sau `cursor`)
     pub offset: Option<usize>,
+    /// Tiếp tục sau vị trí mà một lần xuất trước đã trả về qua `Stream::cursor`
+    pub cursor: Option<Cursor>,
+    /// Chỉ xuất những gì đã đổi hoặc bị xóa sau mốc này (xem `Stream::mark`); không dùng cùng `cursor`
+    pub since: Option<Mark>,
+}
+
+/// Mã hóa byte thành chuỗi hex
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Lỗi cho token không hợp lệ của trường `field`
+fn invalid(field: &str) -> Error {
+    Error::Validation(vec![Fault {
+        field: field.to_string(),
+        message: format!("Token `{}` không hợp lệ.", field),
+    }])
+}
+
+/// Giải mã chuỗi hex của trường `field`
+fn unhex(text: &str, field: &str) -> Result<Vec<u8>, Error> {
+    if !text.len().is_multiple_of(2) {
+        return Err(invalid(field));
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| text.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid(field))
+}
+
+/// Vị trí tiếp tục của một lần xuất, mờ đối với người dùng: chỉ lưu lại rồi truyền vào
+/// `Filter::cursor`, ví dụ khi xuất bị ngắt giữa chừng hoặc client HTTP lấy trang kế tiếp.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cursor(String);
+
+impl Cursor {
+    /// Cursor trỏ ngay sau bản ghi có khóa `key`
+    fn new<K: Serialize>(key: &K) -> Result<Self, Error> {
+        Ok(Self(hex(&bincode::serialize(key)?)))
+    }
+
+    /// Khóa bản ghi mà cursor trỏ tới
+    fn key<K: DeserializeOwned>(&self) -> Result<K, Error> {
+        bincode::deserialize(&unhex(&self.0, "cursor")?).map_err(|_| invalid("cursor"))
+    }
+
+    /// Dạng chuỗi để lưu hoặc gửi qua HTTP
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Cursor {
+    fn from(text: String) -> Self {
+        Self(text)
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Mốc của xuất tăng dần (high-water mark): mọi thay đổi tới mốc đã được xuất. Lưu lại từ
+/// `Stream::mark` sau khi đọc hết stream rồi truyền vào `Filter::since` ở lần chạy sau.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Mark(String);
+
+impl Mark {
+    /// Mốc ngay trước mọi thay đổi commit tại hoặc sau thời điểm `time` (nano giây Unix, theo đồng hồ hệ thống)
+    pub fn time(time: u128) -> Self {
+        Self::new(&change::position(time))
+    }
+
+    /// Mốc tại một vị trí trong nhật ký thay đổi
+    fn new(position: &[u8]) -> Self {
+        Self(hex(position))
+    }
+
+    /// Vị trí trong nhật ký thay đổi mà mốc trỏ tới
+    fn position(&self) -> Result<Vec<u8>, Error> {
+        let position = unhex(&self.0, "since")?;
+        match position.len() >= 16 {
+            true => Ok(position),
+            false => Err(invalid("since")),
+        }
+    }
+
+    /// Dạng chuỗi để lưu hoặc gửi qua HTTP
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Mark {
+    fn from(text: String) -> Self {
+        Self(text)
+    }
+}
+
+impl std::fmt::Display for Mark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
 }
 
 /// Cấu trúc format cho export
@@ -129,31 +228,66 @@ pub enum Format {
     Json,
     /// Export dạng CSV
     Csv,
-    /// Export dạng binary
+    /// Export dạng binary: chuỗi khung `bincode(Vec<u8>)` nối tiếp, mỗi khung là một mục
     Binary,
     /// Export dạng custom với config
     Custom(Config),
+    /// Export dạng NDJSON: mỗi mục một dòng JSON, dễ đọc dần và ghi nối
+    Ndjson,
+    /// Export dạng bảng Markdown, cột theo tên trường như CSV
+    Markdown,
+    /// Export dạng TSV: như CSV nhưng phân cách bằng tab
+    Tsv,
 }
 
 /// Async stream trait cho export hiệu suất cao
 pub trait Streamable: Send + Sync {
-    /// Đọc chunk dữ liệu
+    /// Đọc chunk dữ liệu; `Pending` khi nguồn chưa sinh kịp, waker được đánh thức khi có trang mới
     fn read(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Vec<u8>>, Error>>;
     
     /// Kiểm tra stream đã hết chưa
     fn done(&self) -> bool;
 }
 
-/// Cấu trúc stream cho export với zero-copy
+/// Số chunk (mỗi chunk là một trang `config.batch` bản ghi) tối đa nằm chờ giữa nguồn và người đọc.
+/// Khi kênh đầy, nguồn dừng đọc storage cho tới khi người đọc lấy bớt.
+const DEPTH: usize = 4;
+
+/// Cấu trúc stream cho export.
+///
+/// Stream đệm (`Stream::new` + `push`) chứa sẵn toàn bộ dữ liệu. Stream trực tiếp
+/// (`Export::stream`) nhận từng trang qua kênh có giới hạn, nên xuất bao nhiêu bản ghi
+/// cũng chỉ giữ vài trang trong bộ nhớ. Cả hai đọc được qua `futures_core::Stream`,
+/// `tokio::io::AsyncRead` hoặc `next()`.
 pub struct Stream {
     /// Buffer dữ liệu
-    buffer: VecDeque<Vec<u8>>,
+    buffer: VecDeque<Chunk>,
     /// Vị trí hiện tại trong buffer
     pos: usize,
     /// Tổng kích thước
     size: usize,
     /// State của stream
     state: State,
+    /// Nguồn sinh dữ liệu, có khi stream trực tiếp
+    source: Option<mpsc::Receiver<Result<Chunk, Error>>>,
+    /// Vị trí tiếp tục sau chunk cuối cùng đã đọc hết
+    cursor: Option<Cursor>,
+    /// Mốc cho lần xuất tăng dần kế tiếp, có khi đã đọc hết stream
+    mark: Option<Mark>,
+    /// Tổng số byte đã nhận (sau nén)
+    compressed: u64,
+    /// Tổng số byte đã nhận trước khi nén
+    uncompressed: u64,
+}
+
+/// Một chunk dữ liệu kèm vị trí tiếp tục sau nó
+struct Chunk {
+    data: Vec<u8>,
+    cursor: Option<Cursor>,
+    /// Mốc xuất tăng dần, chỉ có ở chunk cuối
+    mark: Option<Mark>,
+    /// Số byte trước khi nén
+    raw: usize,
 }
 
 #[derive(Debug)]
@@ -174,38 +308,71 @@ impl Stream {
             pos: 0,
             size: 0,
             state: State::Reading,
+            source: None,
+            cursor: None,
+            mark: None,
+            compressed: 0,
+            uncompressed: 0,
         }
     }
+
+    /// Tạo stream trực tiếp nhận dữ liệu từ `source`, bắt đầu tại `cursor`
+    fn live(source: mpsc::Receiver<Result<Chunk, Error>>, cursor: Option<Cursor>) -> Self {
+        Self { source: Some(source), cursor, ..Self::new() }
+    }
     
     /// Thêm dữ liệu vào buffer
     pub fn push(&mut self, data: Vec<u8>) {
-        self.size += data.len();
-        self.buffer.push_back(data);
+        let raw = data.len();
+        self.put(Chunk { data, cursor: None, mark: None, raw });
+    }
+
+    /// Thêm chunk vào buffer
+    fn put(&mut self, chunk: Chunk) {
+        self.size += chunk.data.len();
+        self.compressed += chunk.data.len() as u64;
+        self.uncompressed += chunk.raw as u64;
+        self.buffer.push_back(chunk);
+    }
+
+    /// Tổng số byte stream đã nhận, sau nén (bằng `uncompressed` khi không nén)
+    pub fn compressed(&self) -> u64 {
+        self.compressed
+    }
+
+    /// Tổng số byte stream đã nhận, tính trước khi nén
+    pub fn uncompressed(&self) -> u64 {
+        self.uncompressed
+    }
+
+    /// Vị trí tiếp tục sau phần dữ liệu đã đọc hết; truyền vào `Filter::cursor` để xuất tiếp
+    /// từ đó. Cursor chỉ tiến khi cả một chunk (một trang) đã được đọc.
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.cursor.as_ref()
+    }
+
+    /// Mốc cho lần xuất tăng dần kế tiếp (`Filter::since`); chỉ có sau khi đã đọc hết stream
+    /// và lần xuất đã đi hết dữ liệu (không dừng vì `limit`, không bắt đầu từ `cursor`/`offset`).
+    pub fn mark(&self) -> Option<&Mark> {
+        self.mark.as_ref()
     }
     
-    /// Đọc chunk dữ liệu (trả về Vec<u8> để tránh borrow đồng thời)
+    /// Đọc chunk dữ liệu đã có trong buffer, không chờ nguồn (trả về Vec<u8> để tránh borrow đồng thời)
     pub fn read(&mut self, chunk: usize) -> Option<Vec<u8>> {
-        if self.done() {
+        if self.buffer.is_empty() {
             return None;
         }
-        if let Some(data) = self.buffer.front() {
-            if self.pos < data.len() {
-                let end = (self.pos + chunk).min(data.len());
-                let result = data[self.pos..end].to_vec();
-                self.pos = end;
-                if self.pos >= data.len() {
-                    self.buffer.pop_front();
-                    self.pos = 0;
-                }
-                return Some(result);
-            }
-        }
-        None
+        Some(self.take(chunk))
+    }
+
+    /// Đọc chunk kế tiếp, chờ nguồn nếu cần; None khi đã hết
+    pub async fn next(&mut self) -> Option<Result<Vec<u8>, Error>> {
+        std::future::poll_fn(|cx| self.poll(cx, usize::MAX)).await.transpose()
     }
     
     /// Kiểm tra stream đã hết chưa
     pub fn done(&self) -> bool {
-        matches!(self.state, State::Done) && self.buffer.is_empty() && self.pos == 0
+        matches!(self.state, State::Done) && self.buffer.is_empty() && self.source.is_none()
     }
     
     /// Reset stream về đầu
@@ -214,6 +381,11 @@ impl Stream {
         self.buffer.clear();
         self.size = 0;
         self.state = State::Reading;
+        self.source = None;
+        self.cursor = None;
+        self.mark = None;
+        self.compressed = 0;
+        self.uncompressed = 0;
     }
     
     /// Mark stream as done
@@ -225,6 +397,72 @@ impl Stream {
     pub fn error(&mut self, error: Error) {
         self.state = State::Error(error);
     }
+
+    /// Lấy tối đa `chunk` byte từ đầu buffer (buffer phải khác rỗng)
+    fn take(&mut self, chunk: usize) -> Vec<u8> {
+        let data = &self.buffer[0].data;
+        let end = self.pos.saturating_add(chunk).min(data.len());
+        let result = data[self.pos..end].to_vec();
+        self.pos = end;
+        if self.pos >= data.len() {
+            self.pop();
+        }
+        result
+    }
+
+    /// Bỏ chunk đầu đã đọc hết, ghi nhận vị trí tiếp tục của nó
+    fn pop(&mut self) {
+        if let Some(chunk) = self.buffer.pop_front() {
+            if chunk.cursor.is_some() {
+                self.cursor = chunk.cursor;
+            }
+            if chunk.mark.is_some() {
+                self.mark = chunk.mark;
+            }
+        }
+        self.pos = 0;
+    }
+
+    /// Đảm bảo buffer có dữ liệu: Ok(true) khi có, Ok(false) khi nguồn đã hết
+    fn fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool, Error>> {
+        if let State::Error(ref e) = self.state {
+            return Poll::Ready(Err(Error::Io(std::io::Error::other(format!("stream error: {}", e)))));
+        }
+        loop {
+            // Chunk rỗng không mang dữ liệu; trả về nó sẽ bị hiểu là hết stream
+            while self.buffer.front().is_some_and(|chunk| self.pos >= chunk.data.len()) {
+                self.pop();
+            }
+            if !self.buffer.is_empty() {
+                return Poll::Ready(Ok(true));
+            }
+            let Some(source) = self.source.as_mut() else {
+                return Poll::Ready(Ok(false));
+            };
+            match ready!(source.poll_recv(cx)) {
+                Some(Ok(chunk)) => self.put(chunk),
+                Some(Err(e)) => {
+                    self.source = None;
+                    self.state = State::Error(Error::Io(std::io::Error::other(e.to_string())));
+                    return Poll::Ready(Err(e));
+                }
+                None => {
+                    self.source = None;
+                    self.state = State::Done;
+                    return Poll::Ready(Ok(false));
+                }
+            }
+        }
+    }
+
+    /// Đọc tối đa `chunk` byte, chờ nguồn nếu buffer rỗng
+    fn poll(&mut self, cx: &mut Context<'_>, chunk: usize) -> Poll<Result<Option<Vec<u8>>, Error>> {
+        match ready!(self.fill(cx)) {
+            Ok(true) => Poll::Ready(Ok(Some(self.take(chunk)))),
+            Ok(false) => Poll::Ready(Ok(None)),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
 }
 
 impl Default for Stream {
@@ -234,69 +472,121 @@ impl Default for Stream {
 }
 
 impl Streamable for Stream {
-    fn read(&mut self, _cx: &mut Context<'_>) -> Poll<Result<Option<Vec<u8>>, Error>> {
-        match self.state {
-            State::Done => Poll::Ready(Ok(None)),
-            State::Error(ref e) => Poll::Ready(Err(Error::Io(std::io::Error::other(format!("stream error: {:?}", e))))),
-            State::Reading => {
-                if let Some(data) = self.buffer.front() {
-                    if self.pos < data.len() {
-                        let end = (self.pos + 1024).min(data.len());
-                        let result = data[self.pos..end].to_vec();
-                        self.pos = end;
-                        if self.pos >= data.len() {
-                            self.buffer.pop_front();
-                            self.pos = 0;
-                        }
-                        Poll::Ready(Ok(Some(result)))
-                    } else {
-                        self.buffer.pop_front();
-                        self.pos = 0;
-                        Poll::Ready(Ok(None))
-                    }
-                } else {
-                    Poll::Ready(Ok(None))
-                }
-            }
-        }
+    fn read(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Vec<u8>>, Error>> {
+        self.poll(cx, 1024)
     }
+
     fn done(&self) -> bool {
-        matches!(self.state, State::Done) && self.buffer.is_empty() && self.pos == 0
+        Stream::done(self)
+    }
+}
+
+impl futures_core::Stream for Stream {
+    type Item = Result<Vec<u8>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll(cx, usize::MAX).map(Result::transpose)
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        match ready!(this.poll(cx, buf.remaining())) {
+            Ok(Some(data)) => buf.put_slice(&data),
+            Ok(None) => {}
+            Err(e) => return Poll::Ready(Err(std::io::Error::other(e.to_string()))),
+        }
+        Poll::Ready(Ok(()))
     }
 }
 
-/// Cấu trúc export chính với zero-copy và lazy evaluation
-#[derive(Clone)]
-pub struct Export<S> {
+/// Dạng dữ liệu được xuất cho mỗi thực thể
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum View {
+    /// Thực thể đầy đủ
+    Full,
+    /// `Entity::Summary` (mặc định)
+    #[default]
+    Summary,
+}
+
+/// Cấu trúc export chính, tham số hóa theo loại thực thể `E`
+pub struct Export<S, E> {
     /// Storage backend reference
     storage: std::sync::Arc<S>,
     /// Cấu hình export
     config: Config,
+    /// Dạng dữ liệu xuất
+    view: View,
+    /// Bộ lọc mặc định của `export` và `run`
+    filter: Filter,
+    /// Định dạng mặc định của `run`
+    format: Format,
+    /// Loại thực thể được xuất
+    entity: PhantomData<fn() -> E>,
 }
 
-impl<S> Export<S> {
+impl<S, E> Clone for Export<S, E> {
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            config: self.config.clone(),
+            view: self.view,
+            filter: self.filter.clone(),
+            format: self.format.clone(),
+            entity: PhantomData,
+        }
+    }
+}
+
+impl<S, E> Export<S, E> {
     /// Tạo export mới
     pub fn new(storage: S, config: Config) -> Self {
-        Self { 
-            storage: std::sync::Arc::new(storage), 
-            config 
+        Self::shared(std::sync::Arc::new(storage), config)
+    }
+
+    /// Tạo export từ Arc storage với config cho trước
+    fn shared(storage: std::sync::Arc<S>, config: Config) -> Self {
+        Self {
+            storage,
+            config,
+            view: View::default(),
+            filter: Filter::default(),
+            format: Format::Json,
+            entity: PhantomData,
         }
     }
     
     /// Tạo export với config mặc định
     pub fn default(storage: S) -> Self {
-        Self {
-            storage: std::sync::Arc::new(storage),
-            config: Config::default(),
-        }
+        Self::new(storage, Config::default())
     }
     
     /// Tạo export từ Arc storage với config mặc định
     pub fn arc(storage: std::sync::Arc<S>) -> Self {
-        Self {
-            storage,
-            config: Config::default(),
-        }
+        Self::shared(storage, Config::default())
+    }
+
+    /// Chọn xuất thực thể đầy đủ hay bản tóm tắt
+    pub fn view(mut self, view: View) -> Self {
+        self.view = view;
+        self
+    }
+
+    /// Đặt bộ lọc cho `export` và `run`; `partial` và `stream` dùng bộ lọc truyền vào
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Đặt định dạng cho `run`
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
     }
     
     /// Lấy reference đến storage
@@ -311,149 +601,528 @@ impl<S> Export<S> {
 }
 
 #[async_trait]
-impl<S: crate::storage::Storage> Exportable for Export<S>
+impl<S, E> Exportable for Export<S, E>
 where
-    S: crate::storage::Storage + Send + Sync,
+    S: crate::storage::Storage + Send + Sync + 'static,
+    E: Entity,
+    E::Key: Debug + Serialize + DeserializeOwned,
 {
     type Data = Filter;
     type Format = Format;
     type Stream = Stream;
     
+    /// Xuất theo bộ lọc đã đặt (`Builder::filter`, `Export::filter`) với định dạng cho trước
     async fn export(&self, format: Self::Format) -> Result<Self::Stream, Error> {
-        let filter = Filter::default();
-        self.partial(filter, format).await
+        self.partial(self.filter.clone(), format).await
     }
     
+    /// Xuất vào stream đệm sẵn toàn bộ; dùng `Export::stream` để xuất dần với bộ nhớ cố định
     async fn partial(&self, filter: Self::Data, format: Self::Format) -> Result<Self::Stream, Error> {
-        // Implementation sẽ được triển khai dựa trên format
-        match format {
-            Format::Json => self.json(filter).await,
-            Format::Csv => self.csv(filter).await,
-            Format::Binary => self.binary(filter).await,
-            Format::Custom(config) => self.custom(filter, config).await,
+        let mut live = self.stream(filter, format);
+        let mut content = Vec::new();
+        while let Some(chunk) = live.next().await {
+            content.extend(chunk?);
         }
+        let mut stream = Stream::new();
+        stream.put(Chunk { data: content, cursor: live.cursor, mark: live.mark, raw: live.uncompressed as usize });
+        stream.finish();
+        Ok(stream)
     }
 }
 
-impl<S: crate::storage::Storage> Export<S> {
-    /// Export dạng JSON
-    async fn json(&self, filter: Filter) -> Result<Stream, Error> {
-        let mut stream = Stream::new();
-        let mut data = Vec::new();
-        
-        // Đọc dữ liệu từ storage
-        let query = crate::storage::entity::Query {
-            prefix: filter.prefix,
-            after: None,
-            limit: filter.limit.unwrap_or(1000),
+impl<S, E> Export<S, E>
+where
+    S: crate::storage::Storage + Send + Sync + 'static,
+    E: Entity,
+    E::Key: Debug + Serialize + DeserializeOwned,
+{
+    /// Xuất theo bộ lọc và định dạng đã đặt qua `Builder` (mặc định toàn bộ, JSON)
+    pub async fn run(&self) -> Result<Stream, Error> {
+        self.partial(self.filter.clone(), self.format.clone()).await
+    }
+
+    /// Xuất trực tiếp: storage được đọc theo từng trang `config.batch` trên một task nền,
+    /// mỗi trang được mã hóa thành một chunk ngay khi đọc xong. Kênh giữa hai bên chỉ giữ
+    /// `DEPTH` chunk, nên người đọc chậm làm nguồn dừng lại thay vì dồn dữ liệu vào bộ nhớ.
+    /// Bỏ stream giữa chừng sẽ dừng task nền ở trang kế tiếp.
+    ///
+    /// Mỗi chunk mang theo vị trí tiếp tục sau nó (`Stream::cursor`). Lần xuất tiếp tục từ
+    /// cursor là một tài liệu độc lập chứa phần còn lại; NDJSON và Binary nối thẳng được.
+    ///
+    /// ```rust,ignore
+    /// let mut stream = export.stream(Filter::default(), Format::Json);
+    /// tokio::io::copy(&mut stream, &mut tokio::fs::File::create("tasks.json").await?).await?;
+    /// ```
+    pub fn stream(&self, filter: Filter, format: Format) -> Stream {
+        let (sender, receiver) = mpsc::channel(DEPTH);
+        let cursor = filter.cursor.clone();
+        let export = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = export.produce(filter, format, &sender).await {
+                let _ = sender.send(Err(e)).await;
+            }
+        });
+        Stream::live(receiver, cursor)
+    }
+
+    /// Đọc, lọc theo tiền tố khóa chỉ mục và mã hóa từng trang, gửi sang người đọc
+    async fn produce(&self, filter: Filter, format: Format, sender: &mpsc::Sender<Result<Chunk, Error>>) -> Result<(), Error> {
+        let left = match &format {
+            Format::Custom(config) => filter.limit.unwrap_or(config.batch).min(config.batch),
+            _ => filter.limit.unwrap_or(usize::MAX),
         };
-        let items = self.storage.as_ref().query::<Item>(query).await?;
-        
-        for item in items {
-            let result = item?;
-            let json = serde_json::to_string(&result)?;
-            data.push(json);
+        // `Custom` mang cấu hình riêng, kể cả cách nén
+        let packer = match &format {
+            Format::Custom(config) => Packer::new(config)?,
+            _ => Packer::new(&self.config)?,
+        };
+        let delta = filter.since.is_some();
+        let mut sink = Sink { encoder: Encoder { format, count: 0, delta, columns: 0 }, packer, sender, gone: false };
+        let open = sink.encoder.open()?;
+        sink.send(open, None).await?;
+        let mark = match filter.since.clone() {
+            Some(_) if filter.cursor.is_some() => return Err(invalid("since")),
+            Some(since) => self.delta(&filter, &since, left, &mut sink).await?,
+            None => self.whole(&filter, left, &mut sink).await?,
+        };
+        if !sink.gone {
+            sink.close(mark).await?;
         }
-        
-        // Tạo JSON content
-        let content = format!("[{}]", data.join(","));
-        stream.push(content.into_bytes());
-        stream.finish();
-        
-        Ok(stream)
+        Ok(())
     }
-    
-    /// Export dạng CSV
-    async fn csv(&self, filter: Filter) -> Result<Stream, Error> {
-        let mut stream = Stream::new();
-        let mut data = Vec::new();
-        
-        // Header CSV
-        data.push("id,name".to_string());
-        
-        // Đọc dữ liệu từ storage
-        let query = crate::storage::entity::Query {
-            prefix: filter.prefix,
-            after: None,
-            limit: filter.limit.unwrap_or(1000),
-        };
-        let items = self.storage.as_ref().query::<Item>(query).await?;
-        
-        for item in items {
-            let result = item?;
-            let csv = format!("{},{}", result.id, result.name);
-            data.push(csv);
+
+    /// Xuất toàn bộ, hoặc những mục khớp tiền tố. Trả về mốc cho lần xuất tăng dần kế tiếp khi
+    /// đã đi hết dữ liệu từ đầu; mốc là vị trí nhật ký lấy trước khi đọc (`Storage::position`),
+    /// nên thay đổi commit trong lúc xuất luôn có mặt ở lần sau (có thể lặp lại).
+    async fn whole(&self, filter: &Filter, left: usize, sink: &mut Sink<'_>) -> Result<Option<Mark>, Error> {
+        if filter.prefix.is_empty() {
+            return self.scan(filter, left, sink).await;
         }
-        
-        // Tạo CSV content
-        let content = data.join("\n");
-        stream.push(content.into_bytes());
-        stream.finish();
-        
-        Ok(stream)
+        // Thực thể đầy đủ chỉ đọc theo chỉ mục được khi suy ra được khóa chính từ khóa chỉ mục
+        if self.view == View::Full {
+            let first = self.storage.select::<E>(filter.prefix.clone(), None, 1).await?;
+            if first.iter().any(|(index, _)| E::locate(index).is_none()) {
+                return self.scan(filter, left, sink).await;
+            }
+        }
+        self.indexed(filter, left, sink).await
     }
-    
-    /// Export dạng binary
-    async fn binary(&self, filter: Filter) -> Result<Stream, Error> {
-        let mut stream = Stream::new();
-        let mut data = Vec::new();
-        
-        // Đọc dữ liệu từ storage
-        let query = crate::storage::entity::Query {
-            prefix: filter.prefix,
-            after: None,
-            limit: filter.limit.unwrap_or(1000),
-        };
-        let items = self.storage.as_ref().query::<Item>(query).await?;
-        
-        for item in items {
-            let result = item?;
-            let binary = bincode::serialize(&result)?;
-            data.push(binary);
+
+    /// Duyệt toàn bộ dữ liệu theo thứ tự khóa chính, lọc theo tiền tố khóa chỉ mục nếu có.
+    /// Cursor là khóa chính của mục cuối đã xuất.
+    async fn scan(&self, filter: &Filter, mut left: usize, sink: &mut Sink<'_>) -> Result<Option<Mark>, Error> {
+        let start = self.storage.position().await?;
+        let batch = self.config.batch.max(1);
+        let mut skip = filter.offset.unwrap_or(0);
+        let mut after = filter.cursor.as_ref().map(Cursor::key::<E::Key>).transpose()?;
+        let complete = after.is_none() && skip == 0;
+        while left > 0 && !sink.gone {
+            let page = self.storage.records::<E>(after.take(), batch).await?;
+            let done = page.len() < batch;
+            let last = page.last().map(Entity::key);
+            let mut entities: Vec<E> = Vec::new();
+            let mut full = false;
+            for entity in page {
+                if !entity.index().as_ref().starts_with(&filter.prefix) {
+                    continue;
+                }
+                if skip > 0 {
+                    skip -= 1;
+                } else if entities.len() == left {
+                    full = true;
+                    break;
+                } else {
+                    entities.push(entity);
+                }
+            }
+            left -= entities.len();
+            // Dừng giữa trang vì đủ `limit`: tiếp tục ngay sau mục cuối đã xuất
+            after = if full { entities.last().map(Entity::key) } else { last };
+            let cursor = after.as_ref().map(Cursor::new).transpose()?;
+            let data = self.page(&mut sink.encoder, &entities)?;
+            sink.send(data, cursor).await?;
+            if done || full {
+                return Ok((complete && !full).then(|| Mark::new(&start)));
+            }
         }
-        
-        // Tạo binary content
-        let content = bincode::serialize(&data)?;
-        stream.push(content);
-        stream.finish();
-        
-        Ok(stream)
+        Ok(None)
     }
-    
-    /// Export dạng custom
-    async fn custom(&self, filter: Filter, config: Config) -> Result<Stream, Error> {
-        let mut stream = Stream::new();
-        let mut data = Vec::new();
-        
-        // Đọc dữ liệu từ storage
-        let query = crate::storage::entity::Query {
-            prefix: filter.prefix,
-            after: None,
-            limit: config.batch,
-        };
-        let items = self.storage.as_ref().query::<Item>(query).await?;
-        
+
+    /// Quét tree chỉ mục theo tiền tố theo thứ tự khóa chỉ mục: bản tóm tắt lấy thẳng từ chỉ mục,
+    /// thực thể đầy đủ chỉ đọc những bản ghi khớp. Cursor là khóa chỉ mục của mục cuối đã xuất.
+    async fn indexed(&self, filter: &Filter, mut left: usize, sink: &mut Sink<'_>) -> Result<Option<Mark>, Error> {
+        let start = self.storage.position().await?;
+        let batch = self.config.batch.max(1);
+        let mut skip = filter.offset.unwrap_or(0);
+        let mut after = filter.cursor.as_ref().map(Cursor::key::<Vec<u8>>).transpose()?;
+        let complete = after.is_none() && skip == 0;
+        while left > 0 && !sink.gone {
+            let page = self.storage.select::<E>(filter.prefix.clone(), after.take(), batch).await?;
+            let done = page.len() < batch;
+            let last = page.last().map(|(index, _)| index.clone());
+            let skipped = skip.min(page.len());
+            skip -= skipped;
+            let rest = page.len() - skipped;
+            // Trang còn mục khớp sau `limit`: tiếp tục ngay sau mục cuối đã xuất
+            let full = rest > left;
+            let chosen: Vec<(Vec<u8>, E::Summary)> = page.into_iter().skip(skipped).take(left).collect();
+            left -= chosen.len();
+            after = if full { chosen.last().map(|(index, _)| index.clone()) } else { last };
+            let cursor = after.as_ref().map(Cursor::new).transpose()?;
+            let data = match self.view {
+                View::Summary => sink.encoder.page(&chosen.into_iter().map(|(_, summary)| summary).collect::<Vec<_>>())?,
+                View::Full => {
+                    let mut entities = Vec::with_capacity(chosen.len());
+                    for (index, _) in chosen {
+                        let key = E::locate(&index).ok_or_else(|| invalid("prefix"))?;
+                        // Bản ghi có thể vừa bị xóa sau khi quét chỉ mục
+                        if let Some(entity) = self.storage.fetch::<E>(key).await? {
+                            entities.push(entity);
+                        }
+                    }
+                    sink.encoder.page(&entities)?
+                }
+            };
+            sink.send(data, cursor).await?;
+            if done || full {
+                return Ok((complete && !full).then(|| Mark::new(&start)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Xuất tăng dần sau mốc `since` theo nhật ký thay đổi, làm hai lượt trên cùng một khoảng:
+    /// lượt đầu xuất bản ghi đã đổi (tính `offset`, `limit`) và chốt mốc mới, lượt sau xuất
+    /// các bản ghi đã bị xóa trong khoảng đó, nên CSV có đủ tiêu đề trước các dòng xóa.
+    async fn delta(&self, filter: &Filter, since: &Mark, mut left: usize, sink: &mut Sink<'_>) -> Result<Option<Mark>, Error> {
+        let batch = self.config.batch.max(1);
+        let start = since.position()?;
+        let mut skip = filter.offset.unwrap_or(0);
+        let mut end = start.clone();
+        while left > 0 && !sink.gone {
+            let page = self.storage.changes::<E>(Some(end.clone()), batch).await?;
+            let done = page.done;
+            let mut entities: Vec<E> = Vec::new();
+            let mut full = false;
+            let mut last = None;
+            for change in page.changes {
+                let Some(entity) = change.entity else { continue };
+                if !change.index.starts_with(&filter.prefix) {
+                    continue;
+                }
+                if skip > 0 {
+                    skip -= 1;
+                } else if entities.len() == left {
+                    full = true;
+                    break;
+                } else {
+                    entities.push(entity);
+                }
+                last = Some(change.position);
+            }
+            left -= entities.len();
+            // Dừng giữa trang vì đủ `limit`: mốc dừng ngay sau mục cuối đã xuất
+            end = match (full, last, page.next) {
+                (true, Some(last), _) => last,
+                (_, _, Some(next)) => next,
+                _ => end,
+            };
+            let data = self.page(&mut sink.encoder, &entities)?;
+            sink.send(data, None).await?;
+            if done || full {
+                break;
+            }
+        }
+
+        let mut after = start;
+        while after < end && !sink.gone {
+            let page = self.storage.changes::<E>(Some(after.clone()), batch).await?;
+            let keys = page.changes.iter()
+                .filter(|change| change.entity.is_none() && change.position <= end && change.index.starts_with(&filter.prefix))
+                .map(|change| bincode::deserialize::<E::Key>(&change.key))
+                .collect::<Result<Vec<_>, _>>()?;
+            let data = sink.encoder.tombstones(&keys)?;
+            sink.send(data, None).await?;
+            match page.next {
+                Some(next) if !page.done => after = next,
+                _ => break,
+            }
+        }
+        Ok(Some(Mark::new(&end)))
+    }
+
+    /// Mã hóa một trang thực thể theo `view`
+    fn page(&self, encoder: &mut Encoder, entities: &[E]) -> Result<Vec<u8>, Error> {
+        match self.view {
+            View::Full => encoder.page(entities),
+            View::Summary => encoder.page(&entities.iter().map(Entity::summary).collect::<Vec<_>>()),
+        }
+    }
+}
+
+/// Đầu ra của một lần xuất: mã hóa, nén rồi gửi từng đoạn sang người đọc
+struct Sink<'a> {
+    encoder: Encoder,
+    packer: Packer,
+    sender: &'a mpsc::Sender<Result<Chunk, Error>>,
+    /// Người đọc đã bỏ stream
+    gone: bool,
+}
+
+impl Sink<'_> {
+    /// Nén và gửi một đoạn đã mã hóa kèm vị trí tiếp tục sau nó
+    async fn send(&mut self, data: Vec<u8>, cursor: Option<Cursor>) -> Result<(), Error> {
+        let chunk = Chunk { raw: data.len(), data: self.packer.write(&data)?, cursor, mark: None };
+        self.gone |= !deliver(self.sender, chunk).await;
+        Ok(())
+    }
+
+    /// Gửi phần kết thúc tài liệu, đuôi nén và mốc xuất tăng dần
+    async fn close(mut self, mark: Option<Mark>) -> Result<(), Error> {
+        let close = self.encoder.close();
+        let mut data = self.packer.write(&close)?;
+        data.extend(self.packer.finish()?);
+        deliver(self.sender, Chunk { raw: close.len(), data, cursor: None, mark }).await;
+        Ok(())
+    }
+}
+
+/// Gửi một chunk, chờ khi kênh đầy; false nếu người đọc đã bỏ stream.
+/// Chunk rỗng chỉ được gửi khi mang cursor hoặc mốc.
+async fn deliver(sender: &mpsc::Sender<Result<Chunk, Error>>, chunk: Chunk) -> bool {
+    (chunk.data.is_empty() && chunk.cursor.is_none() && chunk.mark.is_none()) || sender.send(Ok(chunk)).await.is_ok()
+}
+
+/// Nén tăng dần các chunk đã mã hóa theo `Config::compress` và `Config::codec`
+enum Packer {
+    /// Không nén
+    Plain,
+    /// Nén gzip
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    /// Nén zstd
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+impl Packer {
+    fn new(config: &Config) -> Result<Self, Error> {
+        Ok(match (config.compress, config.codec) {
+            (false, _) => Self::Plain,
+            (true, Compression::Gzip) => Self::Gzip(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default())),
+            // Mức 0 là mức mặc định của zstd
+            (true, Compression::Zstd) => Self::Zstd(zstd::stream::write::Encoder::new(Vec::new(), 0)?),
+        })
+    }
+
+    /// Nén một chunk và lấy phần dữ liệu nén đã sẵn sàng. Mỗi chunk được flush để byte ra
+    /// luôn chứa trọn chunk, nên cursor của chunk không vượt trước dữ liệu đã gửi.
+    fn write(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        use std::io::Write;
+        match self {
+            Self::Plain => Ok(data.to_vec()),
+            Self::Gzip(encoder) => {
+                if data.is_empty() {
+                    return Ok(Vec::new());
+                }
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+            Self::Zstd(encoder) => {
+                if data.is_empty() {
+                    return Ok(Vec::new());
+                }
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+        }
+    }
+
+    /// Kết thúc luồng nén, trả về phần đuôi (trailer)
+    fn finish(self) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Plain => Ok(Vec::new()),
+            Self::Gzip(encoder) => Ok(encoder.finish()?),
+            Self::Zstd(encoder) => Ok(encoder.finish()?),
+        }
+    }
+}
+
+/// Mã hóa tăng dần theo định dạng: phần mở đầu, từng trang, phần kết thúc
+struct Encoder {
+    format: Format,
+    /// Số mục đã mã hóa, để đặt dấu phân cách và tiêu đề CSV đúng một lần
+    count: usize,
+    /// Xuất tăng dần: CSV/TSV/Markdown có thêm cột `deleted`
+    delta: bool,
+    /// Số cột dữ liệu của tiêu đề CSV đã ghi (không tính `deleted`)
+    columns: usize,
+}
+
+/// Một bản ghi đã xóa trong xuất tăng dần dạng JSON
+#[derive(Serialize)]
+struct Tombstone<'a, K> {
+    deleted: &'a K,
+}
+
+impl Encoder {
+    /// Phần mở đầu của tài liệu
+    fn open(&self) -> Result<Vec<u8>, Error> {
+        match &self.format {
+            Format::Json => Ok(b"[".to_vec()),
+            Format::Custom(config) => Ok(format!("{{\"config\":{},\"data\":[", serde_json::to_string(config)?).into_bytes()),
+            Format::Csv | Format::Binary | Format::Ndjson | Format::Markdown | Format::Tsv => Ok(Vec::new()),
+        }
+    }
+
+    /// Mã hóa một trang mục
+    fn page<T: Serialize>(&mut self, items: &[T]) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        match &self.format {
+            Format::Json | Format::Custom(_) => {
+                for item in items {
+                    if self.count > 0 {
+                        out.push(b',');
+                    }
+                    serde_json::to_writer(&mut out, item)?;
+                    self.count += 1;
+                }
+            }
+            Format::Ndjson => {
+                for item in items {
+                    serde_json::to_writer(&mut out, item)?;
+                    out.push(b'\n');
+                    self.count += 1;
+                }
+            }
+            Format::Csv => out = self.delimited(items, b',')?,
+            Format::Tsv => out = self.delimited(items, b'\t')?,
+            Format::Markdown => {
+                let header = self.count == 0;
+                let text = self.delimited(items, b',')?;
+                out = table(&text, header)?;
+            }
+            Format::Binary => {
+                for item in items {
+                    bincode::serialize_into(&mut out, &bincode::serialize(item)?)?;
+                    self.count += 1;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Phần kết thúc của tài liệu
+    fn close(&self) -> Vec<u8> {
+        match &self.format {
+            Format::Json => b"]".to_vec(),
+            Format::Custom(_) => b"]}".to_vec(),
+            Format::Csv | Format::Binary | Format::Ndjson | Format::Markdown | Format::Tsv => Vec::new(),
+        }
+    }
+
+    /// Mã hóa các bản ghi đã xóa của xuất tăng dần: `{"deleted": khóa}` với JSON/NDJSON/Custom,
+    /// dòng chỉ có cột `deleted` với CSV/TSV/Markdown. Binary không mang bản ghi đã xóa.
+    fn tombstones<K: Serialize>(&mut self, keys: &[K]) -> Result<Vec<u8>, Error> {
+        match &self.format {
+            Format::Json | Format::Custom(_) | Format::Ndjson => {
+                self.page(&keys.iter().map(|deleted| Tombstone { deleted }).collect::<Vec<_>>())
+            }
+            Format::Csv => self.gaps(keys, b','),
+            Format::Tsv => self.gaps(keys, b'\t'),
+            Format::Markdown => {
+                let header = self.count == 0;
+                let text = self.gaps(keys, b',')?;
+                table(&text, header)
+            }
+            Format::Binary => Ok(Vec::new()),
+        }
+    }
+
+    /// Ghi các mục bằng crate `csv` với dấu phân cách cho trước: tiêu đề lấy từ tên trường serde,
+    /// chỉ ghi ở trang có mục đầu tiên; giá trị được quote đúng chuẩn
+    fn delimited<T: Serialize>(&mut self, items: &[T], delimiter: u8) -> Result<Vec<u8>, Error> {
+        let header = self.count == 0;
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(header)
+            .from_writer(Vec::new());
         for item in items {
-            let result = item?;
-            let custom = serde_json::to_string(&result)?;
-            data.push(custom);
+            writer.serialize(item)?;
+            self.count += 1;
         }
-        
-        // Tạo custom content với config
-        let format = serde_json::to_string(&config)?;
-        let content = format!("{{\"config\":{},\"data\":[{}]}}", format, data.join(","));
-        stream.push(content.into_bytes());
-        stream.finish();
-        
-        Ok(stream)
+        let text = writer.into_inner().map_err(|e| Error::Io(e.into_error()))?;
+        match self.delta {
+            true => self.widen(&text, delimiter, header),
+            false => Ok(text),
+        }
+    }
+
+    /// Thêm cột `deleted` vào các dòng CSV vừa ghi: tiêu đề (nếu `header`) mang tên cột,
+    /// dòng dữ liệu để trống
+    fn widen(&mut self, text: &[u8], delimiter: u8, header: bool) -> Result<Vec<u8>, Error> {
+        let mut reader = csv::ReaderBuilder::new().delimiter(delimiter).has_headers(false).from_reader(text);
+        let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(Vec::new());
+        for (i, record) in reader.records().enumerate() {
+            let mut record = record?;
+            if header && i == 0 {
+                self.columns = record.len();
+                record.push_field("deleted");
+            } else {
+                record.push_field("");
+            }
+            writer.write_record(&record)?;
+        }
+        writer.into_inner().map_err(|e| Error::Io(e.into_error()))
+    }
+
+    /// Ghi các dòng bản ghi đã xóa: ô dữ liệu trống, khóa ở cột `deleted`
+    fn gaps<K: Serialize>(&mut self, keys: &[K], delimiter: u8) -> Result<Vec<u8>, Error> {
+        let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(Vec::new());
+        if self.count == 0 && !keys.is_empty() {
+            writer.write_record(["deleted"])?;
+        }
+        for key in keys {
+            let mut record = vec![String::new(); self.columns];
+            record.push(label(key)?);
+            writer.write_record(&record)?;
+            self.count += 1;
+        }
+        writer.into_inner().map_err(|e| Error::Io(e.into_error()))
     }
 }
 
+/// Dạng chữ của khóa cho cột `deleted`: chuỗi giữ nguyên, kiểu khác theo JSON
+fn label<K: Serialize>(key: &K) -> Result<String, Error> {
+    Ok(match serde_json::to_value(key)? {
+        serde_json::Value::String(text) => text,
+        other => other.to_string(),
+    })
+}
+
+/// Đổi CSV thành các dòng bảng Markdown; dòng đầu là tiêu đề nếu `header`.
+/// Ký tự `|` được escape, xuống dòng trong ô thành `<br>`.
+fn table(text: &[u8], header: bool) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    let mut reader = csv::ReaderBuilder::new().has_headers(false).from_reader(text);
+    for (i, record) in reader.records().enumerate() {
+        let cells: Vec<String> = record?.iter()
+            .map(|cell| cell.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>"))
+            .collect();
+        out.extend(format!("| {} |\n", cells.join(" | ")).into_bytes());
+        if header && i == 0 {
+            out.extend(format!("|{}\n", " --- |".repeat(cells.len())).into_bytes());
+        }
+    }
+    Ok(out)
+}
+
 /// Cấu trúc builder cho export với fluent API
 pub struct Builder {
     config: Config,
     filter: Filter,
     format: Format,
+    view: View,
 }
 
 impl Builder {
@@ -463,6 +1132,7 @@ impl Builder {
             config: Config::default(),
             filter: Filter::default(),
             format: Format::Json,
+            view: View::default(),
         }
     }
     
@@ -483,15 +1153,21 @@ impl Builder {
         self.format = format;
         self
     }
+
+    /// Thiết lập dạng dữ liệu xuất
+    pub fn view(mut self, view: View) -> Self {
+        self.view = view;
+        self
+    }
     
-    /// Build export
-    pub fn build<S>(self, storage: S) -> Export<S> {
-        Export::new(storage, self.config)
+    /// Build export cho thực thể `E`
+    pub fn build<S, E>(self, storage: S) -> Export<S, E> {
+        self.buildarc(std::sync::Arc::new(storage))
     }
     
-    /// Build export từ Arc storage
-    pub fn buildarc<S>(self, storage: std::sync::Arc<S>) -> Export<S> {
-        Export::arc(storage)
+    /// Build export cho thực thể `E` từ Arc storage
+    pub fn buildarc<S, E>(self, storage: std::sync::Arc<S>) -> Export<S, E> {
+        Export::shared(storage, self.config).view(self.view).filter(self.filter).format(self.format)
     }
 }
 
@@ -508,18 +1184,20 @@ pub trait Ext: crate::storage::Storage + Send + Sync + Clone {
         Builder::new()
     }
     
-    /// Export với format cụ thể
-    fn exportas(self, format: Format) -> impl std::future::Future<Output = Result<Stream, Error>> + Send where Self: Sized {
+    /// Export thực thể `E` với format cụ thể
+    fn exportas<E: Entity>(self, format: Format) -> impl std::future::Future<Output = Result<Stream, Error>> + Send
+    where Self: Sized + 'static, E::Key: Debug + Serialize + DeserializeOwned {
         async move {
-            let export = Export::default(self);
+            let export = Export::<Self, E>::default(self);
             export.export(format).await
         }
     }
     
-    /// Export với format cụ thể từ Arc
-    fn exportasarc(self: std::sync::Arc<Self>, format: Format) -> impl std::future::Future<Output = Result<Stream, Error>> + Send {
+    /// Export thực thể `E` với format cụ thể từ Arc
+    fn exportasarc<E: Entity>(self: std::sync::Arc<Self>, format: Format) -> impl std::future::Future<Output = Result<Stream, Error>> + Send
+    where Self: 'static, E::Key: Debug + Serialize + DeserializeOwned {
         async move {
-            let export = Export::arc(self);
+            let export = Export::<Self, E>::arc(self);
             export.export(format).await
         }
     }
@@ -533,6 +1211,30 @@ mod tests {
     use crate::{storage::sled::Sled, Storage};
     use tempfile::tempdir;
     use crate::Id;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Item {
+        id: Id,
+        name: String,
+        value: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Brief {
+        id: Id,
+        name: String,
+    }
+
+    impl Entity for Item {
+        const NAME: &'static str = "test_items";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = Brief;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index { format!("idx_{}", self.value).into_bytes() }
+        fn summary(&self) -> Self::Summary { Brief { id: self.id, name: self.name.clone() } }
+    }
     
     /// Helper function tạo test data
     fn items(count: usize) -> Vec<Item> {
@@ -549,8 +1251,8 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         
-        let export = Builder::new()
-            .config(Config { batch: 500, timeout: 60, compress: true })
+        let export: Export<_, Item> = Builder::new()
+            .config(Config { batch: 500, timeout: 60, compress: true, ..Default::default() })
             .format(Format::Json)
             .build(storage);
             
@@ -558,6 +1260,29 @@ mod tests {
         assert_eq!(export.config.timeout, 60);
         assert!(export.config.compress);
     }
+
+    #[tokio::test]
+    async fn built() {
+        let dir = tempdir().unwrap();
+        let storage = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        storage.mass(Box::new(items(25).into_iter())).await.unwrap();
+
+        // Bộ lọc và định dạng đặt qua builder được dùng khi xuất: idx_2, idx_20..idx_24
+        let filter = Filter { prefix: b"idx_2".to_vec(), ..Default::default() };
+        let export: Export<_, Item> = Builder::new()
+            .config(Config { batch: 4, ..Default::default() })
+            .filter(filter)
+            .format(Format::Ndjson)
+            .build(storage.clone());
+        let text = String::from_utf8(drain(export.run().await.unwrap())).unwrap();
+        let mut names: Vec<String> = text.lines().map(|l| serde_json::from_str::<Brief>(l).unwrap().name).collect();
+        names.sort();
+        assert_eq!(names, ["test_2", "test_20", "test_21", "test_22", "test_23", "test_24"]);
+
+        // `export` giữ bộ lọc đã đặt, chỉ đổi định dạng
+        let briefs: Vec<Brief> = serde_json::from_slice(&drain(export.export(Format::Json).await.unwrap())).unwrap();
+        assert_eq!(briefs.len(), 6);
+    }
     
     #[tokio::test]
     async fn stream() {
@@ -591,7 +1316,7 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storage = std::sync::Arc::new(storage);
-        let export = Export::arc(storage);
+        let export = Export::<_, Item>::arc(storage);
         
         // Test all formats
         let json = export.export(Format::Json).await.unwrap();
@@ -603,7 +1328,7 @@ mod tests {
         let binary = export.export(Format::Binary).await.unwrap();
         assert!(!binary.done());
         
-        let config = Config { batch: 100, timeout: 10, compress: false };
+        let config = Config { batch: 100, timeout: 10, compress: false, ..Default::default() };
         let custom = export.export(Format::Custom(config)).await.unwrap();
         assert!(!custom.done());
     }
@@ -614,12 +1339,14 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storage = std::sync::Arc::new(storage);
-        let export = Export::arc(storage);
+        let export = Export::<_, Item>::arc(storage);
         
         let filter = Filter {
             prefix: b"test_".to_vec(),
             limit: Some(50),
             offset: Some(0),
+            cursor: None,
+            since: None,
         };
         
         let stream = export.partial(filter, Format::Json).await.unwrap();
@@ -632,7 +1359,7 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storage = std::sync::Arc::new(storage);
-        let export = Export::arc(storage);
+        let export = Export::<_, Item>::arc(storage);
         
         // Test concurrent exports
         let mut handles = Vec::new();
@@ -657,7 +1384,7 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storage = std::sync::Arc::new(storage);
-        let export = Export::arc(storage);
+        let export = Export::<_, Item>::arc(storage);
         
         // Test performance với nhiều format
         let start = std::time::Instant::now();
@@ -677,7 +1404,7 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storage = std::sync::Arc::new(storage);
-        let export = Export::arc(storage);
+        let export = Export::<_, Item>::arc(storage);
         
         // Test recovery từ lỗi
         let mut stream = export.export(Format::Json).await.unwrap();
@@ -694,7 +1421,7 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storagearc = std::sync::Arc::new(storage);
-        let export = Export::arc(storagearc.clone());
+        let export = Export::<_, Item>::arc(storagearc.clone());
         
         // Test integration với storage
         let items = items(10);
@@ -712,7 +1439,7 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storagearc = std::sync::Arc::new(storage);
-        let export = Export::arc(storagearc.clone());
+        let export = Export::<_, Item>::arc(storagearc.clone());
         
         // Test speed với large data
         let items = items(1000);
@@ -734,7 +1461,7 @@ mod tests {
         let path = dir.path().to_str().unwrap();
         let storage = Sled::new(path).unwrap();
         let storagearc = std::sync::Arc::new(storage);
-        let export = Export::arc(storagearc.clone());
+        let export = Export::<_, Item>::arc(storagearc.clone());
         
         // Test group operations
         let items = items(100);
@@ -746,9 +1473,357 @@ mod tests {
             prefix: b"test_".to_vec(),
             limit: Some(50),
             offset: Some(0),
+            cursor: None,
+            since: None,
         };
         
         let stream = export.partial(filter, Format::Json).await.unwrap();
         assert!(!stream.done());
     }
+
+    /// Đọc hết nội dung đã đệm của stream
+    fn drain(mut stream: Stream) -> Vec<u8> {
+        let mut out = Vec::new();
+        while let Some(chunk) = stream.read(1024) {
+            out.extend(chunk);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn csv() {
+        let dir = tempdir().unwrap();
+        let storage = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let item = Item { id: Id::new_v4(), name: "a, \"b\"\nc".to_string(), value: 7 };
+        storage.insert(item.clone()).await.unwrap();
+
+        let export = Export::<_, Item>::default(storage.clone());
+        let text = String::from_utf8(drain(export.export(Format::Csv).await.unwrap())).unwrap();
+        assert_eq!(text, format!("id,name\n{},\"a, \"\"b\"\"\nc\"\n", item.id));
+
+        // Đọc lại bằng csv: giá trị còn nguyên
+        let mut reader = csv::Reader::from_reader(text.as_bytes());
+        let back: Vec<Brief> = reader.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(back, vec![item.summary()]);
+
+        let export = Export::<_, Item>::default(storage).view(View::Full);
+        let text = String::from_utf8(drain(export.export(Format::Csv).await.unwrap())).unwrap();
+        assert!(text.starts_with("id,name,value\n"));
+    }
+
+    #[tokio::test]
+    async fn text() {
+        let dir = tempdir().unwrap();
+        let storage = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let a = Item { id: Id::new_v4(), name: "a | b\nc".to_string(), value: 1 };
+        let b = Item { id: Id::new_v4(), name: "tab\there".to_string(), value: 2 };
+        storage.mass(Box::new(vec![a.clone(), b.clone()].into_iter())).await.unwrap();
+
+        // NDJSON: mỗi dòng một mục
+        let text = String::from_utf8(drain(storage.clone().exportas::<Item>(Format::Ndjson).await.unwrap())).unwrap();
+        let mut back: Vec<Brief> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        back.sort_by_key(|b| b.name.clone());
+        assert_eq!(back, vec![a.summary(), b.summary()]);
+
+        // Markdown: tiêu đề, dòng phân cách, ô được escape; Filter vẫn áp dụng
+        let filter = Filter { prefix: b"idx_1".to_vec(), limit: None, offset: None, cursor: None, since: None };
+        let export = Export::<_, Item>::default(storage.clone());
+        let text = String::from_utf8(drain(export.partial(filter, Format::Markdown).await.unwrap())).unwrap();
+        assert_eq!(text, format!("| id | name |\n| --- | --- |\n| {} | a \\| b<br>c |\n", a.id));
+
+        // TSV: phân cách bằng tab, ô chứa tab được quote
+        let export = Export::<_, Item>::default(storage).view(View::Full);
+        let filter = Filter { prefix: b"idx_2".to_vec(), limit: None, offset: None, cursor: None, since: None };
+        let text = String::from_utf8(drain(export.partial(filter, Format::Tsv).await.unwrap())).unwrap();
+        assert_eq!(text, format!("id\tname\tvalue\n{}\t\"tab\there\"\t2\n", b.id));
+    }
+
+    #[tokio::test]
+    async fn view() {
+        let dir = tempdir().unwrap();
+        let storage = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let all = items(25);
+        storage.mass(Box::new(all.clone().into_iter())).await.unwrap();
+
+        let config = Config { batch: 4, timeout: 10, compress: false, ..Default::default() };
+        let export: Export<_, Item> = Builder::new().config(config).view(View::Full).build(storage.clone());
+        let full: Vec<Item> = serde_json::from_slice(&drain(export.export(Format::Json).await.unwrap())).unwrap();
+        assert_eq!(full.len(), 25);
+
+        // Tiền tố áp dụng trên khóa chỉ mục: idx_1, idx_10..idx_19
+        let filter = Filter { prefix: b"idx_1".to_vec(), limit: None, offset: None, cursor: None, since: None };
+        let export = Export::<_, Item>::default(storage);
+        let briefs: Vec<Brief> = serde_json::from_slice(&drain(export.partial(filter, Format::Json).await.unwrap())).unwrap();
+        assert_eq!(briefs.len(), 11);
+    }
+
+    #[tokio::test]
+    async fn live() {
+        use crate::storage::layer::{Measure, Stack};
+        use tokio::io::AsyncReadExt;
+
+        let dir = tempdir().unwrap();
+        let registry = crate::Registry::new();
+        let storage = Stack::new(Sled::new(dir.path().to_str().unwrap()).unwrap())
+            .layer(Measure::new(registry.clone()))
+            .build();
+        storage.mass(Box::new(items(200).into_iter())).await.unwrap();
+        let pages = || registry.get("test_items.records").count();
+
+        let config = Config { batch: 5, timeout: 10, compress: false, ..Default::default() };
+        let export: Export<_, Item> = Builder::new().config(config).build(storage);
+        let mut stream = export.stream(Filter::default(), Format::Json);
+        assert_eq!(stream.next().await.unwrap().unwrap(), b"[".to_vec());
+
+        // Người đọc đứng yên: nguồn dừng khi kênh đầy thay vì đọc hết 40 trang
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(pages() <= DEPTH as u64 + 2, "{}", pages());
+
+        let mut rest = b"[".to_vec();
+        stream.read_to_end(&mut rest).await.unwrap();
+        let briefs: Vec<Brief> = serde_json::from_slice(&rest).unwrap();
+        assert_eq!(briefs.len(), 200);
+        assert!(stream.done());
+        assert_eq!(pages(), 41);
+
+        // Bỏ stream giữa chừng: task nền dừng lại
+        let stream = export.stream(Filter::default(), Format::Csv);
+        drop(stream);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(pages() <= 41 + 2);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Tag {
+        id: Id,
+        group: u8,
+    }
+
+    impl Entity for Tag {
+        const NAME: &'static str = "test_tags";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = u8;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index {
+            let mut index = vec![self.group];
+            index.extend_from_slice(self.id.as_bytes());
+            index
+        }
+        fn summary(&self) -> Self::Summary { self.group }
+        fn locate(index: &[u8]) -> Option<Self::Key> { Id::from_slice(index.get(1..)?).ok() }
+    }
+
+    #[tokio::test]
+    async fn indexed() {
+        use crate::storage::layer::{Measure, Stack};
+
+        let dir = tempdir().unwrap();
+        let registry = crate::Registry::new();
+        let storage = Stack::new(Sled::new(dir.path().to_str().unwrap()).unwrap())
+            .layer(Measure::new(registry.clone()))
+            .build();
+        let tags: Vec<Tag> = (0..200).map(|i| Tag { id: Id::new_v4(), group: (i % 10) as u8 }).collect();
+        storage.mass(Box::new(tags.clone().into_iter())).await.unwrap();
+        let count = |op: &str| registry.get(&format!("test_tags.{}", op)).count();
+        let config = Config { batch: 4, ..Default::default() };
+
+        // Bản tóm tắt lấy thẳng từ chỉ mục: không đọc bản ghi nào
+        let filter = Filter { prefix: vec![3], ..Default::default() };
+        let export: Export<_, Tag> = Builder::new().config(config.clone()).filter(filter.clone()).build(storage.clone());
+        let groups: Vec<u8> = serde_json::from_slice(&drain(export.export(Format::Json).await.unwrap())).unwrap();
+        assert_eq!(groups, vec![3; 20]);
+        assert_eq!((count("records"), count("fetch")), (0, 0));
+
+        // Thực thể đầy đủ: chỉ đọc đúng những bản ghi khớp, tiếp tục được theo cursor
+        let export: Export<_, Tag> = Builder::new().config(config).view(View::Full).build(storage.clone());
+        let mut stream = export.stream(Filter { limit: Some(7), ..filter.clone() }, Format::Ndjson);
+        let mut text = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            text.extend(chunk.unwrap());
+        }
+        let cursor = stream.cursor().cloned();
+        let rest = drain(export.partial(Filter { cursor, ..filter }, Format::Ndjson).await.unwrap());
+        text.extend(rest);
+        let mut found: Vec<Tag> = String::from_utf8(text).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        let mut expected: Vec<Tag> = tags.into_iter().filter(|t| t.group == 3).collect();
+        found.sort_by_key(|t| t.id);
+        expected.sort_by_key(|t| t.id);
+        assert_eq!(found, expected);
+        assert_eq!((count("records"), count("fetch")), (0, 20));
+    }
+
+    /// Đọc hết stream NDJSON, trả về tên các mục và cursor cuối
+    async fn names(mut stream: Stream) -> (Vec<String>, Option<Cursor>) {
+        let mut out = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            out.extend(chunk.unwrap());
+        }
+        let names = String::from_utf8(out).unwrap().lines()
+            .map(|l| serde_json::from_str::<Brief>(l).unwrap().name)
+            .collect();
+        (names, stream.cursor().cloned())
+    }
+
+    #[tokio::test]
+    async fn cursor() {
+        let dir = tempdir().unwrap();
+        let storage = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        storage.mass(Box::new(items(30).into_iter())).await.unwrap();
+        let config = Config { batch: 4, timeout: 10, compress: false, ..Default::default() };
+        let export: Export<_, Item> = Builder::new().config(config).build(storage);
+        let (all, _) = names(export.stream(Filter::default(), Format::Ndjson)).await;
+        assert_eq!(all.len(), 30);
+
+        // Offset
+        let filter = Filter { offset: Some(5), limit: Some(3), ..Default::default() };
+        assert_eq!(names(export.stream(filter, Format::Ndjson)).await.0, all[5..8].to_vec());
+
+        // Phân trang bằng cursor, kể cả khi limit dừng giữa trang
+        let mut paged = Vec::new();
+        let mut cursor = None;
+        for _ in 0..10 {
+            let filter = Filter { limit: Some(7), cursor: cursor.clone(), ..Default::default() };
+            let (page, next) = names(export.stream(filter, Format::Ndjson)).await;
+            if page.is_empty() {
+                break;
+            }
+            paged.extend(page);
+            cursor = next;
+        }
+        assert_eq!(paged, all);
+
+        // Xuất bị ngắt sau hai trang rồi tiếp tục
+        let mut stream = export.stream(Filter::default(), Format::Ndjson);
+        let mut head = Vec::new();
+        for _ in 0..2 {
+            head.extend(stream.next().await.unwrap().unwrap());
+        }
+        let cursor = stream.cursor().cloned();
+        drop(stream);
+        let (rest, _) = names(export.stream(Filter { cursor, ..Default::default() }, Format::Ndjson)).await;
+        let head: Vec<String> = String::from_utf8(head).unwrap().lines()
+            .map(|l| serde_json::from_str::<Brief>(l).unwrap().name)
+            .collect();
+        assert_eq!([head, rest].concat(), all);
+
+        // Cursor hỏng bị từ chối
+        let filter = Filter { cursor: Some(Cursor::from("zz".to_string())), ..Default::default() };
+        assert!(matches!(export.partial(filter, Format::Json).await, Err(Error::Validation(_))));
+    }
+
+    /// Đọc hết stream, trả về nội dung và mốc xuất tăng dần
+    async fn marked(mut stream: Stream) -> (String, Option<Mark>) {
+        let mut out = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            out.extend(chunk.unwrap());
+        }
+        (String::from_utf8(out).unwrap(), stream.mark().cloned())
+    }
+
+    #[tokio::test]
+    async fn delta() {
+        let dir = tempdir().unwrap();
+        let storage = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let data = items(10);
+        storage.mass(Box::new(data.clone().into_iter())).await.unwrap();
+        let config = Config { batch: 4, timeout: 10, compress: false, ..Default::default() };
+        let export: Export<_, Item> = Builder::new().config(config).build(storage.clone());
+
+        // Xuất đầy đủ trả về mốc; dừng vì limit thì không
+        let (_, first) = marked(export.stream(Filter::default(), Format::Csv)).await;
+        let first = first.unwrap();
+        let (_, none) = marked(export.stream(Filter { limit: Some(3), ..Default::default() }, Format::Csv)).await;
+        assert!(none.is_none());
+
+        storage.update::<Item, _>(data[0].id, |mut i| { i.name = "changed".to_string(); i }).await.unwrap();
+        storage.delete::<Item>(data[1].id).await.unwrap();
+        let added = Item { id: Id::new_v4(), name: "added".to_string(), value: 50 };
+        storage.insert(added.clone()).await.unwrap();
+
+        // CSV: cột `deleted` trống ở dòng đổi, dòng xóa chỉ có khóa
+        let since = Filter { since: Some(first.clone()), ..Default::default() };
+        let (text, second) = marked(export.stream(since.clone(), Format::Csv)).await;
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines, vec![
+            "id,name,deleted".to_string(),
+            format!("{},changed,", data[0].id),
+            format!("{},added,", added.id),
+            format!(",,{}", data[1].id),
+        ]);
+        let second = second.unwrap();
+        assert_ne!(second, first);
+
+        // JSON: bản ghi xóa thành `{"deleted": khóa}`
+        let (text, _) = marked(export.stream(since.clone(), Format::Json)).await;
+        let json: Vec<serde_json::Value> = serde_json::from_str(&text).unwrap();
+        assert_eq!(json.len(), 3);
+        assert_eq!(json[2], serde_json::json!({ "deleted": data[1].id }));
+
+        // Không có gì mới sau mốc thứ hai; mốc giữ nguyên
+        let (text, third) = marked(export.stream(Filter { since: Some(second.clone()), ..Default::default() }, Format::Csv)).await;
+        assert!(text.is_empty());
+        assert_eq!(third, Some(second));
+
+        // Limit chia lần xuất; bản ghi xóa đi theo khoảng của mốc
+        let (text, mark) = marked(export.stream(Filter { limit: Some(1), ..since.clone() }, Format::Ndjson)).await;
+        assert_eq!(text.lines().count(), 1);
+        let (text, _) = marked(export.stream(Filter { since: mark, ..Default::default() }, Format::Ndjson)).await;
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.contains("added") && text.contains("deleted"));
+
+        // Tiền tố áp dụng cả cho bản ghi đã xóa (theo khóa chỉ mục lúc xóa)
+        let (text, _) = marked(export.stream(Filter { prefix: b"idx_1".to_vec(), ..since.clone() }, Format::Ndjson)).await;
+        assert_eq!(text.lines().collect::<Vec<_>>(), vec![format!("{{\"deleted\":\"{}\"}}", data[1].id)]);
+
+        // Từ thời điểm 0: mọi bản ghi còn lại và bản ghi đã xóa
+        let (text, _) = marked(export.stream(Filter { since: Some(Mark::time(0)), ..Default::default() }, Format::Ndjson)).await;
+        assert_eq!(text.lines().count(), 11);
+
+        // Mốc hỏng hoặc dùng cùng cursor bị từ chối
+        let wrong = Filter { since: Some(Mark::from("abc".to_string())), ..Default::default() };
+        assert!(matches!(export.partial(wrong, Format::Json).await, Err(Error::Validation(_))));
+        let cursor = Cursor::new(&data[0].id).unwrap();
+        let both = Filter { cursor: Some(cursor), ..since };
+        assert!(matches!(export.partial(both, Format::Json).await, Err(Error::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn compress() {
+        use std::io::Read;
+
+        let dir = tempdir().unwrap();
+        let storage = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        storage.mass(Box::new(items(200).into_iter())).await.unwrap();
+        let plain: Export<_, Item> = Builder::new().config(Config { batch: 16, ..Default::default() }).build(storage.clone());
+
+        for format in [Format::Json, Format::Csv, Format::Ndjson, Format::Binary] {
+            let expected = drain(plain.export(format.clone()).await.unwrap());
+            for codec in [Compression::Gzip, Compression::Zstd] {
+                let config = Config { batch: 16, compress: true, codec, ..Default::default() };
+                let export: Export<_, Item> = Builder::new().config(config).build(storage.clone());
+                let mut stream = export.stream(Filter::default(), format.clone());
+                let mut packed = Vec::new();
+                while let Some(chunk) = stream.next().await {
+                    packed.extend(chunk.unwrap());
+                }
+                assert_eq!(stream.uncompressed(), expected.len() as u64);
+                assert_eq!(stream.compressed(), packed.len() as u64);
+                assert!(stream.compressed() < stream.uncompressed(), "{:?} {:?}", format, codec);
+
+                let mut unpacked = Vec::new();
+                match codec {
+                    Compression::Gzip => flate2::read::GzDecoder::new(&packed[..]).read_to_end(&mut unpacked).unwrap(),
+                    Compression::Zstd => zstd::stream::read::Decoder::new(&packed[..]).unwrap().read_to_end(&mut unpacked).unwrap(),
+                };
+                assert_eq!(unpacked, expected, "{:?} {:?}", format, codec);
+            }
+        }
+
+        // `Custom` nén theo cấu hình nhúng của nó
+        let custom = Config { batch: 300, compress: true, codec: Compression::Zstd, ..Default::default() };
+        let packed = drain(plain.export(Format::Custom(custom)).await.unwrap());
+        assert!(packed.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]));
+    }
 }
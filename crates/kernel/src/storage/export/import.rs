@@ -0,0 +1,508 @@
+//! Khôi phục dữ liệu từ tệp do `Export` ghi ra.
+//!
+//! Đọc được mọi `Format` mà `Export` ghi (kể cả phong bì `Custom` kèm `Config` nhúng bên trong),
+//! với điều kiện tệp được xuất bằng `View::Full`: bản tóm tắt không đủ để dựng lại thực thể.
+//! Tệp được đọc dần: NDJSON, CSV/TSV, Markdown và Binary giải mã từng bản ghi, mỗi lô được kiểm tra
+//! khóa theo `Policy` rồi ghi qua `Storage::mass` trước khi đọc lô kế tiếp, nên bộ nhớ chỉ phụ thuộc
+//! kích thước lô. JSON và phong bì `Custom` là một tài liệu duy nhất nên được giải mã trọn.
+//! Với `Policy::Fail`, một lượt đọc riêng (không ghi) kiểm tra mọi khóa trước, rồi tệp được đọc lại
+//! từ đầu để ghi. Sau cùng số bản ghi trong store trước và sau khi ghi được đối chiếu.
+//! Tệp nén gzip/zstd được nhận ra qua magic bytes và gỡ nén tự động.
+//!
+//! ```rust,ignore
+//! let file = std::fs::File::open("tasks.ndjson")?;
+//! let report = Import::<_, task::Entry>::new(store).policy(Policy::Skip).restore(file, Format::Ndjson).await?;
+//! println!("{}", report);
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt::{self, Debug};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::error::Fault;
+use crate::storage::entity::Entity;
+use crate::storage::export::{Config, Format};
+use crate::storage::Storage;
+use crate::Error;
+
+/// Cách xử lý bản ghi có khóa đã tồn tại trong store (hoặc lặp lại trong tệp)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Policy {
+    /// Giữ bản ghi đang có, bỏ qua bản ghi trong tệp
+    Skip,
+    /// Ghi đè bằng bản ghi trong tệp
+    Overwrite,
+    /// Từ chối cả lần khôi phục, không ghi gì, khi khóa trong tệp đã có trong store (mặc định).
+    /// Khóa lặp lại trong tệp không phải xung đột: bản xuất hiện sau thắng.
+    #[default]
+    Fail,
+}
+
+/// Kết quả một lần khôi phục
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Số bản ghi đọc được từ tệp
+    pub read: usize,
+    /// Số bản ghi mới
+    pub inserted: usize,
+    /// Số bản ghi đã có bị ghi đè
+    pub overwritten: usize,
+    /// Số bản ghi bị bỏ qua vì khóa đã tồn tại
+    pub skipped: usize,
+    /// Số bản ghi trong store trước khi ghi (khi có bước đối chiếu)
+    pub before: Option<usize>,
+    /// Số bản ghi trong store sau khi ghi (khi có bước đối chiếu)
+    pub after: Option<usize>,
+    /// `Config` nhúng trong phong bì `Custom`
+    pub config: Option<Config>,
+}
+
+impl Report {
+    /// Số bản ghi sau khi ghi đúng bằng trước cộng số bản ghi mới; true nếu bỏ qua bước đối chiếu
+    pub fn verified(&self) -> bool {
+        match (self.before, self.after) {
+            (Some(before), Some(after)) => after == before + self.inserted,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bản ghi: {} thêm mới, {} ghi đè, {} bỏ qua",
+            self.read, self.inserted, self.overwritten, self.skipped
+        )?;
+        if let (Some(before), Some(after)) = (self.before, self.after) {
+            write!(f, "; store {} -> {}", before, after)?;
+        }
+        Ok(())
+    }
+}
+
+/// Khôi phục thực thể `E` vào storage từ tệp export
+pub struct Import<S, E> {
+    /// Storage đích
+    storage: Arc<S>,
+    /// Cách xử lý khóa đã tồn tại
+    policy: Policy,
+    /// Có đối chiếu số bản ghi sau khi ghi không
+    verify: bool,
+    /// Loại thực thể được khôi phục
+    entity: PhantomData<fn() -> E>,
+}
+
+impl<S, E> Import<S, E> {
+    /// Tạo import với policy `Fail` và có đối chiếu
+    pub fn new(storage: S) -> Self {
+        Self::arc(Arc::new(storage))
+    }
+
+    /// Tạo import từ Arc storage
+    pub fn arc(storage: Arc<S>) -> Self {
+        Self { storage, policy: Policy::default(), verify: true, entity: PhantomData }
+    }
+
+    /// Chọn cách xử lý khóa đã tồn tại
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Bật/tắt bước đối chiếu số bản ghi (phải quét toàn bộ tree hai lần)
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+}
+
+impl<S, E> Import<S, E>
+where
+    S: Storage,
+    E: Entity,
+    E::Key: Debug + serde::Serialize,
+    E::Index: Debug,
+{
+    /// Đọc tệp theo `format` từng lô, kiểm tra khóa theo policy rồi ghi lô đó.
+    ///
+    /// Với `Policy::Fail`, mọi xung đột được phát hiện trong lượt đọc đầu (không ghi) nên store
+    /// không bị ghi dở; `reader` được tua lại vị trí ban đầu để đọc lần hai.
+    /// Bước đối chiếu thất bại (ví dụ có thao tác ghi đồng thời) trả về `Error::Validation`.
+    pub async fn restore<R: Read + Seek>(&self, mut reader: R, format: Format) -> Result<Report, Error> {
+        let start = reader.stream_position()?;
+        if self.policy == Policy::Fail {
+            let (items, config) = decode::<E, _>(unpack(&mut reader)?, &format)?;
+            self.scan(items, size(&config)).await?;
+            reader.seek(SeekFrom::Start(start))?;
+        }
+        let (mut items, config) = decode::<E, _>(unpack(&mut reader)?, &format)?;
+        let batch = size(&config);
+        let mut report = Report { config, ..Default::default() };
+
+        if self.verify {
+            report.before = Some(self.count(batch).await?);
+        }
+        loop {
+            let chunk = items.by_ref().take(batch).collect::<Result<Vec<E>, Error>>()?;
+            if chunk.is_empty() {
+                break;
+            }
+            report.read += chunk.len();
+            self.write(chunk, &mut report).await?;
+        }
+        if self.verify {
+            report.after = Some(self.count(batch).await?);
+            if !report.verified() {
+                return Err(Error::Validation(vec![Fault {
+                    field: "count".to_string(),
+                    message: format!("số bản ghi không khớp sau khi khôi phục: {}", report),
+                }]));
+            }
+        }
+        tracing::info!(entity = E::NAME, read = report.read, inserted = report.inserted, "Khôi phục hoàn tất");
+        Ok(report)
+    }
+
+    /// Lượt đọc không ghi của `Policy::Fail`: mọi khóa trong tệp đã có trong store là một xung đột
+    async fn scan(&self, mut items: Items<'_, E>, batch: usize) -> Result<(), Error> {
+        let mut conflicts = Vec::new();
+        loop {
+            let chunk = items.by_ref().take(batch).collect::<Result<Vec<E>, Error>>()?;
+            if chunk.is_empty() {
+                break;
+            }
+            for entity in chunk {
+                if self.storage.fetch::<E>(entity.key()).await?.is_some() {
+                    conflicts.push(Fault {
+                        field: "key".to_string(),
+                        message: format!("khóa {:?} đã tồn tại", entity.key()),
+                    });
+                }
+            }
+        }
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Validation(conflicts)),
+        }
+    }
+
+    /// Phân loại một lô theo policy rồi ghi. Khóa đã có trong store (kể cả do lô trước của cùng tệp ghi)
+    /// hoặc lặp lại trong lô được coi là đã tồn tại và bị ghi đè, trừ khi policy là `Skip`; với
+    /// `Policy::Fail` lượt đọc đầu đã loại xung đột với store nên đó chỉ có thể là khóa lặp trong tệp.
+    async fn write(&self, chunk: Vec<E>, report: &mut Report) -> Result<(), Error> {
+        let mut seen = HashSet::new();
+        let mut accepted = Vec::with_capacity(chunk.len());
+        for entity in chunk {
+            let repeat = !seen.insert(bincode::serialize(&entity.key())?);
+            let exists = repeat || self.storage.fetch::<E>(entity.key()).await?.is_some();
+            match (exists, self.policy) {
+                (false, _) => report.inserted += 1,
+                (true, Policy::Skip) => {
+                    report.skipped += 1;
+                    continue;
+                }
+                (true, _) => report.overwritten += 1,
+            }
+            accepted.push(entity);
+        }
+        self.storage.mass(Box::new(accepted.into_iter())).await
+    }
+
+    /// Đếm số bản ghi trong store theo từng trang
+    async fn count(&self, batch: usize) -> Result<usize, Error> {
+        let mut total = 0;
+        let mut after = None;
+        loop {
+            let page = self.storage.records::<E>(after.take(), batch).await?;
+            total += page.len();
+            if page.len() < batch {
+                return Ok(total);
+            }
+            after = page.last().map(Entity::key);
+        }
+    }
+}
+
+/// Gỡ nén nếu dữ liệu bắt đầu bằng magic bytes của gzip hoặc zstd, nếu không đọc nguyên
+fn unpack<'a, R: Read + 'a>(reader: R) -> Result<Box<dyn Read + 'a>, Error> {
+    const GZIP: [u8; 2] = [0x1f, 0x8b];
+    const ZSTD: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+    let mut reader = BufReader::new(reader);
+    let head = reader.fill_buf()?;
+    if head.starts_with(&GZIP) {
+        // Nhiều member gzip nối tiếp vẫn đọc được như một luồng
+        Ok(Box::new(flate2::read::MultiGzDecoder::new(reader)))
+    } else if head.starts_with(&ZSTD) {
+        Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Số bản ghi mỗi lô: `Config::batch` nhúng trong tệp nếu có
+fn size(config: &Option<Config>) -> usize {
+    config.as_ref().map_or(Config::default().batch, |c| c.batch).max(1)
+}
+
+/// Các bản ghi giải mã dần từ tệp
+type Items<'a, T> = Box<dyn Iterator<Item = Result<T, Error>> + 'a>;
+
+/// Phong bì của `Format::Custom`
+#[derive(Deserialize)]
+struct Envelope<T> {
+    config: Config,
+    data: Vec<T>,
+}
+
+/// Giải mã tệp thành luồng các mục, kèm `Config` nếu là phong bì `Custom`.
+/// Trừ JSON và `Custom`, mỗi mục chỉ được đọc khi iterator tới nó.
+fn decode<'a, T: DeserializeOwned + 'a, R: Read + 'a>(reader: R, format: &Format) -> Result<(Items<'a, T>, Option<Config>), Error> {
+    match format {
+        Format::Json => {
+            let items: Vec<T> = serde_json::from_reader(reader)?;
+            Ok((Box::new(items.into_iter().map(Ok)), None))
+        }
+        Format::Custom(_) => {
+            let envelope: Envelope<T> = serde_json::from_reader(reader)?;
+            Ok((Box::new(envelope.data.into_iter().map(Ok)), Some(envelope.config)))
+        }
+        Format::Ndjson => {
+            let lines = BufReader::new(reader).lines();
+            let items = lines.filter_map(|line| match line {
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => Some(serde_json::from_str(&line).map_err(Error::from)),
+                Err(e) => Some(Err(e.into())),
+            });
+            Ok((Box::new(items), None))
+        }
+        Format::Csv => Ok((delimited(reader, b','), None)),
+        Format::Tsv => Ok((delimited(reader, b'\t'), None)),
+        Format::Binary => {
+            let mut reader = BufReader::new(reader);
+            let items = std::iter::from_fn(move || {
+                match reader.fill_buf() {
+                    Ok([]) => None,
+                    Ok(_) => Some(frame(&mut reader)),
+                    Err(e) => Some(Err(e.into())),
+                }
+            });
+            Ok((Box::new(items), None))
+        }
+        Format::Markdown => Ok((table(reader), None)),
+    }
+}
+
+/// Đọc một khung của `Format::Binary`: bincode của bytes bincode thực thể
+fn frame<T: DeserializeOwned, R: Read>(reader: &mut R) -> Result<T, Error> {
+    let frame: Vec<u8> = bincode::deserialize_from(reader)?;
+    Ok(bincode::deserialize(&frame)?)
+}
+
+/// Đọc dần CSV/TSV có dòng tiêu đề
+fn delimited<'a, T: DeserializeOwned + 'a, R: Read + 'a>(reader: R, delimiter: u8) -> Items<'a, T> {
+    let reader = csv::ReaderBuilder::new().delimiter(delimiter).from_reader(reader);
+    Box::new(reader.into_deserialize().map(|row| row.map_err(Error::from)))
+}
+
+/// Đọc dần bảng Markdown do `Export` ghi: dòng đầu là tiêu đề, dòng thứ hai là phân cách.
+/// `\|` trở lại thành `|` và `<br>` thành xuống dòng.
+fn table<'a, T: DeserializeOwned + 'a, R: Read + 'a>(reader: R) -> Items<'a, T> {
+    let mut header: Option<csv::StringRecord> = None;
+    let rows = BufReader::new(reader).lines().filter_map(move |line| {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e.into())),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        let row = cells(line);
+        match &header {
+            None => {
+                header = Some(csv::StringRecord::from(row));
+                None
+            }
+            Some(_) if row.iter().all(|cell| cell == "---") => None,
+            Some(header) => Some(csv::StringRecord::from(row).deserialize(Some(header)).map_err(Error::from)),
+        }
+    });
+    Box::new(rows)
+}
+
+/// Tách một dòng bảng thành các ô, bỏ `|` hai đầu
+fn cells(line: &str) -> Vec<String> {
+    let inner = line.strip_prefix('|').unwrap_or(line);
+    let inner = inner.strip_suffix('|').filter(|rest| !rest.ends_with('\\')).unwrap_or(inner);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells.into_iter().map(|cell| cell.trim().replace("<br>", "\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::export::{Builder, Compression, Export, Exportable, View};
+    use crate::{Id, Sled};
+    use serde::Serialize;
+    use tempfile::tempdir;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Item {
+        id: Id,
+        name: String,
+        value: u32,
+    }
+
+    impl Entity for Item {
+        const NAME: &'static str = "restore_items";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index { self.id.as_bytes().to_vec() }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    fn items(count: usize) -> Vec<Item> {
+        (0..count).map(|i| Item { id: Id::new_v4(), name: format!("a | b\nc {}", i), value: i as u32 }).collect()
+    }
+
+    /// Xuất toàn bộ `Item` đầy đủ ra bytes
+    async fn dump(store: &Sled, format: Format) -> Vec<u8> {
+        let export: Export<_, Item> = Builder::new().view(View::Full).build(store.clone());
+        let mut stream = export.export(format).await.unwrap();
+        let mut out = Vec::new();
+        while let Some(chunk) = stream.read(1024) {
+            out.extend(chunk);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn restore() {
+        let source = Sled::new(tempdir().unwrap().path().to_str().unwrap()).unwrap();
+        let mut all = items(12);
+        source.mass(Box::new(all.clone().into_iter())).await.unwrap();
+        all.sort_by_key(|i| i.id);
+
+        // `Custom` chỉ xuất tối đa `config.batch` mục
+        let config = Config { batch: 50, timeout: 10, compress: false, ..Default::default() };
+        let formats = [Format::Json, Format::Csv, Format::Tsv, Format::Ndjson, Format::Binary, Format::Markdown, Format::Custom(config)];
+        for format in formats {
+            let bytes = dump(&source, format.clone()).await;
+            let dir = tempdir().unwrap();
+            let target = Sled::new(dir.path().to_str().unwrap()).unwrap();
+            let report = Import::<_, Item>::new(target.clone()).restore(std::io::Cursor::new(&bytes), format.clone()).await.unwrap();
+            assert_eq!((report.read, report.inserted, report.before, report.after), (12, 12, Some(0), Some(12)), "{:?}", format);
+            if let Format::Custom(_) = format {
+                assert_eq!(report.config.unwrap().batch, 50);
+            }
+            let mut back = target.records::<Item>(None, 100).await.unwrap();
+            back.sort_by_key(|i| i.id);
+            assert_eq!(back, all, "{:?}", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn policy() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let all = items(4);
+        store.mass(Box::new(all.clone().into_iter())).await.unwrap();
+        let bytes = dump(&store, Format::Ndjson).await;
+        let changed = Item { name: "đổi".to_string(), ..all[0].clone() };
+        store.insert(changed.clone()).await.unwrap();
+        let fresh = Item { id: Id::new_v4(), name: "mới".to_string(), value: 9 };
+        let mut bytes = bytes;
+        bytes.extend(serde_json::to_vec(&fresh).unwrap());
+
+        // Fail: không ghi gì
+        let import = Import::<_, Item>::new(store.clone());
+        match import.restore(std::io::Cursor::new(&bytes), Format::Ndjson).await {
+            Err(Error::Validation(faults)) => assert_eq!(faults.len(), 4),
+            other => panic!("{:?}", other.map(|r| r.to_string())),
+        }
+        assert!(store.fetch::<Item>(fresh.id).await.unwrap().is_none());
+
+        // Skip: giữ bản ghi đang có
+        let report = import.policy(Policy::Skip).restore(std::io::Cursor::new(&bytes), Format::Ndjson).await.unwrap();
+        assert_eq!((report.inserted, report.skipped), (1, 4));
+        assert!(report.verified());
+        assert_eq!(store.fetch::<Item>(all[0].id).await.unwrap().unwrap().name, "đổi");
+
+        // Overwrite: bản ghi trong tệp thắng
+        let import = Import::<_, Item>::new(store.clone()).policy(Policy::Overwrite);
+        let report = import.restore(std::io::Cursor::new(&bytes), Format::Ndjson).await.unwrap();
+        assert_eq!((report.inserted, report.overwritten, report.after), (0, 5, Some(5)));
+        assert_eq!(store.fetch::<Item>(all[0].id).await.unwrap().unwrap().name, all[0].name);
+    }
+
+    #[tokio::test]
+    async fn batches() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let existing = items(1).remove(0);
+        store.insert(existing.clone()).await.unwrap();
+
+        // Lô mặc định 1000 mục: bản lặp của mục đầu và khóa đã tồn tại nằm ở lô thứ hai
+        let all = items(Config::default().batch + 1);
+        let repeat = Item { name: "lặp".to_string(), ..all[0].clone() };
+        let mut bytes = Vec::new();
+        for item in all.iter().chain([&repeat, &existing]) {
+            bytes.extend(serde_json::to_vec(item).unwrap());
+            bytes.push(b'\n');
+        }
+
+        // Fail: xung đột ở lô cuối vẫn chặn cả lô đầu
+        let import = Import::<_, Item>::new(store.clone());
+        match import.restore(std::io::Cursor::new(&bytes), Format::Ndjson).await {
+            Err(Error::Validation(faults)) => assert_eq!(faults.len(), 1),
+            other => panic!("{:?}", other.map(|r| r.to_string())),
+        }
+        assert_eq!(store.records::<Item>(None, 10).await.unwrap().len(), 1);
+
+        // Overwrite: bản lặp ở lô sau ghi đè bản ghi của lô trước
+        let report = import.policy(Policy::Overwrite).restore(std::io::Cursor::new(&bytes), Format::Ndjson).await.unwrap();
+        assert_eq!((report.read, report.inserted, report.overwritten), (all.len() + 2, all.len(), 2));
+        assert_eq!((report.before, report.after), (Some(1), Some(all.len() + 1)));
+        assert_eq!(store.fetch::<Item>(all[0].id).await.unwrap().unwrap().name, "lặp");
+    }
+
+    #[tokio::test]
+    async fn unpack() {
+        let source = Sled::new(tempdir().unwrap().path().to_str().unwrap()).unwrap();
+        source.mass(Box::new(items(20).into_iter())).await.unwrap();
+        for codec in [Compression::Gzip, Compression::Zstd] {
+            let config = Config { compress: true, codec, ..Default::default() };
+            let export: Export<_, Item> = Builder::new().config(config).view(View::Full).build(source.clone());
+            let mut stream = export.export(Format::Csv).await.unwrap();
+            let mut bytes = Vec::new();
+            while let Some(chunk) = stream.read(1024) {
+                bytes.extend(chunk);
+            }
+
+            let target = Sled::new(tempdir().unwrap().path().to_str().unwrap()).unwrap();
+            let report = Import::<_, Item>::new(target).restore(std::io::Cursor::new(&bytes), Format::Csv).await.unwrap();
+            assert_eq!((report.inserted, report.after), (20, Some(20)), "{:?}", codec);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Sinh định danh cho thực thể.
+//!
+//! `Id::new_v4()` ngẫu nhiên nên bản ghi mới rải khắp tree và liệt kê theo khóa không có
+//! thứ tự ý nghĩa. UUIDv7 đặt thời điểm tạo (mili giây) ở đầu, nên khóa tăng dần theo thời gian,
+//! ghi nối vào cuối tree và đọc lại được thời điểm từ chính khóa.
+//!
+//! Bộ đếm giữ thứ tự trong cùng mili giây (`Sequence`) thuộc về từng store, không dùng chung cả
+//! tiến trình: store này đặt clock ở tương lai không kéo thời điểm trong id của store khác theo.
+//!
+//! ```rust,ignore
+//! let created = store.now();
+//! let id = store.generate::<Entry>(created);   // theo `Entity::SCHEME`, tăng dần trong store
+//! assert_eq!(id::time(&id), Some(created / 1_000_000 * 1_000_000));
+//! ```
+
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use uuid::{ContextV7, NoContext, Timestamp};
+use crate::Id;
+
+/// Cách sinh khóa của một loại thực thể (`Entity::SCHEME`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Scheme {
+    /// UUIDv4 ngẫu nhiên (mặc định, giữ tương thích với dữ liệu cũ)
+    #[default]
+    Random,
+    /// UUIDv7 tăng dần theo thời gian tạo
+    Ordered,
+}
+
+/// Bộ đếm UUIDv7 của một store: các id sinh qua cùng một `Sequence` luôn tăng dần,
+/// kể cả khi nhiều id chung một mili giây hoặc thời điểm lùi lại.
+#[derive(Debug)]
+pub struct Sequence {
+    context: Mutex<ContextV7>,
+}
+
+impl Sequence {
+    /// Tạo bộ đếm mới, độc lập với mọi bộ đếm khác
+    pub fn new() -> Self {
+        Self { context: Mutex::new(ContextV7::new()) }
+    }
+
+    /// Sinh id theo `scheme`; `time` (nano giây Unix) là thời điểm tạo, chỉ dùng với `Ordered`
+    pub fn generate(&self, scheme: Scheme, time: u128) -> Id {
+        match scheme {
+            Scheme::Random => Id::new_v4(),
+            Scheme::Ordered => self.at(time),
+        }
+    }
+
+    /// Sinh UUIDv7 mang thời điểm `time` (làm tròn xuống mili giây).
+    /// Khi `time` lùi lại so với id trước của bộ đếm này, id mới mang thời điểm của id trước kèm bộ đếm tăng.
+    pub fn at(&self, time: u128) -> Id {
+        let (seconds, nanos) = split(time);
+        let context = self.context.lock().unwrap_or_else(|e| e.into_inner());
+        Id::new_v7(Timestamp::from_unix(&*context, seconds, nanos))
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sinh id theo `scheme` không qua bộ đếm nào; `time` (nano giây Unix) chỉ dùng với `Ordered`
+pub fn generate(scheme: Scheme, time: u128) -> Id {
+    match scheme {
+        Scheme::Random => Id::new_v4(),
+        Scheme::Ordered => at(time),
+    }
+}
+
+/// Sinh UUIDv7 mang đúng thời điểm `time` (nano giây Unix, làm tròn xuống mili giây).
+/// Phần còn lại ngẫu nhiên nên các id chung một mili giây không có thứ tự; cần thứ tự thì dùng `Sequence`.
+pub fn at(time: u128) -> Id {
+    let (seconds, nanos) = split(time);
+    Id::new_v7(Timestamp::from_unix(NoContext, seconds, nanos))
+}
+
+/// Sinh UUIDv7 mang thời điểm hiện tại
+pub fn ordered() -> Id {
+    at(crate::storage::time::now())
+}
+
+/// Tách nano giây Unix thành (giây, phần nano giây lẻ)
+fn split(time: u128) -> (u64, u32) {
+    ((time / 1_000_000_000) as u64, (time % 1_000_000_000) as u32)
+}
+
+/// Thời điểm tạo (nano giây Unix, độ chính xác mili giây) lưu trong id có thời gian (v1/v6/v7);
+/// None với id ngẫu nhiên
+pub fn time(id: &Id) -> Option<u128> {
+    let (seconds, nanos) = id.get_timestamp()?.to_unix();
+    Some(seconds as u128 * 1_000_000_000 + nanos as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order() {
+        let sequence = Sequence::new();
+        let time = 1_700_000_000_123_456_789u128;
+        // Cùng thời điểm, hoặc thời điểm lùi lại, vẫn tăng dần
+        let ids: Vec<Id> = (0..1000).map(|i| sequence.at(time - (i % 3) * 1_000_000)).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(sequence.at(time + 1_000_000_000) > ids[999]);
+        assert_eq!(ids[0].get_version_num(), 7);
+    }
+
+    #[test]
+    fn scope() {
+        // Bộ đếm của store này đi trước không ảnh hưởng thời điểm trong id của bộ đếm khác
+        let ahead = Sequence::new();
+        let later = 1_800_000_000_000_000_000u128;
+        ahead.at(later);
+        let moment = 1_700_000_000_123_456_789u128;
+        assert_eq!(time(&Sequence::new().at(moment)), Some(1_700_000_000_123_000_000));
+        assert_eq!(time(&at(moment)), Some(1_700_000_000_123_000_000));
+        assert!(time(&ahead.at(moment)).unwrap() >= later / 1_000_000 * 1_000_000);
+    }
+
+    #[test]
+    fn extract() {
+        let moment = 1_700_000_000_123_456_789u128;
+        assert_eq!(time(&at(moment)), Some(1_700_000_000_123_000_000));
+        assert_eq!(time(&Id::new_v4()), None);
+        assert_eq!(generate(Scheme::Random, moment).get_version_num(), 4);
+        assert_eq!(generate(Scheme::Ordered, moment).get_version_num(), 7);
+        assert_eq!(Sequence::new().generate(Scheme::Ordered, moment).get_version_num(), 7);
+    }
+}
@@ -0,0 +1,396 @@
+//! Nhập dữ liệu CSV vào bất kỳ `Entity` nào.
+//!
+//! Mỗi dòng được đọc thành một `Row` theo tiêu đề cột, rồi `Mapping` chuyển thành thực thể
+//! (kèm xác thực). Dòng lỗi được báo cáo kèm số dòng thay vì dừng cả lần nhập; các dòng hợp lệ
+//! được ghi qua `Storage::mass` theo từng lô `CHUNK` bản ghi ngay trong lúc đọc, nên bộ nhớ chỉ giữ
+//! một lô thực thể cùng khóa đã mã hóa của các dòng đã gặp.
+//!
+//! ```rust,ignore
+//! let file = std::fs::File::open("todo.csv")?;
+//! let outcome = import::<_, task::Entry, _, _>(&store, file, &|row: &Row| {
+//!     Ok(task::Entry { task: row.require("Task")?.to_string(), ..})
+//! }, Options { upsert: true, dry: false }).await?;
+//! println!("{}", outcome);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::io::Read;
+
+use crate::error::Fault;
+use crate::storage::entity::Entity;
+use crate::storage::Storage;
+use crate::{Error, Id};
+
+/// Số bản ghi mỗi lần ghi `Storage::mass`
+const CHUNK: usize = 1000;
+
+/// Một dòng CSV, truy cập theo tên cột
+pub struct Row<'a> {
+    headers: &'a csv::StringRecord,
+    record: &'a csv::StringRecord,
+}
+
+impl<'a> Row<'a> {
+    /// Giá trị của cột (đã bỏ khoảng trắng hai đầu); None nếu không có cột hoặc ô trống
+    pub fn get(&self, column: &str) -> Option<&'a str> {
+        let position = self.headers.iter().position(|h| h.trim() == column)?;
+        self.record.get(position).map(str::trim).filter(|v| !v.is_empty())
+    }
+
+    /// Giá trị của cột, chuỗi rỗng nếu thiếu
+    pub fn text(&self, column: &str) -> String {
+        self.get(column).unwrap_or_default().to_string()
+    }
+
+    /// Giá trị bắt buộc của cột, lỗi xác thực nếu thiếu
+    pub fn require(&self, column: &str) -> Result<&'a str, Error> {
+        self.get(column).ok_or_else(|| Error::Validation(vec![Fault {
+            field: column.to_string(),
+            message: "Cột bắt buộc bị trống hoặc không tồn tại.".to_string(),
+        }]))
+    }
+}
+
+/// Cách chuyển một dòng CSV thành thực thể, kể cả xác thực.
+/// Mọi closure `Fn(&Row) -> Result<E, Error>` đều là một `Mapping`.
+pub trait Mapping<E>: Send + Sync {
+    fn map(&self, row: &Row) -> Result<E, Error>;
+
+    /// Gộp thực thể đọc từ dòng vào bản ghi đã lưu cùng khóa khi upsert.
+    /// Mặc định dòng thay thế hoàn toàn; ghi đè để giữ các trường dòng không mang (ví dụ `created`).
+    fn merge(&self, _row: &Row, _stored: E, entity: E) -> E {
+        entity
+    }
+}
+
+impl<E, F> Mapping<E> for F
+where F: Fn(&Row) -> Result<E, Error> + Send + Sync {
+    fn map(&self, row: &Row) -> Result<E, Error> {
+        self(row)
+    }
+}
+
+/// `Mapping` ghép từ hàm đọc dòng và hàm gộp vào bản ghi đã lưu, ví dụ `Merge(task::parse, task::merge)`
+pub struct Merge<P, G>(pub P, pub G);
+
+impl<E, P, G> Mapping<E> for Merge<P, G>
+where
+    P: Fn(&Row) -> Result<E, Error> + Send + Sync,
+    G: Fn(&Row, E, E) -> E + Send + Sync,
+{
+    fn map(&self, row: &Row) -> Result<E, Error> {
+        (self.0)(row)
+    }
+
+    fn merge(&self, row: &Row, stored: E, entity: E) -> E {
+        (self.1)(row, stored, entity)
+    }
+}
+
+/// Tùy chọn nhập
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Ghi đè bản ghi đã có cùng khóa; nếu tắt, dòng trùng khóa bị từ chối
+    pub upsert: bool,
+    /// Chỉ đọc và kiểm tra, không ghi gì
+    pub dry: bool,
+}
+
+/// Một dòng bị từ chối
+#[derive(Debug, Clone)]
+pub struct Reject {
+    /// Số dòng trong tệp (dòng tiêu đề là 1)
+    pub line: u64,
+    /// Lý do từ chối
+    pub reason: String,
+}
+
+/// Kết quả một lần nhập
+#[derive(Debug, Clone, Default)]
+pub struct Outcome {
+    /// Số dòng dữ liệu đã đọc
+    pub rows: usize,
+    /// Số bản ghi mới
+    pub inserted: usize,
+    /// Số bản ghi đã có bị ghi đè (chỉ khi upsert)
+    pub updated: usize,
+    /// Các dòng bị từ chối
+    pub rejects: Vec<Reject>,
+    /// Lần nhập chạy thử, không ghi gì
+    pub dry: bool,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = if self.dry { " (chạy thử)" } else { "" };
+        write!(
+            f,
+            "{} dòng{}: {} thêm mới, {} ghi đè, {} bị từ chối",
+            self.rows, mode, self.inserted, self.updated, self.rejects.len()
+        )?;
+        for reject in &self.rejects {
+            write!(f, "\n  dòng {}: {}", reject.line, reject.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Id cố định cho giá trị cột ID: giữ nguyên nếu đã là UUID, nếu không suy ra UUIDv5 từ
+/// tên thực thể và giá trị, để nhập lại cùng tệp ra cùng khóa (cần cho upsert).
+pub fn identity(name: &str, raw: &str) -> Id {
+    Id::parse_str(raw).unwrap_or_else(|_| Id::new_v5(&Id::NAMESPACE_OID, format!("{}:{}", name, raw).as_bytes()))
+}
+
+/// Mô tả lỗi của một dòng, liệt kê từng trường nếu là lỗi xác thực
+fn reason(error: &Error) -> String {
+    match error {
+        Error::Validation(faults) => faults.iter().map(|f| f.to_string()).collect::<Vec<_>>().join("; "),
+        other => other.to_string(),
+    }
+}
+
+/// Mô tả lỗi đọc CSV của một dòng
+fn malformed(error: &csv::Error) -> String {
+    match error.kind() {
+        csv::ErrorKind::UnequalLengths { expected_len, len, .. } => {
+            format!("dòng có {} trường nhưng tiêu đề có {} cột (ô chứa dấu phẩy cần đặt trong ngoặc kép)", len, expected_len)
+        }
+        _ => error.to_string(),
+    }
+}
+
+/// Đọc CSV (có dòng tiêu đề) từ `reader`, chuyển từng dòng qua `mapping` và ghi vào `store`.
+///
+/// Khóa trùng trong cùng tệp: khi upsert, dòng sau được gộp (`Mapping::merge`) vào dòng trước
+/// (đang chờ trong lô, hoặc đọc lại từ store nếu lô đó đã ghi); nếu không, dòng sau bị từ chối.
+/// Dòng có số trường khác tiêu đề hoặc lỗi đọc CSV khác (ví dụ UTF-8 hỏng) chỉ làm hỏng dòng đó.
+/// Lỗi ghi trả về `Err` cho cả lần nhập; các lô trước đó đã được ghi.
+pub async fn import<S, E, R, M>(store: &S, reader: R, mapping: &M, options: Options) -> Result<Outcome, Error>
+where
+    S: Storage,
+    E: Entity,
+    E::Key: Debug + serde::Serialize,
+    E::Index: Debug,
+    R: Read,
+    M: Mapping<E>,
+{
+    let mut outcome = Outcome { dry: options.dry, ..Default::default() };
+    let mut csv = csv::ReaderBuilder::new().from_reader(reader);
+    let headers = csv.headers()?.clone();
+
+    // Khóa đã gặp trong tệp; `slots` trỏ khóa của lô đang chờ tới vị trí trong `pending`
+    let mut seen = HashSet::new();
+    let mut slots = HashMap::new();
+    let mut pending: Vec<E> = Vec::new();
+    let mut record = csv::StringRecord::new();
+    loop {
+        let line = csv.position().line();
+        match csv.read_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => {
+                outcome.rows += 1;
+                outcome.rejects.push(Reject { line, reason: malformed(&e) });
+                continue;
+            }
+        }
+        outcome.rows += 1;
+        let line = record.position().map(|p| p.line()).unwrap_or(line);
+        let row = Row { headers: &headers, record: &record };
+        let entity = match mapping.map(&row) {
+            Ok(entity) => entity,
+            Err(e) => {
+                outcome.rejects.push(Reject { line, reason: reason(&e) });
+                continue;
+            }
+        };
+
+        let key = bincode::serialize(&entity.key())?;
+        if seen.contains(&key) {
+            if !options.upsert {
+                outcome.rejects.push(Reject { line, reason: format!("khóa {:?} đã tồn tại", entity.key()) });
+                continue;
+            }
+            if let Some(&slot) = slots.get(&key) {
+                let earlier: &mut E = &mut pending[slot];
+                *earlier = mapping.merge(&row, earlier.clone(), entity);
+                continue;
+            }
+            if options.dry {
+                continue;
+            }
+            // Dòng trước cùng khóa nằm trong lô đã ghi
+            let entity = match store.fetch::<E>(entity.key()).await? {
+                Some(stored) => mapping.merge(&row, stored, entity),
+                None => entity,
+            };
+            slots.insert(key, pending.len());
+            pending.push(entity);
+        } else {
+            let entity = match (store.fetch::<E>(entity.key()).await?, options.upsert) {
+                (Some(_), false) => {
+                    outcome.rejects.push(Reject { line, reason: format!("khóa {:?} đã tồn tại", entity.key()) });
+                    continue;
+                }
+                (Some(stored), true) => {
+                    outcome.updated += 1;
+                    mapping.merge(&row, stored, entity)
+                }
+                (None, _) => {
+                    outcome.inserted += 1;
+                    entity
+                }
+            };
+            seen.insert(key.clone());
+            if options.dry {
+                continue;
+            }
+            slots.insert(key, pending.len());
+            pending.push(entity);
+        }
+
+        if pending.len() >= CHUNK {
+            slots.clear();
+            store.mass(Box::new(std::mem::take(&mut pending).into_iter())).await?;
+        }
+    }
+
+    if !pending.is_empty() {
+        store.mass(Box::new(pending.into_iter())).await?;
+    }
+    tracing::info!(entity = E::NAME, rows = outcome.rows, rejects = outcome.rejects.len(), dry = options.dry, "Nhập CSV hoàn tất");
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sled;
+    use serde::{Deserialize, Serialize};
+    use tempfile::tempdir;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Person {
+        id: u32,
+        name: String,
+        age: u8,
+    }
+
+    impl Entity for Person {
+        const NAME: &'static str = "people";
+        type Key = Vec<u8>;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id.to_be_bytes().to_vec() }
+        fn index(&self) -> Self::Index { self.id.to_be_bytes().to_vec() }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    fn person(row: &Row) -> Result<Person, Error> {
+        let parse = |column: &str| -> Result<u32, Error> {
+            row.require(column)?.parse().map_err(|_| Error::Validation(vec![Fault {
+                field: column.to_string(),
+                message: "không phải số".to_string(),
+            }]))
+        };
+        Ok(Person { id: parse("ID")?, name: row.require("Name")?.to_string(), age: parse("Age")? as u8 })
+    }
+
+    const DATA: &str = "ID,Name,Age\n1,An,30\n2,,20\nx,Bình,40\n3,Chi,25\n1,An mới,31\n";
+
+    #[tokio::test]
+    async fn import() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+
+        let outcome = super::import(&store, DATA.as_bytes(), &person, Options::default()).await.unwrap();
+        assert_eq!(outcome.rows, 5);
+        assert_eq!(outcome.inserted, 2);
+        let lines: Vec<_> = outcome.rejects.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![3, 4, 6]);
+        assert!(outcome.rejects[0].reason.contains("Name"));
+        assert_eq!(store.fetch::<Person>(1u32.to_be_bytes().to_vec()).await.unwrap().unwrap().name, "An");
+
+        // Chạy thử với upsert: không ghi gì
+        let outcome = super::import(&store, DATA.as_bytes(), &person, Options { upsert: true, dry: true }).await.unwrap();
+        assert_eq!((outcome.inserted, outcome.updated), (0, 2));
+        assert_eq!(store.fetch::<Person>(1u32.to_be_bytes().to_vec()).await.unwrap().unwrap().name, "An");
+
+        // Upsert thật: dòng sau cùng khóa thắng
+        let outcome = super::import(&store, DATA.as_bytes(), &person, Options { upsert: true, dry: false }).await.unwrap();
+        assert_eq!(outcome.rejects.len(), 2);
+        assert_eq!(store.fetch::<Person>(1u32.to_be_bytes().to_vec()).await.unwrap().unwrap().name, "An mới");
+        let count = store.query::<Person>(crate::Query { limit: usize::MAX, ..Default::default() }).await.unwrap().count();
+        assert_eq!(count, 2);
+    }
+
+    #[tokio::test]
+    async fn unequal() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        // Dòng 3 thừa trường (dấu phẩy không đặt trong ngoặc kép), dòng 4 thiếu trường
+        let data = "ID,Name,Age\n1,An,30\n2,Bình, con,20\n3,Chi\n4,\"Dung, con\",25\n";
+        let outcome = super::import(&store, data.as_bytes(), &person, Options::default()).await.unwrap();
+        assert_eq!((outcome.rows, outcome.inserted), (4, 2));
+        let lines: Vec<_> = outcome.rejects.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![3, 4]);
+        assert!(outcome.rejects[0].reason.contains("4 trường"));
+        assert_eq!(store.fetch::<Person>(4u32.to_be_bytes().to_vec()).await.unwrap().unwrap().name, "Dung, con");
+    }
+
+    #[tokio::test]
+    async fn merge() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        store.insert(Person { id: 1, name: "An".to_string(), age: 30 }).await.unwrap();
+
+        // Upsert giữ tuổi đã lưu, chỉ lấy tên từ dòng; dòng trùng khóa trong tệp gộp tiếp vào dòng trước
+        let keep = |_: &Row, stored: Person, row: Person| Person { age: stored.age, ..row };
+        let data = "ID,Name,Age\n1,An mới,99\n2,Bình,20\n2,Bình mới,21\n";
+        let options = Options { upsert: true, dry: false };
+        let outcome = super::import(&store, data.as_bytes(), &Merge(person, keep), options).await.unwrap();
+        assert_eq!((outcome.inserted, outcome.updated), (1, 1));
+        let fetch = |id: u32| {
+            let store = store.clone();
+            async move { store.fetch::<Person>(id.to_be_bytes().to_vec()).await.unwrap().unwrap() }
+        };
+        assert_eq!(fetch(1).await, Person { id: 1, name: "An mới".to_string(), age: 30 });
+        assert_eq!(fetch(2).await, Person { id: 2, name: "Bình mới".to_string(), age: 20 });
+    }
+
+    #[tokio::test]
+    async fn chunks() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+
+        // Khóa 1 lặp lại sau khi lô đầu đã ghi: gộp vào bản đã ghi, không đếm thêm
+        let mut data = "ID,Name,Age\n".to_string();
+        for id in 1..=CHUNK + 1 {
+            data.push_str(&format!("{},P{},20\n", id, id));
+        }
+        data.push_str("1,An,99\n");
+        let keep = |_: &Row, stored: Person, row: Person| Person { age: stored.age, ..row };
+        let options = Options { upsert: true, dry: false };
+        let outcome = super::import(&store, data.as_bytes(), &Merge(person, keep), options).await.unwrap();
+        assert_eq!((outcome.rows, outcome.inserted, outcome.updated), (CHUNK + 2, CHUNK + 1, 0));
+        let first = store.fetch::<Person>(1u32.to_be_bytes().to_vec()).await.unwrap().unwrap();
+        assert_eq!(first, Person { id: 1, name: "An".to_string(), age: 20 });
+
+        // Không upsert: dòng lặp bị từ chối dù lô chứa dòng trước đã ghi
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let outcome = super::import(&store, data.as_bytes(), &person, Options::default()).await.unwrap();
+        assert_eq!((outcome.inserted, outcome.rejects.len()), (CHUNK + 1, 1));
+        assert_eq!(outcome.rejects[0].line as usize, CHUNK + 3);
+    }
+
+    #[test]
+    fn identity() {
+        let id = Id::new_v4();
+        assert_eq!(super::identity("tasks", &id.to_string()), id);
+        assert_eq!(super::identity("tasks", "1"), super::identity("tasks", "1"));
+        assert_ne!(super::identity("tasks", "1"), super::identity("memories", "1"));
+    }
+}
@@ -0,0 +1,349 @@
+//! Hệ thống layer (middleware) cho `Storage`, lấy cảm hứng từ tower.
+//!
+//! Mỗi layer bọc một `Storage` bất kỳ và trả về một `Storage` mới, nên có thể
+//! xếp chồng nhiều layer quanh `Sled` mà không phải sửa backend:
+//!
+//! ```rust,ignore
+//! use kernel::storage::layer::{Stack, Trace, Measure, Retry, Memo, Audit};
+//!
+//! let store = Stack::new(Sled::new("./db")?)
+//!     .layer(Retry::new(3, Duration::from_millis(10)))
+//!     .layer(Memo::new(Duration::from_secs(60)))
+//!     .layer(Measure::new(registry.clone()))
+//!     .layer(Trace)
+//!     .layer(Audit::default())
+//!     .build();
+//! ```
+//!
+//! Layer được thêm sau nằm ở ngoài cùng: trong ví dụ trên, `Audit` thấy lời gọi đầu tiên,
+//! còn `Retry` nằm sát `Sled`.
+
+use crate::storage::Storage;
+
+pub mod trace;    // Layer tracing span cho mỗi thao tác
+pub mod measure;  // Layer ghi metric vào Registry
+pub mod retry;    // Layer thử lại với backoff khi gặp Error::Store
+pub mod memo;     // Layer cache đọc cho fetch
+pub mod guard;    // Layer xác thực thực thể trước khi ghi
+pub mod audit;    // Layer ghi nhật ký kiểm toán cho thao tác ghi
+
+pub use trace::{Trace, Traced};
+pub use measure::{Measure, Measured};
+pub use retry::{Retry, Retried};
+pub use memo::{Memo, Memoized};
+pub use guard::{Guard, Guarded};
+pub use audit::{Audit, Audited, Record};
+
+/// Một layer biến đổi store `S` thành store mới bọc quanh nó.
+///
+/// Mục đích: Chuẩn hóa cách gắn thêm hành vi (tracing, metric, retry, ...) cho mọi backend.
+pub trait Layer<S: Storage> {
+    /// Store kết quả sau khi bọc
+    type Output: Storage;
+
+    /// Bọc store bên trong
+    fn wrap(self, inner: S) -> Self::Output;
+}
+
+/// Builder xếp chồng các layer quanh một store gốc.
+///
+/// Mỗi lời gọi `layer` bọc store hiện tại, nên layer thêm sau nằm ở ngoài cùng.
+pub struct Stack<S: Storage> {
+    inner: S,
+}
+
+impl<S: Storage> Stack<S> {
+    /// Bắt đầu từ store gốc (thường là `Sled`)
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// Thêm một layer bọc quanh store hiện tại
+    pub fn layer<L: Layer<S>>(self, layer: L) -> Stack<L::Output> {
+        Stack { inner: layer.wrap(self.inner) }
+    }
+
+    /// Hoàn tất và trả về store đã bọc
+    pub fn build(self) -> S {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Fault;
+    use crate::storage::entity::{Entity, Query};
+    use crate::storage::stats::Stats;
+    use crate::storage::change::Page;
+    use crate::{Error, Id, Registry, Sled};
+    use async_trait::async_trait;
+    use serde::{Deserialize, Serialize};
+    use std::fmt::Debug;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Thing {
+        id: Id,
+        name: String,
+    }
+
+    impl Entity for Thing {
+        const NAME: &'static str = "things";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index { self.name.clone().into_bytes() }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    fn thing(name: &str) -> Thing {
+        Thing { id: Id::new_v4(), name: name.to_string() }
+    }
+
+    /// Store giả lập: lỗi `Error::Store` ở `fail` lần đầu, đếm số lần gọi fetch,
+    /// insert chờ `hold` trước khi ghi và fetch chờ `pause` sau khi đọc để test chen thao tác vào giữa
+    #[derive(Clone)]
+    struct Flaky {
+        inner: Sled,
+        fail: Arc<AtomicUsize>,
+        calls: Arc<AtomicUsize>,
+        hold: Arc<tokio::sync::Mutex<()>>,
+        pause: Arc<tokio::sync::Mutex<()>>,
+    }
+
+    impl Flaky {
+        fn new(fail: usize) -> (Self, tempfile::TempDir) {
+            let dir = tempdir().unwrap();
+            let inner = Sled::new(dir.path().to_str().unwrap()).unwrap();
+            let store = Self {
+                inner,
+                fail: Arc::new(AtomicUsize::new(fail)),
+                calls: Arc::new(AtomicUsize::new(0)),
+                hold: Arc::new(tokio::sync::Mutex::new(())),
+                pause: Arc::new(tokio::sync::Mutex::new(())),
+            };
+            (store, dir)
+        }
+
+        fn trip(&self) -> Result<(), Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) > 0 {
+                self.fail.fetch_sub(1, Ordering::SeqCst);
+                return Err(Error::Store(sled::Error::Unsupported("flaky".to_string())));
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Storage for Flaky {
+        async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
+        where E::Key: Debug + serde::Serialize, E::Index: Debug {
+            self.trip()?;
+            let _hold = self.hold.lock().await;
+            self.inner.insert(entity).await
+        }
+
+        async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
+        where E::Key: Debug + serde::Serialize {
+            self.trip()?;
+            let res = self.inner.fetch(key).await;
+            let _pause = self.pause.lock().await;
+            res
+        }
+
+        async fn update<E: Entity, F>(&self, key: E::Key, transform: F) -> Result<E, Error>
+        where F: FnOnce(E) -> E + Send + 'static, E::Key: Debug + serde::Serialize {
+            self.inner.update(key, transform).await
+        }
+
+        async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
+        where E::Key: Debug + serde::Serialize {
+            self.trip()?;
+            self.inner.delete(key).await
+        }
+
+        async fn query<E: Entity>(&self, query: Query<E::Index>)
+            -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
+        where E::Index: Debug {
+            self.inner.query::<E>(query).await
+        }
+
+        async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+        where E::Key: Debug + serde::Serialize, E::Index: Debug {
+            self.inner.mass(iter).await
+        }
+
+        async fn stats(&self) -> Result<Stats, Error> {
+            self.inner.stats().await
+        }
+
+        async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+        where E::Key: Debug + serde::Serialize {
+            self.inner.records::<E>(after, limit).await
+        }
+
+        async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+            self.inner.select::<E>(prefix, after, limit).await
+        }
+
+        async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+            self.inner.changes::<E>(after, limit).await
+        }
+
+        async fn position(&self) -> Result<Vec<u8>, Error> {
+            self.inner.position().await
+        }
+
+        async fn keys<E: Entity>(&self, query: Query<E::Index>)
+            -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+        where E::Index: Debug {
+            self.inner.keys::<E>(query).await
+        }
+    }
+
+    #[tokio::test]
+    async fn stack() {
+        let dir = tempdir().unwrap();
+        let registry = Registry::new();
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink = records.clone();
+        let store = Stack::new(Sled::new(dir.path().to_str().unwrap()).unwrap())
+            .layer(Retry::new(2, Duration::from_millis(1)))
+            .layer(Memo::new(Duration::from_secs(60)))
+            .layer(Measure::new(registry.clone()))
+            .layer(Trace)
+            .layer(Audit::new(move |record| sink.lock().unwrap().push(record)))
+            .build();
+
+        let item = thing("stack");
+        store.insert(item.clone()).await.unwrap();
+        assert_eq!(store.fetch::<Thing>(item.id).await.unwrap(), Some(item.clone()));
+        let changed = store.update::<Thing, _>(item.id, |mut t| { t.name = "changed".to_string(); t }).await.unwrap();
+        assert_eq!(changed.name, "changed");
+        // Cache phải bị vô hiệu sau update
+        assert_eq!(store.fetch::<Thing>(item.id).await.unwrap().unwrap().name, "changed");
+        store.delete::<Thing>(item.id).await.unwrap();
+        assert!(store.fetch::<Thing>(item.id).await.unwrap().is_none());
+
+        let stats = registry.stats();
+        assert!(stats.contains("things.insert"));
+        assert!(stats.contains("things.fetch"));
+
+        let ops: Vec<_> = records.lock().unwrap().iter().map(|r| r.op).collect();
+        assert_eq!(ops, vec!["insert", "update", "delete"]);
+        assert!(records.lock().unwrap().iter().all(|r| r.ok && r.entity == "things"));
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        let (flaky, _dir) = Flaky::new(2);
+        let calls = flaky.calls.clone();
+        let store = Stack::new(flaky).layer(Retry::new(3, Duration::from_millis(1))).build();
+        let item = thing("retry");
+        store.insert(item.clone()).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3); // 2 lần lỗi + 1 lần thành công
+
+        // Hết lượt thử thì trả lỗi gốc
+        let (flaky, _dir) = Flaky::new(5);
+        let store = Stack::new(flaky).layer(Retry::new(2, Duration::from_millis(1))).build();
+        assert!(matches!(store.fetch::<Thing>(item.id).await, Err(Error::Store(_))));
+    }
+
+    #[tokio::test]
+    async fn memo() {
+        let (flaky, _dir) = Flaky::new(0);
+        let calls = flaky.calls.clone();
+        let store = Stack::new(flaky).layer(Memo::new(Duration::from_secs(60))).build();
+        let item = thing("memo");
+        store.insert(item.clone()).await.unwrap();
+        for _ in 0..3 {
+            assert_eq!(store.fetch::<Thing>(item.id).await.unwrap(), Some(item.clone()));
+        }
+        // 1 insert + 1 fetch thật, 2 fetch còn lại lấy từ cache
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let again = Thing { name: "again".to_string(), ..item.clone() };
+        store.mass(Box::new(vec![again.clone()].into_iter())).await.unwrap();
+        assert_eq!(store.fetch::<Thing>(item.id).await.unwrap(), Some(again));
+    }
+
+    #[tokio::test]
+    async fn stale() {
+        let (flaky, _dir) = Flaky::new(0);
+        let hold = flaky.hold.clone();
+        let store = Stack::new(flaky).layer(Memo::new(Duration::from_secs(60))).build();
+        let item = thing("old");
+        store.insert(item.clone()).await.unwrap();
+
+        // Fetch chen vào lúc insert đang chờ ghi vẫn thấy giá trị cũ, nhưng không giữ nó lại sau khi ghi xong
+        let guard = hold.lock().await;
+        let fresh = Thing { name: "new".to_string(), ..item.clone() };
+        let writer = tokio::spawn({
+            let store = store.clone();
+            let fresh = fresh.clone();
+            async move { store.insert(fresh).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(store.fetch::<Thing>(item.id).await.unwrap(), Some(item.clone()));
+        drop(guard);
+        writer.await.unwrap().unwrap();
+        assert_eq!(store.fetch::<Thing>(item.id).await.unwrap(), Some(fresh));
+    }
+
+    #[tokio::test]
+    async fn epoch() {
+        let (flaky, _dir) = Flaky::new(0);
+        let pause = flaky.pause.clone();
+        let store = Stack::new(flaky).layer(Memo::new(Duration::from_secs(60))).build();
+        let item = thing("old");
+        store.insert(item.clone()).await.unwrap();
+
+        // Fetch đọc giá trị cũ, insert ghi xong và xóa slot, rồi fetch mới tới lúc đưa vào cache
+        let guard = pause.lock().await;
+        let reader = tokio::spawn({
+            let store = store.clone();
+            async move { store.fetch::<Thing>(item.id).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let fresh = Thing { name: "new".to_string(), ..item.clone() };
+        store.insert(fresh.clone()).await.unwrap();
+        drop(guard);
+        assert_eq!(reader.await.unwrap().unwrap(), Some(item.clone()));
+        assert_eq!(store.fetch::<Thing>(item.id).await.unwrap(), Some(fresh));
+    }
+
+    #[tokio::test]
+    async fn guard() {
+        let dir = tempdir().unwrap();
+        let guard = Guard::new().rule::<Thing, _>(|t| {
+            if t.name.is_empty() {
+                Err(vec![Fault { field: "name".to_string(), message: "không được để trống".to_string() }])
+            } else {
+                Ok(())
+            }
+        });
+        let store = Stack::new(Sled::new(dir.path().to_str().unwrap()).unwrap()).layer(guard).build();
+
+        let good = thing("good");
+        store.insert(good.clone()).await.unwrap();
+        assert!(matches!(store.insert(thing("")).await, Err(Error::Validation(_))));
+
+        // Update không hợp lệ bị chặn, dữ liệu cũ giữ nguyên
+        let res = store.update::<Thing, _>(good.id, |mut t| { t.name.clear(); t }).await;
+        assert!(matches!(res, Err(Error::Validation(_))));
+        assert_eq!(store.fetch::<Thing>(good.id).await.unwrap(), Some(good.clone()));
+
+        // Mass bị từ chối toàn bộ nếu có một thực thể lỗi
+        let batch = vec![thing("a"), thing("")];
+        let first = batch[0].id;
+        assert!(matches!(store.mass(Box::new(batch.into_iter())).await, Err(Error::Validation(_))));
+        assert!(store.fetch::<Thing>(first).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,156 @@
+//! Layer kiểm toán: ghi lại mọi thao tác ghi (insert, update, delete, mass).
+
+use async_trait::async_trait;
+use std::fmt::Debug;
+use std::sync::Arc;
+use serde::Serialize;
+use crate::storage::entity::{Entity, Query};
+use crate::storage::layer::Layer;
+use crate::storage::Storage;
+use crate::storage::stats::Stats;
+use crate::storage::change::Page;
+use crate::Error;
+
+/// Một bản ghi kiểm toán
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    /// Thao tác (insert, update, delete, mass)
+    pub op: &'static str,
+    /// Tên thực thể (`Entity::NAME`)
+    pub entity: &'static str,
+    /// Khóa chính dạng Debug (rỗng với mass)
+    pub key: String,
+    /// Thao tác có thành công không
+    pub ok: bool,
+    /// Thời điểm hoàn tất (nano giây Unix)
+    pub time: u128,
+}
+
+/// Nơi nhận bản ghi kiểm toán
+type Sink = Arc<dyn Fn(Record) + Send + Sync>;
+
+/// Layer kiểm toán, mặc định ghi ra tracing với target `audit`
+#[derive(Clone)]
+pub struct Audit {
+    sink: Sink,
+}
+
+impl Audit {
+    /// Tạo layer với nơi nhận tùy chọn (file, channel, bộ nhớ, ...)
+    pub fn new(sink: impl Fn(Record) + Send + Sync + 'static) -> Self {
+        Self { sink: Arc::new(sink) }
+    }
+}
+
+impl Default for Audit {
+    fn default() -> Self {
+        Self::new(|record: Record| {
+            tracing::info!(target: "audit", op = record.op, entity = record.entity, key = %record.key, ok = record.ok, time = %record.time);
+        })
+    }
+}
+
+/// Store đã được bọc bởi `Audit`
+#[derive(Clone)]
+pub struct Audited<S> {
+    inner: S,
+    sink: Sink,
+}
+
+impl<S: Storage> Audited<S> {
+    /// Gửi bản ghi cho một thao tác đã hoàn tất
+    fn emit<T>(&self, op: &'static str, entity: &'static str, key: String, res: &Result<T, Error>) {
+        (self.sink)(Record { op, entity, key, ok: res.is_ok(), time: self.inner.now() });
+    }
+}
+
+impl<S: Storage> Layer<S> for Audit {
+    type Output = Audited<S>;
+
+    fn wrap(self, inner: S) -> Self::Output {
+        Audited { inner, sink: self.sink }
+    }
+}
+
+#[async_trait]
+impl<S: Storage> Storage for Audited<S> {
+    async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        let key = format!("{:?}", entity.key());
+        let res = self.inner.insert(entity).await;
+        self.emit("insert", E::NAME, key, &res);
+        res
+    }
+
+    async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.inner.fetch(key).await
+    }
+
+    async fn update<E: Entity, F>(&self, key: E::Key, transform: F) -> Result<E, Error>
+    where
+        F: FnOnce(E) -> E + Send + 'static,
+        E::Key: Debug + serde::Serialize {
+        let name = format!("{:?}", key);
+        let res = self.inner.update(key, transform).await;
+        self.emit("update", E::NAME, name, &res);
+        res
+    }
+
+    async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
+    where E::Key: Debug + serde::Serialize {
+        let name = format!("{:?}", key);
+        let res = self.inner.delete(key).await;
+        self.emit("delete", E::NAME, name, &res);
+        res
+    }
+
+    async fn query<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.query::<E>(query).await
+    }
+
+    async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        let res = self.inner.mass(iter).await;
+        self.emit("mass", E::NAME, String::new(), &res);
+        res
+    }
+
+    async fn stats(&self) -> Result<Stats, Error> {
+        self.inner.stats().await
+    }
+
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.inner.records::<E>(after, limit).await
+    }
+
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+        self.inner.select::<E>(prefix, after, limit).await
+    }
+
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+        self.inner.changes::<E>(after, limit).await
+    }
+
+    async fn position(&self) -> Result<Vec<u8>, Error> {
+        self.inner.position().await
+    }
+
+    fn now(&self) -> u128 {
+        self.inner.now()
+    }
+
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        self.inner.generate::<E>(time)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    async fn keys<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.keys::<E>(query).await
+    }
+}
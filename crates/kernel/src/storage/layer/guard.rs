@@ -0,0 +1,166 @@
+//! Layer xác thực: chạy luật kiểm tra của từng loại thực thể trước khi ghi.
+//!
+//! Luật được đăng ký theo `Entity::NAME`; thực thể không có luật được ghi bình thường.
+//! `mass` kiểm tra toàn bộ lô trước khi ghi để không để lại dữ liệu ghi dở.
+
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use crate::error::Fault;
+use crate::storage::entity::{Entity, Query};
+use crate::storage::layer::Layer;
+use crate::storage::Storage;
+use crate::storage::stats::Stats;
+use crate::storage::change::Page;
+use crate::Error;
+
+/// Luật đã xóa kiểu, nhận thực thể dưới dạng `Any`
+type Rule = Arc<dyn Fn(&dyn Any) -> Result<(), Vec<Fault>> + Send + Sync>;
+
+/// Layer xác thực với các luật theo loại thực thể
+#[derive(Clone, Default)]
+pub struct Guard {
+    rules: HashMap<&'static str, Rule>,
+}
+
+impl Guard {
+    /// Tạo layer chưa có luật nào
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Đăng ký luật cho thực thể `E` (ghi đè luật cũ nếu có)
+    pub fn rule<E, F>(mut self, check: F) -> Self
+    where
+        E: Entity,
+        F: Fn(&E) -> Result<(), Vec<Fault>> + Send + Sync + 'static,
+    {
+        let rule: Rule = Arc::new(move |any: &dyn Any| match any.downcast_ref::<E>() {
+            Some(entity) => check(entity),
+            None => Ok(()),
+        });
+        self.rules.insert(E::NAME, rule);
+        self
+    }
+}
+
+/// Store đã được bọc bởi `Guard`
+#[derive(Clone)]
+pub struct Guarded<S> {
+    inner: S,
+    rules: Arc<HashMap<&'static str, Rule>>,
+}
+
+impl<S> Guarded<S> {
+    /// Kiểm tra một thực thể theo luật của loại đó
+    fn check<E: Entity>(&self, entity: &E) -> Result<(), Error> {
+        match self.rules.get(E::NAME) {
+            Some(rule) => rule(entity).map_err(Error::Validation),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S: Storage> Layer<S> for Guard {
+    type Output = Guarded<S>;
+
+    fn wrap(self, inner: S) -> Self::Output {
+        Guarded { inner, rules: Arc::new(self.rules) }
+    }
+}
+
+#[async_trait]
+impl<S: Storage> Storage for Guarded<S> {
+    async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        self.check(&entity)?;
+        self.inner.insert(entity).await
+    }
+
+    async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.inner.fetch(key).await
+    }
+
+    async fn update<E: Entity, F>(&self, key: E::Key, transform: F) -> Result<E, Error>
+    where
+        F: FnOnce(E) -> E + Send + 'static,
+        E::Key: Debug + serde::Serialize {
+        if !self.rules.contains_key(E::NAME) {
+            return self.inner.update(key, transform).await;
+        }
+        // Áp dụng biến đổi trước để kiểm tra kết quả, rồi ghi giá trị đã kiểm tra
+        let old = self.inner.fetch::<E>(key.clone()).await?.ok_or(Error::Missing)?;
+        let new = transform(old);
+        self.check(&new)?;
+        self.inner.update(key, move |_| new).await
+    }
+
+    async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.inner.delete(key).await
+    }
+
+    async fn query<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.query::<E>(query).await
+    }
+
+    async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        if !self.rules.contains_key(E::NAME) {
+            return self.inner.mass(iter).await;
+        }
+        let items: Vec<E> = iter.collect();
+        let faults: Vec<Fault> = items.iter()
+            .filter_map(|e| self.check(e).err())
+            .flat_map(|e| match e {
+                Error::Validation(faults) => faults,
+                _ => Vec::new(),
+            })
+            .collect();
+        if !faults.is_empty() {
+            return Err(Error::Validation(faults));
+        }
+        self.inner.mass(Box::new(items.into_iter())).await
+    }
+
+    async fn stats(&self) -> Result<Stats, Error> {
+        self.inner.stats().await
+    }
+
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.inner.records::<E>(after, limit).await
+    }
+
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+        self.inner.select::<E>(prefix, after, limit).await
+    }
+
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+        self.inner.changes::<E>(after, limit).await
+    }
+
+    async fn position(&self) -> Result<Vec<u8>, Error> {
+        self.inner.position().await
+    }
+
+    fn now(&self) -> u128 {
+        self.inner.now()
+    }
+
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        self.inner.generate::<E>(time)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    async fn keys<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.keys::<E>(query).await
+    }
+}
@@ -0,0 +1,156 @@
+//! Layer metric: đo thời gian và kết quả mỗi thao tác, ghi vào `Registry`.
+
+use async_trait::async_trait;
+use std::fmt::Debug;
+use std::time::Instant;
+use crate::storage::entity::{Entity, Query};
+use crate::storage::layer::Layer;
+use crate::storage::Storage;
+use crate::storage::stats::Stats;
+use crate::storage::change::Page;
+use crate::{Error, Registry};
+
+/// Layer ghi metric theo tên `<thực thể>.<thao tác>`, ví dụ `tasks.insert`
+#[derive(Clone)]
+pub struct Measure {
+    registry: Registry,
+}
+
+impl Measure {
+    /// Tạo layer ghi vào registry cho trước (chia sẻ được với router, CLI, ...)
+    pub fn new(registry: Registry) -> Self {
+        Self { registry }
+    }
+}
+
+/// Store đã được bọc bởi `Measure`
+#[derive(Clone)]
+pub struct Measured<S> {
+    inner: S,
+    registry: Registry,
+}
+
+impl<S> Measured<S> {
+    /// Registry đang nhận metric
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Ghi kết quả một thao tác bắt đầu tại `start`
+    fn record<T>(&self, entity: &str, op: &str, start: Instant, res: &Result<T, Error>) {
+        self.registry.record(&format!("{}.{}", entity, op), start, res);
+    }
+}
+
+impl<S: Storage> Layer<S> for Measure {
+    type Output = Measured<S>;
+
+    fn wrap(self, inner: S) -> Self::Output {
+        Measured { inner, registry: self.registry }
+    }
+}
+
+#[async_trait]
+impl<S: Storage> Storage for Measured<S> {
+    async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        let start = Instant::now();
+        let res = self.inner.insert(entity).await;
+        self.record(E::NAME, "insert", start, &res);
+        res
+    }
+
+    async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        let start = Instant::now();
+        let res = self.inner.fetch(key).await;
+        self.record(E::NAME, "fetch", start, &res);
+        res
+    }
+
+    async fn update<E: Entity, F>(&self, key: E::Key, transform: F) -> Result<E, Error>
+    where
+        F: FnOnce(E) -> E + Send + 'static,
+        E::Key: Debug + serde::Serialize {
+        let start = Instant::now();
+        let res = self.inner.update(key, transform).await;
+        self.record(E::NAME, "update", start, &res);
+        res
+    }
+
+    async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
+    where E::Key: Debug + serde::Serialize {
+        let start = Instant::now();
+        let res = self.inner.delete(key).await;
+        self.record(E::NAME, "delete", start, &res);
+        res
+    }
+
+    async fn query<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
+    where E::Index: Debug {
+        let start = Instant::now();
+        let res = self.inner.query::<E>(query).await;
+        self.record(E::NAME, "query", start, &res);
+        res
+    }
+
+    async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        let start = Instant::now();
+        let res = self.inner.mass(iter).await;
+        self.record(E::NAME, "mass", start, &res);
+        res
+    }
+
+    async fn stats(&self) -> Result<Stats, Error> {
+        let start = Instant::now();
+        let res = self.inner.stats().await;
+        self.record("store", "stats", start, &res);
+        res
+    }
+
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        let start = Instant::now();
+        let res = self.inner.records::<E>(after, limit).await;
+        self.record(E::NAME, "records", start, &res);
+        res
+    }
+
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+        let start = Instant::now();
+        let res = self.inner.select::<E>(prefix, after, limit).await;
+        self.record(E::NAME, "select", start, &res);
+        res
+    }
+
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+        let start = Instant::now();
+        let res = self.inner.changes::<E>(after, limit).await;
+        self.record(E::NAME, "changes", start, &res);
+        res
+    }
+
+    async fn position(&self) -> Result<Vec<u8>, Error> {
+        let start = Instant::now();
+        let res = self.inner.position().await;
+        self.record("store", "position", start, &res);
+        res
+    }
+
+    fn now(&self) -> u128 {
+        self.inner.now()
+    }
+
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        self.inner.generate::<E>(time)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    async fn keys<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.keys::<E>(query).await
+    }
+}
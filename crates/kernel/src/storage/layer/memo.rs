@@ -0,0 +1,189 @@
+//! Layer cache đọc: giữ kết quả `fetch` trong `Cache` có TTL, vô hiệu khi ghi.
+//!
+//! Mỗi slot có một thế hệ (epoch) được tăng khi ghi, trước lúc xóa slot khỏi cache. `fetch` đọc thế hệ
+//! trước khi hỏi store bên trong và chỉ đưa kết quả vào cache nếu thế hệ chưa đổi, nên một fetch đọc
+//! giá trị cũ rồi bị một lần ghi vượt qua không thể đặt lại giá trị cũ vào cache.
+
+use async_trait::async_trait;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::storage::cache::Cache;
+use crate::storage::entity::{Entity, Query};
+use crate::storage::layer::Layer;
+use crate::storage::Storage;
+use crate::storage::stats::Stats;
+use crate::storage::change::Page;
+use crate::Error;
+
+/// Số bộ đếm thế hệ; các slot băm trùng nhau dùng chung một bộ đếm
+const EPOCHS: usize = 1024;
+
+/// Layer cache đọc với thời gian sống cho trước
+#[derive(Clone)]
+pub struct Memo {
+    cache: Cache<Vec<u8>, Vec<u8>>,
+    epochs: Arc<Vec<AtomicU64>>,
+}
+
+impl Memo {
+    /// Tạo cache mới với TTL
+    pub fn new(ttl: Duration) -> Self {
+        let epochs = (0..EPOCHS).map(|_| AtomicU64::new(0)).collect();
+        Self { cache: Cache::new(ttl), epochs: Arc::new(epochs) }
+    }
+}
+
+/// Store đã được bọc bởi `Memo`
+#[derive(Clone)]
+pub struct Memoized<S> {
+    inner: S,
+    cache: Cache<Vec<u8>, Vec<u8>>,
+    epochs: Arc<Vec<AtomicU64>>,
+}
+
+impl<S> Memoized<S> {
+    /// Bộ đếm thế hệ của slot. Hai slot dùng chung bộ đếm chỉ làm một fetch bỏ qua cache, không sai dữ liệu.
+    fn epoch(&self, slot: &[u8]) -> &AtomicU64 {
+        let mut hasher = DefaultHasher::new();
+        slot.hash(&mut hasher);
+        &self.epochs[hasher.finish() as usize % EPOCHS]
+    }
+
+    /// Vô hiệu slot sau khi ghi: tăng thế hệ trước rồi mới xóa khỏi cache
+    async fn invalidate(&self, slot: &Vec<u8>) {
+        self.epoch(slot).fetch_add(1, Ordering::SeqCst);
+        self.cache.del(slot).await;
+    }
+}
+
+/// Khóa cache: tên thực thể + khóa chính đã mã hóa, tránh đụng độ giữa các loại thực thể
+fn slot<E: Entity>(key: &E::Key) -> Result<Vec<u8>, Error>
+where E::Key: serde::Serialize {
+    let mut slot = E::NAME.as_bytes().to_vec();
+    slot.push(0);
+    slot.extend(bincode::serialize(key)?);
+    Ok(slot)
+}
+
+impl<S: Storage> Layer<S> for Memo {
+    type Output = Memoized<S>;
+
+    fn wrap(self, inner: S) -> Self::Output {
+        Memoized { inner, cache: self.cache, epochs: self.epochs }
+    }
+}
+
+#[async_trait]
+impl<S: Storage> Storage for Memoized<S> {
+    async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        // Vô hiệu sau khi ghi xong; fetch đang dở thấy thế hệ đã đổi nên không giữ lại giá trị cũ
+        let slot = slot::<E>(&entity.key())?;
+        let res = self.inner.insert(entity).await;
+        self.invalidate(&slot).await;
+        res
+    }
+
+    async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        let slot = slot::<E>(&key)?;
+        if let Some(bytes) = self.cache.get(&slot).await {
+            return Ok(Some(bincode::deserialize(&bytes)?));
+        }
+        let epoch = self.epoch(&slot).load(Ordering::SeqCst);
+        let res = self.inner.fetch::<E>(key).await?;
+        if let Some(ref entity) = res {
+            if self.epoch(&slot).load(Ordering::SeqCst) != epoch {
+                return Ok(res);
+            }
+            self.cache.set(slot.clone(), bincode::serialize(entity)?).await;
+            // Lần ghi xen vào giữa lần kiểm tra và `set` có thể đã xóa slot trước khi `set` chạy
+            if self.epoch(&slot).load(Ordering::SeqCst) != epoch {
+                self.cache.del(&slot).await;
+            }
+        }
+        Ok(res)
+    }
+
+    async fn update<E: Entity, F>(&self, key: E::Key, transform: F) -> Result<E, Error>
+    where
+        F: FnOnce(E) -> E + Send + 'static,
+        E::Key: Debug + serde::Serialize {
+        let slot = slot::<E>(&key)?;
+        let res = self.inner.update(key, transform).await;
+        self.invalidate(&slot).await;
+        res
+    }
+
+    async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
+    where E::Key: Debug + serde::Serialize {
+        let slot = slot::<E>(&key)?;
+        let res = self.inner.delete(key).await;
+        self.invalidate(&slot).await;
+        res
+    }
+
+    async fn query<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.query::<E>(query).await
+    }
+
+    async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        // Ghi nhận khóa khi iterator chạy qua, không cần giữ toàn bộ thực thể trong bộ nhớ
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let iter = iter.inspect(move |e| {
+            if let Ok(mut keys) = sink.lock() {
+                keys.push(e.key());
+            }
+        });
+        let res = self.inner.mass(Box::new(iter)).await;
+        let keys = std::mem::take(&mut *seen.lock().map_err(|_| Error::Cache)?);
+        for key in keys {
+            self.invalidate(&slot::<E>(&key)?).await;
+        }
+        res
+    }
+
+    async fn stats(&self) -> Result<Stats, Error> {
+        self.inner.stats().await
+    }
+
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.inner.records::<E>(after, limit).await
+    }
+
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+        self.inner.select::<E>(prefix, after, limit).await
+    }
+
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+        self.inner.changes::<E>(after, limit).await
+    }
+
+    async fn position(&self) -> Result<Vec<u8>, Error> {
+        self.inner.position().await
+    }
+
+    fn now(&self) -> u128 {
+        self.inner.now()
+    }
+
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        self.inner.generate::<E>(time)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    async fn keys<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.keys::<E>(query).await
+    }
+}
@@ -0,0 +1,144 @@
+//! Layer retry: thử lại với backoff lũy thừa khi backend trả `Error::Store`.
+//!
+//! Chỉ các thao tác có đầu vào phát lại được (insert, fetch, delete, query) mới được thử lại.
+//! `update` (hàm biến đổi `FnOnce`) và `mass` (iterator dùng một lần) được chuyển thẳng xuống dưới.
+
+use async_trait::async_trait;
+use std::fmt::Debug;
+use std::future::Future;
+use std::time::Duration;
+use crate::storage::entity::{Entity, Query};
+use crate::storage::layer::Layer;
+use crate::storage::Storage;
+use crate::storage::stats::Stats;
+use crate::storage::change::Page;
+use crate::Error;
+
+/// Layer thử lại tối đa `attempts` lần, chờ `backoff * 2^n` giữa các lần
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    attempts: usize,
+    backoff: Duration,
+}
+
+impl Retry {
+    /// Tạo layer với số lần thử (tính cả lần đầu) và thời gian chờ ban đầu
+    pub fn new(attempts: usize, backoff: Duration) -> Self {
+        Self { attempts: attempts.max(1), backoff }
+    }
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(20))
+    }
+}
+
+/// Store đã được bọc bởi `Retry`
+#[derive(Clone)]
+pub struct Retried<S> {
+    inner: S,
+    policy: Retry,
+}
+
+impl<S> Retried<S> {
+    /// Chạy `call` cho đến khi thành công, gặp lỗi không phải `Store`, hoặc hết lượt
+    async fn run<T, F, Fut>(&self, op: &str, mut call: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut + Send,
+        Fut: Future<Output = Result<T, Error>> + Send,
+    {
+        let mut attempt = 0;
+        loop {
+            match call().await {
+                Err(Error::Store(e)) if attempt + 1 < self.policy.attempts => {
+                    let wait = self.policy.backoff * 2u32.saturating_pow(attempt as u32);
+                    tracing::warn!(op, attempt, ?e, ?wait, "Lỗi lưu trữ, thử lại sau backoff");
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+impl<S: Storage> Layer<S> for Retry {
+    type Output = Retried<S>;
+
+    fn wrap(self, inner: S) -> Self::Output {
+        Retried { inner, policy: self }
+    }
+}
+
+#[async_trait]
+impl<S: Storage> Storage for Retried<S> {
+    async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        self.run("insert", move || self.inner.insert(entity.clone())).await
+    }
+
+    async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.run("fetch", move || self.inner.fetch(key.clone())).await
+    }
+
+    async fn update<E: Entity, F>(&self, key: E::Key, transform: F) -> Result<E, Error>
+    where
+        F: FnOnce(E) -> E + Send + 'static,
+        E::Key: Debug + serde::Serialize {
+        self.inner.update(key, transform).await
+    }
+
+    async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.run("delete", move || self.inner.delete(key.clone())).await
+    }
+
+    async fn query<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.run("query", move || self.inner.query::<E>(query.clone())).await
+    }
+
+    async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        self.inner.mass(iter).await
+    }
+
+    async fn stats(&self) -> Result<Stats, Error> {
+        self.inner.stats().await
+    }
+
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        self.run("records", move || self.inner.records::<E>(after.clone(), limit)).await
+    }
+
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+        self.run("select", move || self.inner.select::<E>(prefix.clone(), after.clone(), limit)).await
+    }
+
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+        self.run("changes", move || self.inner.changes::<E>(after.clone(), limit)).await
+    }
+
+    async fn position(&self) -> Result<Vec<u8>, Error> {
+        self.run("position", || self.inner.position()).await
+    }
+
+    fn now(&self) -> u128 {
+        self.inner.now()
+    }
+
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        self.inner.generate::<E>(time)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    async fn keys<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+    where E::Index: Debug {
+        self.inner.keys::<E>(query).await
+    }
+}
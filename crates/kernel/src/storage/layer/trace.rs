@@ -0,0 +1,113 @@
+//! Layer tracing: mở một span cho mỗi thao tác storage.
+
+use async_trait::async_trait;
+use std::fmt::Debug;
+use tracing::Instrument;
+use crate::storage::entity::{Entity, Query};
+use crate::storage::layer::Layer;
+use crate::storage::Storage;
+use crate::storage::stats::Stats;
+use crate::storage::change::Page;
+use crate::Error;
+
+/// Layer gắn span `storage` (thao tác + tên thực thể) quanh mỗi lời gọi
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Trace;
+
+/// Store đã được bọc bởi `Trace`
+#[derive(Clone)]
+pub struct Traced<S> {
+    inner: S,
+}
+
+impl<S: Storage> Layer<S> for Trace {
+    type Output = Traced<S>;
+
+    fn wrap(self, inner: S) -> Self::Output {
+        Traced { inner }
+    }
+}
+
+#[async_trait]
+impl<S: Storage> Storage for Traced<S> {
+    async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        let span = tracing::info_span!("storage", op = "insert", entity = E::NAME, key = ?entity.key());
+        self.inner.insert(entity).instrument(span).await
+    }
+
+    async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        let span = tracing::info_span!("storage", op = "fetch", entity = E::NAME, key = ?key);
+        self.inner.fetch(key).instrument(span).await
+    }
+
+    async fn update<E: Entity, F>(&self, key: E::Key, transform: F) -> Result<E, Error>
+    where
+        F: FnOnce(E) -> E + Send + 'static,
+        E::Key: Debug + serde::Serialize {
+        let span = tracing::info_span!("storage", op = "update", entity = E::NAME, key = ?key);
+        self.inner.update(key, transform).instrument(span).await
+    }
+
+    async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
+    where E::Key: Debug + serde::Serialize {
+        let span = tracing::info_span!("storage", op = "delete", entity = E::NAME, key = ?key);
+        self.inner.delete(key).instrument(span).await
+    }
+
+    async fn query<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
+    where E::Index: Debug {
+        let span = tracing::info_span!("storage", op = "query", entity = E::NAME, limit = query.limit);
+        self.inner.query::<E>(query).instrument(span).await
+    }
+
+    async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+    where E::Key: Debug + serde::Serialize, E::Index: Debug {
+        let span = tracing::info_span!("storage", op = "mass", entity = E::NAME);
+        self.inner.mass(iter).instrument(span).await
+    }
+
+    async fn stats(&self) -> Result<Stats, Error> {
+        let span = tracing::info_span!("storage", op = "stats");
+        self.inner.stats().instrument(span).await
+    }
+
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: Debug + serde::Serialize {
+        let span = tracing::info_span!("storage", op = "records", entity = E::NAME, limit);
+        self.inner.records::<E>(after, limit).instrument(span).await
+    }
+
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+        let span = tracing::info_span!("storage", op = "select", entity = E::NAME, limit);
+        self.inner.select::<E>(prefix, after, limit).instrument(span).await
+    }
+
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+        let span = tracing::info_span!("storage", op = "changes", entity = E::NAME, limit);
+        self.inner.changes::<E>(after, limit).instrument(span).await
+    }
+
+    async fn position(&self) -> Result<Vec<u8>, Error> {
+        let span = tracing::info_span!("storage", op = "position");
+        self.inner.position().instrument(span).await
+    }
+
+    fn now(&self) -> u128 {
+        self.inner.now()
+    }
+
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        self.inner.generate::<E>(time)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    async fn keys<E: Entity>(&self, query: Query<E::Index>)
+        -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+    where E::Index: Debug {
+        let span = tracing::info_span!("storage", op = "keys", entity = E::NAME);
+        self.inner.keys::<E>(query).instrument(span).await
+    }
+}
@@ -10,6 +10,8 @@
 use async_trait::async_trait; // Cho phép định nghĩa trait với hàm async
 use std::fmt::Debug; // Đảm bảo các khóa/chỉ mục có thể debug dễ dàng
 use crate::{Error, storage::entity::{Entity, Query}}; // Import các định nghĩa lỗi, trait Entity và struct Query
+use crate::storage::stats::Stats; // Thống kê dung lượng của store
+use crate::storage::change::Page; // Trang nhật ký thay đổi
 use serde; // Import serde module
 
 /// Hợp đồng cho bất kỳ cơ chế lưu trữ nào muốn làm việc với framework.
@@ -64,6 +66,53 @@ pub trait Storage: Send + Sync { // Trait phải thread-safe để dùng trong m
     async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
     where E::Key: Debug + serde::Serialize, E::Index: Debug;
     
+    /// Thống kê dung lượng theo từng thực thể và dung lượng trên đĩa.
+    /// Mục đích: Quan sát kích thước dữ liệu và chỉ mục mà không cần biết trước các loại thực thể.
+    /// Thuật toán: Duyệt mọi tree của backend, ghép tree dữ liệu với tree chỉ mục tương ứng.
+    /// Thành tựu: Cung cấp số liệu cho `knowledge stats` và giám sát vận hành.
+    async fn stats(&self) -> Result<Stats, Error>;
+
+    /// Đọc một trang thực thể đầy đủ theo thứ tự khóa chính, bắt đầu sau khóa `after`.
+    /// Mục đích: Cho phép duyệt toàn bộ dữ liệu (export, sao lưu) mà không cần giữ hết trong bộ nhớ.
+    /// Thuật toán: Quét tree dữ liệu theo khóa đã tuần tự hóa; trang ngắn hơn `limit` là trang cuối.
+    /// Thành tựu: Nguồn dữ liệu phân trang chung cho mọi định dạng xuất.
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: Debug + serde::Serialize;
+
+    /// Đọc một trang mục chỉ mục `(khóa chỉ mục, tóm tắt)` có tiền tố `prefix`, bắt đầu sau khóa chỉ mục `after`.
+    /// Mục đích: Cho phép xuất theo tiền tố chỉ chạm tới những mục khớp thay vì duyệt toàn bộ dữ liệu.
+    /// Thuật toán: Quét tree chỉ mục bao phủ theo tiền tố; trang ngắn hơn `limit` là trang cuối.
+    /// Thành tựu: Chi phí xuất theo tiền tố tỉ lệ với số mục khớp.
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error>;
+
+    /// Đọc một trang nhật ký thay đổi của `E` sau vị trí `after` (xem `change::position`).
+    /// Mục đích: Cho phép xuất tăng dần (delta) chỉ những gì đã đổi hoặc bị xóa kể từ lần trước.
+    /// Thuật toán: Quét tree nhật ký theo thời điểm, bỏ mục đã bị thay thế, đọc kèm giá trị hiện hành.
+    /// Thành tựu: Đồng bộ định kỳ không phải đọc lại toàn bộ dữ liệu.
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error>;
+
+    /// Vị trí hiện tại của nhật ký thay đổi: mọi thay đổi commit sau lời gọi nằm sau vị trí này.
+    /// Mục đích: Mốc cho lần xuất tăng dần kế tiếp, lấy trước khi bắt đầu đọc dữ liệu.
+    /// Thuật toán: Đọc vị trí đã cấp gần nhất; vị trí mới được cấp trong giao dịch commit nên luôn lớn hơn.
+    /// Thành tựu: Lần ghi đang dở trong lúc xuất không bao giờ bị bỏ sót ở lần xuất tăng dần sau.
+    async fn position(&self) -> Result<Vec<u8>, Error>;
+
+    /// Thời điểm hiện tại (nano giây Unix) theo clock của backend.
+    /// Mục đích: Mọi timestamp nghiệp vụ (ví dụ `created`) lấy từ một nguồn tiêm được.
+    /// Thuật toán: Mặc định đọc đồng hồ hệ thống; `Sled` dùng clock đã cấu hình, layer chuyển tiếp.
+    /// Thành tựu: Test và mô phỏng điều khiển thời gian một cách tất định.
+    fn now(&self) -> u128 {
+        crate::storage::time::now()
+    }
+
+    /// Sinh khóa cho một thực thể `E` mới tạo lúc `time` (nano giây Unix) theo `E::SCHEME`.
+    /// Mục đích: Khóa UUIDv7 mang đúng thời điểm `created` và tăng dần trong cùng một store.
+    /// Thuật toán: Mặc định dùng `Entity::generate`; `Sled` dùng bộ đếm riêng của nó, layer chuyển tiếp.
+    /// Thành tựu: Store và clock khác nhau không làm lệch thời điểm trong id của nhau.
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        E::generate(time)
+    }
+
     /// Hàm trợ giúp cho benchmark - lấy các khóa chỉ mục (chỉ bật khi test/benchmark).
     /// Mục đích: Hỗ trợ kiểm thử hiệu năng và xác minh hoạt động index.
     /// Thuật toán: Truy vấn index, trả về iterator các khoá.
@@ -81,7 +130,17 @@ pub mod pool;    // Module quản lý pool kết nối
 pub mod cache;   // Module cache
 pub mod entity;  // Module định nghĩa trait Entity
 pub mod time;    // Module tiện ích thời gian
+pub mod id;      // Module sinh định danh (UUIDv7 tăng dần theo thời gian)
 pub mod export;  // Module export dữ liệu
+pub mod layer;   // Module layer (middleware) bọc quanh Storage
+pub mod check;   // Module kiểm tra và sửa chỉ mục (fsck)
+pub mod reindex; // Module dựng lại chỉ mục trực tuyến
+pub mod upgrade; // Module chuyển dữ liệu từ bố cục cũ
+pub mod stats;   // Module thống kê dung lượng store
+pub mod predicate; // Module biểu thức lọc trên Summary
+pub mod search;  // Module chỉ mục ngược và tìm kiếm toàn văn
+pub mod import;  // Module nhập dữ liệu CSV
+pub mod change;  // Module nhật ký thay đổi cho xuất tăng dần
 
 // --- Re-export các thành phần từ module export ---
 pub use export::{
@@ -92,8 +151,15 @@ pub use export::{
     Config,
     Filter,
     Format,
+    View,
+    Cursor,
+    Mark,
+    Compression,
     Stream,
     Export,
     Builder,
     Ext,
-};
\ No newline at end of file
+};
+
+// --- Re-export hệ thống layer ---
+pub use layer::{Layer, Stack};
\ No newline at end of file
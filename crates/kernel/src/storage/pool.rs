@@ -8,6 +8,7 @@
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 use crate::error::{Error, Fault};
+use crate::metric::{Counter, Registry};
 
 /// Pool quản lý các kết nối database
 /// Mục đích: Tối ưu hóa việc tái sử dụng kết nối, giảm chi phí khởi tạo mới
@@ -17,6 +18,10 @@ pub struct Pool<T> {
     conn: Arc<Vec<T>>,
     /// Semaphore để kiểm soát số lượng kết nối đồng thời
     sem: Arc<Semaphore>,
+    /// Số lần lấy kết nối thành công
+    taken: Counter,
+    /// Số lần không lấy được kết nối
+    fail: Counter,
 }
 
 impl<T: Clone + Send + Sync + 'static> Pool<T> {
@@ -32,17 +37,32 @@ impl<T: Clone + Send + Sync + 'static> Pool<T> {
         Ok(Self {
             conn: Arc::new(conn), // Chia sẻ danh sách kết nối qua Arc
             sem: Arc::new(Semaphore::new(size)), // Semaphore với số lượng permit = size
+            taken: Counter::default(),
+            fail: Counter::default(),
         })
     }
+
+    /// Gắn bộ đếm lấy kết nối và gauge kích thước vào registry với nhãn `pool="<name>"`
+    pub fn metric(mut self, registry: &Registry, name: &str) -> Self {
+        let pairs = [("pool", name)];
+        registry.gauge("kernel_pool_size", &pairs).set(self.conn.len() as u64);
+        self.taken = registry.counter("kernel_pool_acquired_total", &pairs);
+        self.fail = registry.counter("kernel_pool_failures_total", &pairs);
+        self
+    }
     
     /// Lấy một kết nối từ pool (bất đồng bộ)
     /// Mục đích: Đảm bảo không vượt quá số lượng kết nối tối đa
     /// Thuật toán: acquire semaphore, trả về bản sao kết nối đầu tiên (demo, có thể mở rộng round-robin)
     pub async fn get(&self) -> Result<T, Error> {
-        let _permit = self.sem.acquire().await.map_err(|_| Error::Validation(vec![Fault {
-            field: "pool".to_string(),
-            message: "Không thể lấy permit từ semaphore.".to_string()
-        }]))?;
+        let _permit = self.sem.acquire().await.map_err(|_| {
+            self.fail.add(1);
+            Error::Validation(vec![Fault {
+                field: "pool".to_string(),
+                message: "Không thể lấy permit từ semaphore.".to_string()
+            }])
+        })?;
+        self.taken.add(1);
         Ok(self.conn[0].clone())
     }
     
@@ -0,0 +1,222 @@
+//! Biểu thức lọc (predicate) trên các trường của `Entity::Summary`.
+//!
+//! `Query` mang theo một `Predicate`; backend dùng tiền tố chỉ mục khi thực thể biết cách
+//! suy ra (`Entity::prefix`), phần còn lại được áp dụng như bộ lọc dư trong lúc quét,
+//! và `limit` được tính sau khi lọc.
+//!
+//! ```rust,ignore
+//! use kernel::storage::predicate::Predicate;
+//!
+//! let open = Predicate::eq("status", Status::Open)
+//!     .and(Predicate::among("priority", [Priority::High, Priority::Medium]))
+//!     .and(!Predicate::contains("task", "draft"));
+//! let query = Query { limit: 20, ..Default::default() }.filter(open);
+//! ```
+//!
+//! Trường được đánh giá trên dạng JSON của Summary (enum đơn vị là chuỗi tên biến thể),
+//! đường dẫn lồng nhau viết bằng dấu chấm: `"owner.name"`.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::Error;
+
+/// Một biểu thức lọc
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Predicate {
+    /// Trường bằng giá trị
+    Eq(String, Value),
+    /// Trường khác giá trị
+    Ne(String, Value),
+    /// Trường bằng một trong các giá trị
+    In(String, Vec<Value>),
+    /// Trường chuỗi chứa chuỗi con, hoặc trường mảng chứa phần tử
+    Contains(String, Value),
+    /// Trường nằm trong khoảng `[low, high)`; cận bỏ trống là không giới hạn
+    Range(String, Option<Value>, Option<Value>),
+    /// Mọi biểu thức con đều đúng
+    And(Vec<Predicate>),
+    /// Ít nhất một biểu thức con đúng
+    Or(Vec<Predicate>),
+    /// Phủ định
+    Not(Box<Predicate>),
+}
+
+/// Chuyển giá trị bất kỳ sang JSON; giá trị không tuần tự hóa được trở thành `null`
+fn json<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+impl Predicate {
+    /// `field == value`
+    pub fn eq<T: Serialize>(field: &str, value: T) -> Self {
+        Self::Eq(field.to_string(), json(value))
+    }
+
+    /// `field != value`
+    pub fn ne<T: Serialize>(field: &str, value: T) -> Self {
+        Self::Ne(field.to_string(), json(value))
+    }
+
+    /// `field` thuộc tập giá trị
+    pub fn among<T: Serialize>(field: &str, values: impl IntoIterator<Item = T>) -> Self {
+        Self::In(field.to_string(), values.into_iter().map(json).collect())
+    }
+
+    /// `field` chứa `value` (chuỗi con hoặc phần tử mảng)
+    pub fn contains<T: Serialize>(field: &str, value: T) -> Self {
+        Self::Contains(field.to_string(), json(value))
+    }
+
+    /// `low <= field < high`
+    pub fn range<T: Serialize>(field: &str, low: Option<T>, high: Option<T>) -> Self {
+        Self::Range(field.to_string(), low.map(json), high.map(json))
+    }
+
+    /// Kết hợp AND, làm phẳng các AND lồng nhau
+    pub fn and(self, other: Predicate) -> Self {
+        match self {
+            Self::And(mut all) => {
+                all.push(other);
+                Self::And(all)
+            }
+            one => Self::And(vec![one, other]),
+        }
+    }
+
+    /// Kết hợp OR, làm phẳng các OR lồng nhau
+    pub fn or(self, other: Predicate) -> Self {
+        match self {
+            Self::Or(mut any) => {
+                any.push(other);
+                Self::Or(any)
+            }
+            one => Self::Or(vec![one, other]),
+        }
+    }
+
+    /// Giá trị mà biểu thức bắt buộc trường phải bằng (Eq ở gốc hoặc trong AND gốc).
+    /// Dùng trong `Entity::prefix` để đổi điều kiện thành tiền tố chỉ mục.
+    pub fn pin<T: DeserializeOwned>(&self, field: &str) -> Option<T> {
+        match self {
+            Self::Eq(name, value) if name == field => serde_json::from_value(value.clone()).ok(),
+            Self::And(all) => all.iter().find_map(|p| p.pin(field)),
+            _ => None,
+        }
+    }
+
+    /// Đánh giá biểu thức trên một Summary bất kỳ
+    pub fn check<S: Serialize>(&self, summary: &S) -> Result<bool, Error> {
+        Ok(self.matches(&serde_json::to_value(summary)?))
+    }
+
+    /// Đánh giá biểu thức trên một tài liệu JSON
+    pub fn matches(&self, doc: &Value) -> bool {
+        match self {
+            Self::Eq(field, value) => lookup(doc, field) == Some(value),
+            Self::Ne(field, value) => lookup(doc, field) != Some(value),
+            Self::In(field, values) => lookup(doc, field).is_some_and(|v| values.contains(v)),
+            Self::Contains(field, value) => match (lookup(doc, field), value) {
+                (Some(Value::String(text)), Value::String(part)) => text.contains(part.as_str()),
+                (Some(Value::Array(items)), value) => items.contains(value),
+                _ => false,
+            },
+            Self::Range(field, low, high) => match lookup(doc, field) {
+                Some(v) => {
+                    low.as_ref().is_none_or(|l| matches!(compare(v, l), Some(Ordering::Greater | Ordering::Equal)))
+                        && high.as_ref().is_none_or(|h| matches!(compare(v, h), Some(Ordering::Less)))
+                }
+                None => false,
+            },
+            Self::And(all) => all.iter().all(|p| p.matches(doc)),
+            Self::Or(any) => any.iter().any(|p| p.matches(doc)),
+            Self::Not(inner) => !inner.matches(doc),
+        }
+    }
+}
+
+impl std::ops::Not for Predicate {
+    type Output = Predicate;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Self::Not(inner) => *inner,
+            one => Self::Not(Box::new(one)),
+        }
+    }
+}
+
+/// Lấy giá trị theo đường dẫn dấu chấm
+fn lookup<'a>(doc: &'a Value, field: &str) -> Option<&'a Value> {
+    field.split('.').try_fold(doc, |v, part| v.get(part))
+}
+
+/// So sánh hai giá trị cùng loại: số theo giá trị, chuỗi theo thứ tự từ điển
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    enum Kind { Open, Done }
+
+    #[derive(Serialize)]
+    struct Brief {
+        kind: Kind,
+        title: String,
+        size: u32,
+        tags: Vec<String>,
+        owner: Owner,
+    }
+
+    #[derive(Serialize)]
+    struct Owner { name: String }
+
+    fn brief() -> Brief {
+        Brief {
+            kind: Kind::Open,
+            title: "viết bộ lọc".to_string(),
+            size: 42,
+            tags: vec!["kernel".to_string()],
+            owner: Owner { name: "an".to_string() },
+        }
+    }
+
+    #[test]
+    fn check() {
+        let b = brief();
+        assert!(Predicate::eq("kind", Kind::Open).check(&b).unwrap());
+        assert!(Predicate::ne("kind", Kind::Done).check(&b).unwrap());
+        assert!(Predicate::among("size", [1, 42]).check(&b).unwrap());
+        assert!(Predicate::contains("title", "bộ lọc").check(&b).unwrap());
+        assert!(Predicate::contains("tags", "kernel").check(&b).unwrap());
+        assert!(Predicate::range("size", Some(40), Some(43)).check(&b).unwrap());
+        assert!(!Predicate::range("size", None, Some(42)).check(&b).unwrap());
+        assert!(Predicate::eq("owner.name", "an").check(&b).unwrap());
+        assert!(!Predicate::eq("missing", 1).check(&b).unwrap());
+
+        let both = Predicate::eq("kind", Kind::Open).and(Predicate::eq("size", 42));
+        assert!(both.check(&b).unwrap());
+        let either = Predicate::eq("kind", Kind::Done).or(Predicate::eq("size", 42));
+        assert!(either.check(&b).unwrap());
+        assert!(!(!either).check(&b).unwrap());
+    }
+
+    #[test]
+    fn pin() {
+        let p = Predicate::eq("kind", "Open").and(Predicate::range("size", Some(1), None));
+        assert_eq!(p.pin::<String>("kind"), Some("Open".to_string()));
+        assert_eq!(p.pin::<u32>("size"), None);
+        assert_eq!(Predicate::eq("kind", "Open").or(Predicate::eq("size", 1)).pin::<String>("kind"), None);
+    }
+}
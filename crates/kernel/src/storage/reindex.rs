@@ -0,0 +1,194 @@
+//! Dựng lại chỉ mục trực tuyến khi bố cục `Entity::index()` thay đổi.
+//!
+//! Quy trình:
+//! 1. Actor mở một tree vật lý mới cho chỉ mục và bắt đầu ghi kép: mọi insert/update/delete
+//!    từ lúc này ghi vào cả chỉ mục cũ lẫn chỉ mục mới.
+//! 2. Tree dữ liệu được quét theo từng lô; mỗi lô ghi vào chỉ mục mới trong một giao dịch
+//!    kèm điều kiện bản ghi chưa đổi, nên không ghi đè kết quả của thao tác ghi đồng thời.
+//! 3. Khi quét xong, actor chuyển tuyến sang tree mới và xóa tree cũ trong cùng một message,
+//!    nên truy vấn không bao giờ thấy chỉ mục dở dang.
+//! 4. `E::LAYOUT` được ghi vào tree `__layouts` để `upgrade` biết chỉ mục đã theo bố cục mới.
+//!
+//! ```rust,ignore
+//! let count = store.reindex::<task::Entry>().await?;
+//! ```
+
+use std::time::Instant;
+
+use crate::serializer::Codec;
+use crate::storage::actor::message::{Batch, Pair};
+use crate::storage::actor::Actorable;
+use crate::storage::entity::Entity;
+use crate::storage::sled::{index, Sled};
+use crate::storage::time::now;
+use crate::storage::upgrade::LAYOUTS;
+use crate::Error;
+
+/// Số bản ghi mỗi lô
+const BATCH: usize = 512;
+
+/// Số lần quét lại một lô khi bị xung đột trước khi bỏ cuộc
+const ATTEMPTS: usize = 16;
+
+impl<C: Codec> Sled<C> {
+    /// Dựng lại chỉ mục của `E` theo bố cục hiện tại, trả về số mục đã ghi.
+    ///
+    /// Store vẫn dùng được trong suốt quá trình; truy vấn đọc chỉ mục cũ cho tới khi chuyển.
+    /// Tiến độ được ghi qua tracing và metric `"{NAME}.reindex"` cho từng lô.
+    pub async fn reindex<E: Entity>(&self) -> Result<usize, Error> {
+        let tree = index(E::NAME);
+        let target = format!("{}.{}", tree, now());
+        self.handle.begin(&tree, &target).await?;
+        tracing::info!(entity = E::NAME, target, "Bắt đầu reindex");
+
+        match self.backfill::<E>(&target).await {
+            Ok((count, _)) => {
+                let old = self.handle.swap(&tree).await?;
+                let mut batch = Batch::default();
+                batch.put(LAYOUTS, E::NAME.as_bytes().to_vec(), E::LAYOUT.to_be_bytes().to_vec());
+                self.handle.apply(batch).await?;
+                tracing::info!(entity = E::NAME, count, old, "Reindex hoàn tất");
+                Ok(count)
+            }
+            Err(e) => {
+                tracing::error!(?e, entity = E::NAME, "Reindex thất bại, giữ chỉ mục cũ");
+                self.handle.cancel(&tree).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Quét tree dữ liệu và ghi chỉ mục mới vào `target` theo từng lô.
+    /// Trả về số mục đã ghi và khóa các bản ghi không giải mã được (bị bỏ qua).
+    pub(crate) async fn backfill<E: Entity>(&self, target: &str) -> Result<(usize, Vec<Vec<u8>>), Error> {
+        self.fill::<E, _>(target, |entity, _| Ok(vec![(entity.index().as_ref().to_vec(), self.encode::<E, _>(&entity.summary())?)])).await
+    }
+
+    /// Quét tree dữ liệu và ghi các mục `entries(thực thể, khóa bản ghi)` vào `target` theo từng lô,
+    /// mỗi lô kèm `expect` trên các bản ghi nguồn. Trả về số mục đã ghi và khóa các bản ghi hỏng.
+    pub(crate) async fn fill<E, F>(&self, target: &str, entries: F) -> Result<(usize, Vec<Vec<u8>>), Error>
+    where
+        E: Entity,
+        F: Fn(&E, &[u8]) -> Result<Vec<Pair>, Error>,
+    {
+        let metric = self.handle.metrics().get(&format!("{}.reindex", E::NAME));
+        let mut count = 0;
+        let mut broken = Vec::new();
+        let mut after: Option<Vec<u8>> = None;
+        loop {
+            let mut attempt = 0;
+            let (page, last, skipped) = loop {
+                let start = Instant::now();
+                let page = self.handle.scan(E::NAME, Vec::new(), after.clone(), BATCH).await?;
+                let last = page.last().map(|(k, _)| k.clone());
+                let mut batch = Batch::default();
+                let mut skipped = Vec::new();
+                for (key, value) in &page {
+                    match self.decode::<E, E>(value) {
+                        Ok(entity) => {
+                            for (k, v) in entries(&entity, key)? {
+                                batch.put(target, k, v);
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!(?e, entity = E::NAME, "Bỏ qua bản ghi không giải mã được khi reindex");
+                            skipped.push(key.clone());
+                        }
+                    }
+                    batch.expect(E::NAME, key.clone(), Some(value.clone()));
+                }
+                let written = batch.ops.len();
+                let res = self.handle.apply(batch).await;
+                metric.record(start, &res);
+                match res {
+                    Ok(()) => break (written, last, skipped),
+                    Err(Error::Conflict) if attempt < ATTEMPTS => attempt += 1,
+                    Err(e) => return Err(e),
+                }
+            };
+            count += page;
+            broken.extend(skipped);
+            tracing::info!(entity = E::NAME, count, "Tiến độ reindex");
+            match last {
+                Some(key) => after = Some(key),
+                None => return Ok((count, broken)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Entity, Id, Query, Sled, Storage};
+    use serde::{Deserialize, Serialize};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tempfile::tempdir;
+
+    /// Bật để đổi bố cục chỉ mục, giả lập việc sửa `index()` giữa hai phiên bản
+    static LAYOUT: AtomicBool = AtomicBool::new(false);
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Thing {
+        id: Id,
+        group: u8,
+        name: String,
+    }
+
+    impl Entity for Thing {
+        const NAME: &'static str = "things";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index {
+            let mut index = Vec::new();
+            if LAYOUT.load(Ordering::SeqCst) {
+                index.push(self.group);
+            }
+            index.extend_from_slice(self.id.as_bytes());
+            index
+        }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    #[tokio::test]
+    async fn reindex() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let things: Vec<_> = (0..1500).map(|i| Thing { id: Id::new_v4(), group: (i % 3) as u8, name: format!("t{}", i) }).collect();
+        store.mass(Box::new(things.clone().into_iter())).await.unwrap();
+
+        LAYOUT.store(true, Ordering::SeqCst);
+        assert!(!store.check::<Thing>().await.unwrap().clean());
+
+        // Ghi đồng thời trong lúc reindex
+        let writer = {
+            let store = store.clone();
+            let things = things.clone();
+            tokio::spawn(async move {
+                for thing in things.iter().take(100) {
+                    store.update::<Thing, _>(thing.id, |mut t| { t.name.push('!'); t }).await.unwrap();
+                }
+                store.delete::<Thing>(things[0].id).await.unwrap();
+                store.insert(Thing { id: Id::new_v4(), group: 1, name: "new".to_string() }).await.unwrap();
+            })
+        };
+        store.reindex::<Thing>().await.unwrap();
+        writer.await.unwrap();
+
+        let report = store.check::<Thing>().await.unwrap();
+        assert!(report.clean(), "{}", report);
+        assert_eq!(report.records, 1500);
+
+        // Tiền tố theo bố cục mới hoạt động
+        let query = Query { prefix: vec![1], after: None, limit: usize::MAX, filter: None };
+        let count = store.query::<Thing>(query).await.unwrap().count();
+        assert_eq!(count, 501);
+
+        // Reindex lần hai trên tuyến đã chuyển
+        assert_eq!(store.reindex::<Thing>().await.unwrap(), 1500);
+        assert!(store.check::<Thing>().await.unwrap().clean());
+        LAYOUT.store(false, Ordering::SeqCst);
+    }
+}
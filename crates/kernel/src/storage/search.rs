@@ -0,0 +1,309 @@
+//! Chỉ mục ngược (inverted index) cho tìm kiếm toàn văn.
+//!
+//! Mỗi thực thể khai báo các trường văn bản qua `Entity::text()`. Khi ghi, văn bản được tách
+//! thành từ (chữ thường, bỏ dấu, nên "Chỉ Mục" và "chi muc" là một) và mỗi cặp (từ, bản ghi)
+//! được ghi vào tree `NAME.terms` trong cùng giao dịch với bản ghi và chỉ mục bao phủ.
+//!
+//! ```rust,ignore
+//! for hit in store.search::<memories::Entry>("covering index", 10).await? {
+//!     println!("{:.2} {:?}", hit.score, hit.summary);
+//! }
+//! ```
+//!
+//! Khóa trong tree: `từ 0x00 khóa-bản-ghi`, giá trị: số lần xuất hiện (u32 big-endian).
+//! Xếp hạng theo TF-IDF; từ cuối cùng của truy vấn khớp cả tiền tố với trọng số thấp hơn.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::serializer::Codec;
+use crate::storage::actor::Actorable;
+use crate::storage::check::Walk;
+use crate::storage::entity::Entity;
+use crate::storage::sled::Sled;
+use crate::storage::time::now;
+use crate::Error;
+
+/// Số cặp khóa/giá trị đọc mỗi lần quét
+const PAGE: usize = 1024;
+
+/// Trọng số của từ chỉ khớp tiền tố so với khớp trọn
+const PARTIAL: f64 = 0.5;
+
+/// Tên tree chỉ mục ngược của một thực thể
+pub fn terms(name: &str) -> String {
+    format!("{}.terms", name)
+}
+
+/// Tách văn bản thành các từ đã chuẩn hóa: chữ thường, bỏ dấu, chỉ giữ chữ và số.
+pub fn words(text: &str) -> Vec<String> {
+    let folded: String = text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .map(|c| if c == 'đ' || c == 'Đ' { 'd' } else { c })
+        .flat_map(char::to_lowercase)
+        .collect();
+    folded.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Đếm số lần xuất hiện của từng từ trong các trường văn bản
+pub(crate) fn count(fields: &[String]) -> BTreeMap<String, u32> {
+    let mut counts = BTreeMap::new();
+    for word in fields.iter().flat_map(|f| words(f)) {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Khóa của một cặp (từ, bản ghi) trong tree chỉ mục ngược
+pub(crate) fn posting(word: &str, key: &[u8]) -> Vec<u8> {
+    let mut posting = Vec::with_capacity(word.len() + 1 + key.len());
+    posting.extend_from_slice(word.as_bytes());
+    posting.push(0);
+    posting.extend_from_slice(key);
+    posting
+}
+
+/// Một kết quả tìm kiếm
+#[derive(Debug, Clone)]
+pub struct Hit<S> {
+    /// Điểm xếp hạng, càng cao càng liên quan
+    pub score: f64,
+    /// Tóm tắt của bản ghi khớp
+    pub summary: S,
+}
+
+impl<C: Codec> Sled<C> {
+    /// Tìm các bản ghi của `E` chứa các từ trong `text`, xếp theo độ liên quan giảm dần.
+    ///
+    /// Bản ghi chứa càng nhiều từ của truy vấn càng được ưu tiên; từ hiếm có trọng số cao hơn.
+    pub async fn search<E: Entity>(&self, text: &str, limit: usize) -> Result<Vec<Hit<E::Summary>>, Error> {
+        let mut query = words(text);
+        let mut seen = HashSet::new();
+        query.retain(|w| seen.insert(w.clone()));
+        if query.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let tree = terms(E::NAME);
+        let mut postings = Vec::with_capacity(query.len());
+        for (i, word) in query.iter().enumerate() {
+            // Chỉ từ cuối cùng khớp tiền tố, để gõ dở vẫn tìm được
+            let partial = i + 1 == query.len();
+            let mut docs: HashMap<Vec<u8>, f64> = HashMap::new();
+            let mut after = None;
+            loop {
+                let page = self.handle.scan(&tree, word.as_bytes().to_vec(), after.take(), PAGE).await?;
+                let done = page.len() < PAGE;
+                for (key, value) in page {
+                    if let Some(split) = key.iter().position(|b| *b == 0) {
+                        let exact = &key[..split] == word.as_bytes();
+                        if exact || partial {
+                            let tf = value.get(..4).and_then(|b| b.try_into().ok()).map(u32::from_be_bytes).unwrap_or(1);
+                            let weight = (1.0 + (tf as f64).ln()) * if exact { 1.0 } else { PARTIAL };
+                            let best = docs.entry(key[split + 1..].to_vec()).or_insert(0.0);
+                            *best = best.max(weight);
+                        }
+                    }
+                    after = Some(key);
+                }
+                if done {
+                    break;
+                }
+            }
+            postings.push(docs);
+        }
+
+        // Tổng số bản ghi ứng viên làm mẫu số cho IDF
+        let total = postings.iter().flat_map(|docs| docs.keys()).collect::<HashSet<_>>().len();
+        let mut scores: HashMap<Vec<u8>, (f64, usize)> = HashMap::new();
+        for docs in postings {
+            let idf = (1.0 + total as f64 / docs.len().max(1) as f64).ln();
+            for (key, weight) in docs {
+                let score = scores.entry(key).or_insert((0.0, 0));
+                score.0 += weight * idf;
+                score.1 += 1;
+            }
+        }
+        let mut ranked: Vec<_> = scores.into_iter()
+            .map(|(key, (score, matched))| (key, score * matched as f64 / query.len() as f64))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked.truncate(limit);
+
+        let keys = ranked.iter().map(|(key, _)| key.clone()).collect();
+        let values = self.handle.many(E::NAME, keys).await?;
+        let mut hits = Vec::with_capacity(ranked.len());
+        for ((_, score), value) in ranked.into_iter().zip(values) {
+            // Bản ghi có thể vừa bị xóa giữa lúc quét và lúc đọc
+            if let Some(bytes) = value {
+                let entity = self.decode::<E, E>(&bytes)?;
+                hits.push(Hit { score, summary: entity.summary() });
+            }
+        }
+        Ok(hits)
+    }
+
+    /// Dựng lại chỉ mục ngược của `E` từ tree dữ liệu, trả về số cặp (từ, bản ghi) thiếu hoặc lệch đã ghi.
+    /// Dùng cho dữ liệu ghi trước khi thực thể khai báo `text()`, hoặc khi cách tách từ thay đổi.
+    ///
+    /// Như `repair`, chỉ mục ngược đúng được dựng vào một tree tạm (từng lô, ghi kép trong lúc dựng),
+    /// rồi so song song với tree hiện hành từng trang một; có khác biệt thì tree tạm thay tree hiện hành.
+    /// Không chạy đồng thời với `refresh` khác của cùng thực thể (lần gọi sau nhận `Error::Conflict`).
+    pub async fn refresh<E: Entity>(&self) -> Result<usize, Error> {
+        let tree = terms(E::NAME);
+        let target = format!("{}.{}", tree, now());
+        self.handle.begin(&tree, &target).await?;
+        let (written, removed) = match self.diff::<E>(&tree, &target).await {
+            Ok(diff) => diff,
+            Err(e) => {
+                self.handle.cancel(&tree).await?;
+                return Err(e);
+            }
+        };
+        if written + removed == 0 {
+            self.handle.cancel(&tree).await?;
+        } else {
+            self.handle.swap(&tree).await?;
+        }
+        tracing::info!(entity = E::NAME, written, removed, "Đã dựng lại chỉ mục ngược");
+        Ok(written)
+    }
+
+    /// Ghi chỉ mục ngược đúng vào `target` rồi đếm số cặp thiếu hoặc lệch và số cặp thừa trong `tree`
+    async fn diff<E: Entity>(&self, tree: &str, target: &str) -> Result<(usize, usize), Error> {
+        self.fill::<E, _>(target, |entity, key| {
+            Ok(count(&entity.text()).into_iter().map(|(word, n)| (posting(&word, key), n.to_be_bytes().to_vec())).collect())
+        }).await?;
+
+        let (mut written, mut removed) = (0, 0);
+        let mut actual = Walk::new(tree);
+        let mut wanted = Walk::new(target);
+        let mut left = actual.next(self).await?;
+        let mut right = wanted.next(self).await?;
+        loop {
+            let order = match (&left, &right) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((key, _)), Some((expected, _))) => key.cmp(expected),
+            };
+            match order {
+                Ordering::Less => {
+                    removed += 1;
+                    left = actual.next(self).await?;
+                }
+                Ordering::Greater => {
+                    written += 1;
+                    right = wanted.next(self).await?;
+                }
+                Ordering::Equal => {
+                    if let (Some((_, value)), Some((_, n))) = (&left, &right) {
+                        written += usize::from(value != n);
+                    }
+                    left = actual.next(self).await?;
+                    right = wanted.next(self).await?;
+                }
+            }
+        }
+        Ok((written, removed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Id, Storage};
+    use serde::{Deserialize, Serialize};
+    use tempfile::tempdir;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Note {
+        id: Id,
+        title: String,
+        body: String,
+    }
+
+    impl Entity for Note {
+        const NAME: &'static str = "notes";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index { self.id.as_bytes().to_vec() }
+        fn summary(&self) -> Self::Summary { self.title.clone() }
+        fn text(&self) -> Vec<String> { vec![self.title.clone(), self.body.clone()] }
+    }
+
+    fn note(title: &str, body: &str) -> Note {
+        Note { id: Id::new_v4(), title: title.to_string(), body: body.to_string() }
+    }
+
+    #[test]
+    fn fold() {
+        assert_eq!(words("Chỉ Mục bao phủ, ĐỒNG BỘ!"), vec!["chi", "muc", "bao", "phu", "dong", "bo"]);
+        assert_eq!(words("covering-index v2"), vec!["covering", "index", "v2"]);
+    }
+
+    #[tokio::test]
+    async fn search() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let a = note("Chỉ mục bao phủ", "covering index cho truy vấn danh sách");
+        let b = note("Export", "xuất dữ liệu ra csv, index không cần");
+        let c = note("Ghi chú", "không liên quan");
+        for n in [&a, &b, &c] {
+            store.insert(n.clone()).await.unwrap();
+        }
+
+        let hits = store.search::<Note>("covering index", 10).await.unwrap();
+        let titles: Vec<_> = hits.iter().map(|h| h.summary.as_str()).collect();
+        assert_eq!(titles, vec!["Chỉ mục bao phủ", "Export"]);
+        assert!(hits[0].score > hits[1].score);
+
+        // Không phân biệt hoa thường và dấu, từ cuối khớp tiền tố
+        let hits = store.search::<Note>("CHI MUC", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(store.search::<Note>("expo", 10).await.unwrap()[0].summary, "Export");
+
+        // Chỉ mục ngược theo kịp cập nhật và xóa
+        store.update::<Note, _>(b.id, |mut n| { n.body = "chỉ còn csv".to_string(); n }).await.unwrap();
+        assert_eq!(store.search::<Note>("index", 10).await.unwrap().len(), 1);
+        store.delete::<Note>(a.id).await.unwrap();
+        assert!(store.search::<Note>("covering", 10).await.unwrap().is_empty());
+        assert_eq!(store.refresh::<Note>().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn refresh() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let notes: Vec<_> = (0..600).map(|i| note(&format!("ghi chú {}", i), "chỉ mục ngược")).collect();
+        store.mass(Box::new(notes.clone().into_iter())).await.unwrap();
+
+        // Làm hỏng có chủ đích: cặp thiếu, cặp lệch số lần xuất hiện, cặp mồ côi
+        let tree = terms(Note::NAME);
+        let key = bincode::serialize(&notes[0].id).unwrap();
+        let mut batch = crate::storage::actor::message::Batch::default();
+        batch.remove(&tree, posting("nguoc", &key));
+        batch.put(&tree, posting("muc", &key), 7u32.to_be_bytes().to_vec());
+        batch.put(&tree, posting("ma", b"ghost"), 1u32.to_be_bytes().to_vec());
+        store.handle.apply(batch).await.unwrap();
+
+        assert_eq!(store.refresh::<Note>().await.unwrap(), 2);
+        assert!(store.search::<Note>("ma", 10).await.unwrap().is_empty());
+        assert_eq!(store.search::<Note>("nguoc", 1000).await.unwrap().len(), 600);
+        assert_eq!(store.refresh::<Note>().await.unwrap(), 0);
+
+        // Tree tạm đã thay tree hiện hành không bị đếm như một thực thể
+        let stats = store.stats().await.unwrap();
+        let names: Vec<_> = stats.trees.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec![Note::NAME]);
+    }
+}
@@ -11,22 +11,81 @@
 // ---
 // Import các module, trait, struct cần thiết cho lưu trữ, đồng bộ hóa, cache, metric, tracing, v.v.
 use crate::storage::actor::{Handle, Actor, Actorable};
+use crate::storage::actor::message::Batch;
+use crate::serializer::{Bincode, Codec};
+use serde::{de::DeserializeOwned, Serialize};
 use crate::Error;
 use async_trait::async_trait;
 use crate::storage::entity::{Entity, Query};
+use crate::storage::search::{count, posting, terms};
+use crate::storage::change::{self, Page};
+use crate::storage::time::{Clock, System};
+use crate::storage::id::Sequence;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Wrapper xung quanh actor lưu trữ
 /// Mục đích: Gom nhóm các thành phần lưu trữ qua actor để tối ưu hóa hiệu năng và khả năng mở rộng
+/// Giá trị được mã hóa bằng codec `C` (mặc định Bincode); khóa luôn dùng bincode để giữ thứ tự ổn định.
 #[derive(Clone)]
-pub struct Sled {
+pub struct Sled<C: Codec = Bincode> {
     pub handle: Handle,
+    codec: C,
+    /// Nguồn thời gian cho nhật ký thay đổi và `Storage::now`
+    clock: Arc<dyn Clock>,
+    /// Bộ đếm UUIDv7 riêng của store (xem `id::Sequence`)
+    sequence: Arc<Sequence>,
+    /// Thời gian giữ bia mộ trong nhật ký thay đổi (xem `Sled::expire`)
+    pub(crate) retention: Duration,
 }
 
 impl Sled {
     pub fn new(path: &str) -> Result<Self, Error> {
+        Self::with(path, Bincode)
+    }
+}
+
+impl<C: Codec> Sled<C> {
+    /// Mở store với codec giá trị tùy chọn, ví dụ `Sled::with(path, Json)` để dữ liệu đọc được bằng tay.
+    pub fn with(path: &str, codec: C) -> Result<Self, Error> {
         let inner = Inner::new(path)?;
         let actor = Actor::new(inner);
-        Ok(Self { handle: actor.handle() })
+        Ok(Self { handle: actor.handle(), codec, clock: Arc::new(System), sequence: Arc::new(Sequence::new()), retention: change::RETENTION })
+    }
+
+    /// Dùng clock khác đồng hồ hệ thống, ví dụ `time::Manual` để test điều khiển thời gian
+    /// Bộ đếm UUIDv7 được làm mới theo clock, nên id luôn mang thời điểm của clock này.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self.sequence = Arc::new(Sequence::new());
+        self
+    }
+
+    /// Giữ bia mộ trong nhật ký thay đổi trong `retention` thay vì `change::RETENTION`
+    pub fn retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Codec giá trị đang dùng
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Mã hóa một giá trị của thực thể `E`: theo `E::CODEC` nếu có, ngược lại theo codec của store
+    pub(crate) fn encode<E: Entity, T: Serialize + DeserializeOwned>(&self, data: &T) -> Result<Vec<u8>, Error> {
+        match E::CODEC {
+            Some(codec) => codec.encode(data),
+            None => self.codec.encode(data),
+        }
+    }
+
+    /// Giải mã một giá trị của thực thể `E`, cùng quy tắc chọn codec với `encode`
+    pub(crate) fn decode<E: Entity, T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        match E::CODEC {
+            Some(codec) => codec.decode(bytes),
+            None => self.codec.decode(bytes),
+        }
     }
 }
 
@@ -39,36 +98,126 @@ pub(crate) struct Inner {
     pub cache: crate::storage::cache::Cache<Vec<u8>, Vec<u8>>,
     #[allow(dead_code)]
     pub metric: crate::metric::Registry,
+    /// Bảng tuyến tree logic -> tree vật lý, được lưu trong tree `ROUTES`
+    pub routes: crate::storage::actor::state::Routes,
 }
 
+/// Tree lưu tuyến đã chốt của các tree logic (ví dụ chỉ mục sau reindex)
+pub(crate) const ROUTES: &str = "__routes";
+
 impl Inner {
     pub fn new(path: &str) -> Result<Self, Error> {
         let db = sled::Config::new()
             .path(path)
             .temporary(path.is_empty())
             .open()?;
-        let pool = crate::storage::pool::Pool::new(10, || Ok(db.clone()))?;
-        let cache = crate::storage::cache::Cache::new(std::time::Duration::from_secs(300));
         let metric = crate::metric::Registry::new();
-        Ok(Self { db, pool, cache, metric })
+        let pool = crate::storage::pool::Pool::new(10, || Ok(db.clone()))?.metric(&metric, "store");
+        let cache = crate::storage::cache::Cache::new(std::time::Duration::from_secs(300)).metric(&metric, "store");
+        let saved = db.open_tree(ROUTES)?.iter()
+            .map(|kv| kv.map(|(k, v)| (String::from_utf8_lossy(&k).into_owned(), String::from_utf8_lossy(&v).into_owned())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let routes = crate::storage::actor::state::Routes::new(saved);
+        Ok(Self { db, pool, cache, metric, routes })
+    }
+}
+
+/// Số lần thử lại tối đa khi một thao tác ghi bị `Error::Conflict`
+const ATTEMPTS: usize = 8;
+
+/// Số thực thể mỗi giao dịch trong `mass`
+const CHUNK: usize = 512;
+
+/// Số mục chỉ mục tối thiểu mỗi lần quét khi truy vấn có bộ lọc dư
+const PAGE: usize = 64;
+
+/// Tên tree chỉ mục bao phủ của một loại thực thể (tree dữ liệu dùng chính `Entity::NAME`).
+pub fn index(name: &str) -> String {
+    format!("{}.index", name)
+}
+
+impl<C: Codec> Sled<C> {
+    /// Đưa vào lô các thao tác ghi (hoặc xóa nếu `new` là None) một bản ghi cùng mục chỉ mục của nó.
+    /// Mục chỉ mục của giá trị cũ `old` (nếu có) được gỡ trước để không để lại mục mồ côi.
+    /// Chỉ mục ngược (`Entity::text`) được cập nhật theo cùng cách, kèm một mục nhật ký thay đổi.
+    pub(crate) fn stage<E: Entity>(&self, batch: &mut Batch, key: &[u8], old: Option<&[u8]>, new: Option<&E>) -> Result<(), Error> {
+        let tree = index(E::NAME);
+        let text = terms(E::NAME);
+        let mut last = Vec::new();
+        if let Some(bytes) = old {
+            match self.decode::<E, E>(bytes) {
+                Ok(prev) => {
+                    last = prev.index().as_ref().to_vec();
+                    batch.remove(&tree, last.clone());
+                    for word in count(&prev.text()).into_keys() {
+                        batch.remove(&text, posting(&word, key));
+                    }
+                }
+                Err(e) => tracing::warn!(?e, entity = E::NAME, "Không giải mã được bản ghi cũ, mục chỉ mục cũ có thể bị mồ côi"),
+            }
+        }
+        match new {
+            Some(entity) => {
+                batch.put(E::NAME, key.to_vec(), self.encode::<E, _>(entity)?);
+                let index = entity.index().as_ref().to_vec();
+                batch.put(&tree, index.clone(), self.encode::<E, _>(&entity.summary())?);
+                for (word, n) in count(&entity.text()) {
+                    batch.put(&text, posting(&word, key), n.to_be_bytes().to_vec());
+                }
+                change::stage(batch, E::NAME, key, &index, false);
+            }
+            None => {
+                batch.remove(E::NAME, key.to_vec());
+                change::stage(batch, E::NAME, key, &last, true);
+            }
+        }
+        Ok(())
+    }
+
+    /// Ghi một lô thực thể trong một giao dịch, thử lại khi có xung đột
+    async fn chunk<E: Entity>(&self, entities: &[E]) -> Result<(), Error>
+    where E::Key: serde::Serialize {
+        let keys = entities.iter()
+            .map(|e| bincode::serialize(&e.key()))
+            .collect::<Result<Vec<_>, _>>()?;
+        for _ in 0..ATTEMPTS {
+            let olds = self.handle.many(E::NAME, keys.clone()).await?;
+            let mut batch = Batch::default();
+            // Giá trị hiện hành của từng khóa trong lô, để khóa lặp lại gỡ đúng chỉ mục vừa ghi
+            let mut current: std::collections::HashMap<&[u8], Option<Vec<u8>>> = std::collections::HashMap::new();
+            for ((key, old), entity) in keys.iter().zip(olds).zip(entities) {
+                let prev = match current.get(key.as_slice()) {
+                    Some(prev) => prev.clone(),
+                    None => {
+                        batch.expect(E::NAME, key.clone(), old.clone());
+                        old
+                    }
+                };
+                self.stage(&mut batch, key, prev.as_deref(), Some(entity))?;
+                current.insert(key, Some(self.encode::<E, _>(entity)?));
+            }
+            match self.handle.apply(batch).await {
+                Err(Error::Conflict) => continue,
+                res => return res,
+            }
+        }
+        Err(Error::Conflict)
     }
 }
 
 #[async_trait]
-impl crate::storage::Storage for Sled {
+impl<C: Codec> crate::storage::Storage for Sled<C> {
     async fn insert<E: Entity>(&self, entity: E) -> Result<(), Error>
     where E::Key: std::fmt::Debug + serde::Serialize, E::Index: std::fmt::Debug {
-        let key = bincode::serialize(&entity.key())?;
-        let value = bincode::serialize(&entity)?;
-        self.handle.insert(key, value).await
+        self.chunk(std::slice::from_ref(&entity)).await
     }
 
     async fn fetch<E: Entity>(&self, key: E::Key) -> Result<Option<E>, Error>
     where E::Key: std::fmt::Debug + serde::Serialize {
         let key = bincode::serialize(&key)?;
-        let res = self.handle.fetch(key).await?;
+        let res = self.handle.get(E::NAME, key).await?;
         match res {
-            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            Some(bytes) => Ok(Some(self.decode::<E, E>(&bytes)?)),
             None => Ok(None),
         }
     }
@@ -77,71 +226,151 @@ impl crate::storage::Storage for Sled {
     where
         F: FnOnce(E) -> E + Send + 'static,
         E::Key: std::fmt::Debug + serde::Serialize {
-        let old = self.fetch::<E>(key.clone()).await?.ok_or(Error::Missing)?;
-        let new = transform(old);
         let key = bincode::serialize(&key)?;
-        let value = bincode::serialize(&new)?;
-        let res = self.handle.update(key, value).await?;
-        Ok(bincode::deserialize(&res)?)
+        // transform chỉ chạy được một lần nên việc đọc, biến đổi và ghi diễn ra trọn trong actor:
+        // không thao tác ghi nào chen vào giữa, caller không bao giờ nhận `Error::Conflict`
+        let this = self.clone();
+        let name = key.clone();
+        let plan = Box::new(move |old: Option<Vec<u8>>| {
+            let old = old.ok_or(Error::Missing)?;
+            let new = transform(this.decode::<E, E>(&old)?);
+            let mut batch = Batch::default();
+            this.stage(&mut batch, &name, Some(&old), Some(&new))?;
+            Ok((batch, this.encode::<E, _>(&new)?))
+        });
+        let value = self.handle.modify(E::NAME, key, plan).await?;
+        self.decode::<E, E>(&value)
     }
 
     async fn delete<E: Entity>(&self, key: E::Key) -> Result<E, Error>
     where E::Key: std::fmt::Debug + serde::Serialize {
         let key = bincode::serialize(&key)?;
-        let res = self.handle.delete(key).await?;
-        Ok(bincode::deserialize(&res)?)
+        for _ in 0..ATTEMPTS {
+            let old = self.handle.get(E::NAME, key.clone()).await?.ok_or(Error::Missing)?;
+            let entity = self.decode::<E, E>(&old)?;
+            let mut batch = Batch::default();
+            batch.expect(E::NAME, key.clone(), Some(old.clone()));
+            self.stage::<E>(&mut batch, &key, Some(&old), None)?;
+            match self.handle.apply(batch).await {
+                Err(Error::Conflict) => continue,
+                res => return res.map(|_| entity),
+            }
+        }
+        Err(Error::Conflict)
     }
 
     async fn query<E: Entity>(&self, query: Query<E::Index>) -> Result<Box<dyn Iterator<Item = Result<E::Summary, Error>> + Send>, Error>
     where E::Index: std::fmt::Debug {
-        tracing::debug!("Sled query với prefix: {:?}, after: {:?}, limit: {}", query.prefix, query.after, query.limit);
-        
-        let res = self.handle.query().await?;
+        tracing::debug!("Sled query với prefix: {:?}, after: {:?}, limit: {}, filter: {:?}", query.prefix, query.after, query.limit, query.filter);
+
+        // Tiền tố tường minh được ưu tiên; nếu không có, để thực thể suy ra từ biểu thức lọc
+        let prefix = match (&query.filter, query.prefix.is_empty()) {
+            (Some(filter), true) => E::prefix(filter),
+            _ => query.prefix,
+        };
+        // Có bộ lọc dư thì đọc theo trang và tiếp tục cho tới khi đủ `limit` kết quả
+        let page = match query.filter {
+            Some(_) => query.limit.clamp(PAGE, PAGE * 16),
+            None => query.limit,
+        };
+        let tree = index(E::NAME);
+        let mut after = query.after.map(|a| a.as_ref().to_vec());
         let mut items: Vec<E::Summary> = Vec::new();
-        
-        for (i, bytes) in res.into_iter().enumerate() {
-            if i >= query.limit {
-                break;
-            }
-            
-            match bincode::deserialize::<E>(&bytes) {
-                Ok(entry) => {
-                    items.push(entry.summary());
-                },
-                Err(e) => {
-                    tracing::warn!("Lỗi deserialize item {}: {:?}", i, e);
-                    // Bỏ qua item lỗi thay vì fail toàn bộ query
-                    continue;
+
+        // Quét trực tiếp chỉ mục bao phủ: không cần đọc bản ghi đầy đủ
+        while items.len() < query.limit {
+            let res = self.handle.scan(&tree, prefix.clone(), after.take(), page).await?;
+            let done = res.len() < page || query.filter.is_none();
+            for (key, bytes) in res {
+                let summary = match self.decode::<E, E::Summary>(&bytes) {
+                    Ok(summary) => summary,
+                    Err(e) => {
+                        tracing::warn!("Lỗi deserialize mục chỉ mục {:?}: {:?}", key, e);
+                        // Bỏ qua item lỗi thay vì fail toàn bộ query
+                        after = Some(key);
+                        continue;
+                    }
+                };
+                after = Some(key);
+                if let Some(filter) = &query.filter {
+                    if !filter.check(&summary)? {
+                        continue;
+                    }
+                }
+                items.push(summary);
+                if items.len() >= query.limit {
+                    break;
                 }
             }
+            if done {
+                break;
+            }
         }
-        
+
         tracing::debug!("Query trả về {} items thành công", items.len());
         Ok(Box::new(items.into_iter().map(Ok)))
     }
 
-    async fn mass<E: Entity>(&self, iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
+    async fn mass<E: Entity>(&self, mut iter: Box<dyn Iterator<Item = E> + Send>) -> Result<(), Error>
     where E::Key: std::fmt::Debug + serde::Serialize, E::Index: std::fmt::Debug {
-        let entries: Vec<(Vec<u8>, Vec<u8>)> = iter.map(|e| {
-            let k = bincode::serialize(&e.key()).unwrap();
-            let v = bincode::serialize(&e).unwrap();
-            (k, v)
-        }).collect();
-        self.handle.mass(entries).await
+        loop {
+            let chunk: Vec<E> = iter.by_ref().take(CHUNK).collect();
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            self.chunk(&chunk).await?;
+        }
+    }
+
+    async fn stats(&self) -> Result<crate::storage::stats::Stats, Error> {
+        self.handle.stats().await
+    }
+
+    async fn records<E: Entity>(&self, after: Option<E::Key>, limit: usize) -> Result<Vec<E>, Error>
+    where E::Key: std::fmt::Debug + serde::Serialize {
+        let after = after.map(|key| bincode::serialize(&key)).transpose()?;
+        let page = self.handle.scan(E::NAME, Vec::new(), after, limit).await?;
+        page.iter().map(|(_, bytes)| self.decode::<E, E>(bytes)).collect()
+    }
+
+    async fn select<E: Entity>(&self, prefix: Vec<u8>, after: Option<Vec<u8>>, limit: usize) -> Result<Vec<(Vec<u8>, E::Summary)>, Error> {
+        let page = self.handle.scan(&index(E::NAME), prefix, after, limit).await?;
+        page.into_iter().map(|(key, bytes)| Ok((key, self.decode::<E, E::Summary>(&bytes)?))).collect()
+    }
+
+    async fn changes<E: Entity>(&self, after: Option<Vec<u8>>, limit: usize) -> Result<Page<E>, Error> {
+        self.journal(after, limit).await
+    }
+
+    async fn position(&self) -> Result<Vec<u8>, Error> {
+        self.head().await
+    }
+
+    fn now(&self) -> u128 {
+        self.clock.now()
+    }
+
+    fn generate<E: Entity>(&self, time: u128) -> crate::Id {
+        self.sequence.generate(E::SCHEME, time)
     }
 
     #[cfg(any(test, feature = "testing"))]
-    async fn keys<E: Entity>(&self, _query: Query<E::Index>) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
+    async fn keys<E: Entity>(&self, query: Query<E::Index>) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>, Error>> + Send>, Error>
     where E::Index: std::fmt::Debug {
-        let res = self.handle.keys().await?;
-        Ok(Box::new(res.into_iter().map(Ok)))
+        let after = query.after.map(|a| a.as_ref().to_vec());
+        let res = self.handle.scan(&index(E::NAME), query.prefix, after, query.limit).await?;
+        Ok(Box::new(res.into_iter().map(|(k, _)| Ok(k))))
     }
 }
 
+#[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
     use crate::storage::Storage;
-    use crate::{Entity, Id, Sled};
+    use crate::{Entity, Id, Query, Sled};
+    use crate::serializer::{Encoding, Json};
+    use crate::storage::predicate::Predicate;
+    use crate::storage::actor::Actorable;
     use serde::{Serialize, Deserialize};
     use tempfile::tempdir;
 
@@ -218,4 +447,124 @@ mod tests {
             assert_eq!(*item, fetched);
         }
     }
+
+    #[tokio::test]
+    async fn json() {
+        let dir = tempdir().unwrap();
+        let store = Sled::with(dir.path().to_str().unwrap(), Json).unwrap();
+        let item = Thing { id: Id::new_v4(), name: "Json".to_string(), value: 7 };
+        store.insert(item.clone()).await.unwrap();
+        // Giá trị lưu dưới dạng JSON, đọc được trực tiếp từ backend
+        let key = bincode::serialize(&item.id).unwrap();
+        let raw = store.handle.get(Thing::NAME, key).await.unwrap().unwrap();
+        let text = String::from_utf8(raw).unwrap();
+        assert!(text.contains("\"name\":\"Json\""));
+        // Đọc lại qua codec
+        let fetched = store.fetch::<Thing>(item.id).await.unwrap().unwrap();
+        assert_eq!(item, fetched);
+        let names: Vec<_> = store.query::<Thing>(Query::default()).await.unwrap()
+            .map(|b| b.unwrap().name)
+            .collect();
+        assert_eq!(names, vec!["Json".to_string()]);
+    }
+
+    /// Cùng dữ liệu với `Thing` nhưng ghi đè codec của store
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Plain {
+        id: Id,
+        name: String,
+    }
+
+    impl Entity for Plain {
+        const NAME: &'static str = "plain";
+        const CODEC: Option<Encoding> = Some(Encoding::Json);
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index { self.name.clone().into_bytes() }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    #[tokio::test]
+    async fn encoding() {
+        let store = memory();
+        let plain = Plain { id: Id::new_v4(), name: "Plain".to_string() };
+        let thing = Thing { id: Id::new_v4(), name: "Thing".to_string(), value: 1 };
+        store.insert(plain.clone()).await.unwrap();
+        store.insert(thing.clone()).await.unwrap();
+
+        // Plain ghi JSON dù store dùng Bincode; Thing vẫn theo codec của store
+        let raw = store.handle.get(Plain::NAME, bincode::serialize(&plain.id).unwrap()).await.unwrap().unwrap();
+        assert!(String::from_utf8(raw).unwrap().contains("\"name\":\"Plain\""));
+        let raw = store.handle.get(Thing::NAME, bincode::serialize(&thing.id).unwrap()).await.unwrap().unwrap();
+        assert_eq!(raw, bincode::serialize(&thing).unwrap());
+
+        let updated = store.update::<Plain, _>(plain.id, |mut p| { p.name = "Renamed".to_string(); p }).await.unwrap();
+        assert_eq!(store.fetch::<Plain>(plain.id).await.unwrap(), Some(updated));
+        let names: Vec<_> = store.query::<Plain>(Query::default()).await.unwrap().map(|s| s.unwrap()).collect();
+        assert_eq!(names, vec!["Renamed".to_string()]);
+        assert!(store.check::<Plain>().await.unwrap().clean());
+        assert_eq!(store.fetch::<Thing>(thing.id).await.unwrap(), Some(thing));
+    }
+
+    #[tokio::test]
+    async fn filter() {
+        let store = memory();
+        let things: Vec<_> = (0..100).map(|i| Thing { id: Id::new_v4(), name: format!("Thing {}", i), value: i }).collect();
+        store.mass(Box::new(things.into_iter())).await.unwrap();
+
+        // Limit được tính sau khi lọc, kể cả khi kết quả khớp nằm rải rác
+        let sevens = Predicate::contains("name", "7");
+        let query = Query { limit: 5, ..Default::default() }.filter(sevens.clone());
+        let names: Vec<_> = store.query::<Thing>(query).await.unwrap().map(|b| b.unwrap().name).collect();
+        assert_eq!(names.len(), 5);
+        assert!(names.iter().all(|n| n.contains('7')));
+
+        let query = Query { limit: 1000, ..Default::default() }.filter(sevens.and(!Predicate::eq("name", "Thing 77")));
+        assert_eq!(store.query::<Thing>(query).await.unwrap().count(), 18);
+    }
+
+    #[tokio::test]
+    async fn stats() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        let things: Vec<_> = (0..10).map(|i| Thing { id: Id::new_v4(), name: format!("Thing {}", i), value: i }).collect();
+        store.mass(Box::new(things.into_iter())).await.unwrap();
+        let stats = store.stats().await.unwrap();
+        assert_eq!(stats.trees.len(), 1);
+        let tree = &stats.trees[0];
+        assert_eq!(tree.name, "things");
+        assert_eq!((tree.records, tree.entries), (10, 10));
+        assert!(tree.keys > 0 && tree.values > 0 && tree.index > 0);
+        assert!(tree.average() > 0.0);
+        assert!(stats.disk > 0);
+
+        // Tên chứa ".terms" không bị coi là tree phụ; tree chỉ mục sau reindex vẫn thuộc "things"
+        let dotted = Dotted { id: Id::new_v4(), name: "log".to_string() };
+        store.insert(dotted).await.unwrap();
+        store.reindex::<Thing>().await.unwrap();
+        let stats = store.stats().await.unwrap();
+        let names: Vec<_> = stats.trees.iter().map(|t| (t.name.as_str(), t.records, t.entries)).collect();
+        assert_eq!(names, vec![("my.terms.log", 1, 1), ("things", 10, 10)]);
+    }
+
+    /// Thực thể có tên trông giống tree phụ
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Dotted {
+        id: Id,
+        name: String,
+    }
+
+    impl Entity for Dotted {
+        const NAME: &'static str = "my.terms.log";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index { self.name.clone().into_bytes() }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
 }
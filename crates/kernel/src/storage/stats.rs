@@ -0,0 +1,54 @@
+//! Thống kê dung lượng của backend lưu trữ: số bản ghi, số mục chỉ mục, số byte theo từng thực thể.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Thống kê của một thực thể: tree dữ liệu `NAME` cùng tree chỉ mục của nó
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tree {
+    /// Tên thực thể (`Entity::NAME`)
+    pub name: String,
+    /// Số bản ghi trong tree dữ liệu
+    pub records: u64,
+    /// Số mục trong tree chỉ mục
+    pub entries: u64,
+    /// Tổng số byte khóa của tree dữ liệu
+    pub keys: u64,
+    /// Tổng số byte giá trị của tree dữ liệu
+    pub values: u64,
+    /// Tổng số byte (khóa và tóm tắt) của tree chỉ mục
+    pub index: u64,
+}
+
+impl Tree {
+    /// Kích thước trung bình một giá trị (byte)
+    pub fn average(&self) -> f64 {
+        if self.records == 0 {
+            return 0.0;
+        }
+        self.values as f64 / self.records as f64
+    }
+}
+
+/// Thống kê toàn bộ store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Các thực thể, sắp theo tên
+    pub trees: Vec<Tree>,
+    /// Dung lượng trên đĩa do sled báo cáo (byte)
+    pub disk: u64,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<20} {:>10} {:>10} {:>12} {:>12} {:>12} {:>10}", "Tree", "Bản ghi", "Chỉ mục", "Khóa (B)", "Giá trị (B)", "Chỉ mục (B)", "TB (B)")?;
+        for tree in &self.trees {
+            writeln!(
+                f,
+                "{:<20} {:>10} {:>10} {:>12} {:>12} {:>12} {:>10.1}",
+                tree.name, tree.records, tree.entries, tree.keys, tree.values, tree.index, tree.average()
+            )?;
+        }
+        write!(f, "Dung lượng trên đĩa: {} byte", self.disk)
+    }
+}
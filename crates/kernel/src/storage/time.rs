@@ -1,6 +1,12 @@
 //! Module này cung cấp các tiện ích liên quan đến thời gian, đặc biệt là tạo timestamp.
+//!
+//! `Clock` là nguồn thời gian tiêm được: store mang một clock (`Sled::clock`) và mọi
+//! timestamp do nghiệp vụ tạo ra đi qua `Storage::now`, nên test và mô phỏng điều khiển
+//! được thời gian mà không cần `sleep`.
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Lấy thời gian hiện tại dưới dạng Unix timestamp nano giây.
 ///
@@ -10,4 +16,79 @@ pub fn now() -> u128 {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos()
-}
\ No newline at end of file
+}
+
+/// Nguồn thời gian (nano giây Unix)
+pub trait Clock: Send + Sync + Debug {
+    /// Thời điểm hiện tại theo clock này
+    fn now(&self) -> u128;
+}
+
+/// Đồng hồ hệ thống (mặc định)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct System;
+
+impl Clock for System {
+    fn now(&self) -> u128 {
+        now()
+    }
+}
+
+/// Đồng hồ đứng yên tại một thời điểm
+#[derive(Debug, Clone, Copy)]
+pub struct Fixed(pub u128);
+
+impl Clock for Fixed {
+    fn now(&self) -> u128 {
+        self.0
+    }
+}
+
+/// Đồng hồ chỉ chạy khi được chỉnh tay (`advance`, `set`); chia sẻ qua `Arc` giữa test và store
+#[derive(Debug, Default)]
+pub struct Manual {
+    time: Mutex<u128>,
+}
+
+impl Manual {
+    /// Tạo đồng hồ bắt đầu tại `start`
+    pub fn new(start: u128) -> Self {
+        Self { time: Mutex::new(start) }
+    }
+
+    /// Tiến đồng hồ thêm `by`
+    pub fn advance(&self, by: Duration) {
+        *self.time.lock().unwrap_or_else(|e| e.into_inner()) += by.as_nanos();
+    }
+
+    /// Đặt đồng hồ về thời điểm `time`
+    pub fn set(&self, time: u128) {
+        *self.time.lock().unwrap_or_else(|e| e.into_inner()) = time;
+    }
+}
+
+impl Clock for Manual {
+    fn now(&self) -> u128 {
+        *self.time.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn clocks() {
+        assert_eq!(Fixed(42).now(), 42);
+        assert!(System.now() > 0);
+
+        let manual = Arc::new(Manual::new(1_000));
+        let shared: Arc<dyn Clock> = manual.clone();
+        assert_eq!(shared.now(), 1_000);
+        manual.advance(Duration::from_micros(3));
+        assert_eq!(shared.now(), 4_000);
+        manual.set(7);
+        assert_eq!(shared.now(), 7);
+    }
+}
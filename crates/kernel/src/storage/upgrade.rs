@@ -0,0 +1,201 @@
+//! Nâng cấp dữ liệu ghi bởi các phiên bản trước sang bố cục hiện tại.
+//!
+//! Trước khi mỗi thực thể có tree riêng, mọi bản ghi nằm chung trong tree mặc định của sled
+//! với khóa `bincode(Entity::key())`, giá trị `bincode(Entity)` và không có chỉ mục.
+//! `upgrade` chuyển các bản ghi thuộc `E` sang tree `E::NAME`, ghi kèm chỉ mục bao phủ,
+//! chỉ mục ngược và nhật ký thay đổi, rồi gỡ bản ghi cũ trong cùng một giao dịch.
+//!
+//! ```rust,ignore
+//! let moved = store.upgrade::<task::Entry>().await?;
+//! ```
+//!
+//! Sau đó, nếu `E::LAYOUT` khác phiên bản bố cục chỉ mục đã ghi trong tree `__layouts`,
+//! chỉ mục của `E` được dựng lại bằng `reindex` (xem `reindex.rs`).
+//!
+//! Gọi lại nhiều lần là an toàn: khi tree cũ không còn bản ghi của `E` và bố cục chỉ mục
+//! đã khớp thì không làm gì.
+
+use crate::serializer::Codec;
+use crate::storage::actor::message::Batch;
+use crate::storage::actor::Actorable;
+use crate::storage::entity::Entity;
+use crate::storage::sled::Sled;
+use crate::Error;
+
+/// Tree mặc định của sled, nơi bố cục cũ lưu mọi bản ghi
+pub const LEGACY: &str = "__sled__default";
+
+/// Tree lưu phiên bản bố cục chỉ mục đã áp dụng, khóa `E::NAME`, giá trị `u32` big-endian
+pub const LAYOUTS: &str = "__layouts";
+
+/// Số cặp khóa/giá trị đọc mỗi lần quét
+const PAGE: usize = 512;
+
+impl<C: Codec> Sled<C> {
+    /// Chuyển bản ghi của `E` từ tree mặc định sang bố cục hiện tại, trả về số bản ghi đã chuyển.
+    ///
+    /// Tree cũ chứa lẫn mọi loại thực thể nên một cặp chỉ thuộc `E` khi giải mã được bằng bincode
+    /// và mã hóa lại cho đúng từng byte cả khóa lẫn giá trị. Nếu tree mới đã có bản ghi cùng khóa
+    /// thì bản ghi mới được giữ và bản ghi cũ chỉ bị gỡ. Cuối cùng chỉ mục được dựng lại
+    /// khi bố cục đã ghi khác `E::LAYOUT`.
+    pub async fn upgrade<E: Entity>(&self) -> Result<usize, Error>
+    where E::Key: serde::Serialize {
+        let mut count = 0;
+        let mut after = None;
+        loop {
+            let page = self.handle.scan(LEGACY, Vec::new(), after.take(), PAGE).await?;
+            let done = page.len() < PAGE;
+            let owned: Vec<(Vec<u8>, Vec<u8>, E)> = page.iter()
+                .filter_map(|(key, value)| legacy::<E>(key, value).map(|e| (key.clone(), value.clone(), e)))
+                .collect();
+            after = page.last().map(|(k, _)| k.clone());
+            if !owned.is_empty() {
+                let keys = owned.iter().map(|(k, _, _)| k.clone()).collect();
+                let current = self.handle.many(E::NAME, keys).await?;
+                let mut batch = Batch::default();
+                for ((key, value, entity), now) in owned.iter().zip(current) {
+                    batch.expect(LEGACY, key.clone(), Some(value.clone()));
+                    batch.remove(LEGACY, key.clone());
+                    if now.is_none() {
+                        batch.expect(E::NAME, key.clone(), None);
+                        self.stage(&mut batch, key, None, Some(entity))?;
+                        count += 1;
+                    }
+                }
+                self.handle.apply(batch).await?;
+            }
+            if done {
+                break;
+            }
+        }
+        if count > 0 {
+            tracing::info!(entity = E::NAME, count, "Đã chuyển bản ghi từ bố cục cũ");
+        }
+        if self.layout::<E>().await? != E::LAYOUT {
+            tracing::info!(entity = E::NAME, layout = E::LAYOUT, "Bố cục chỉ mục đã đổi, dựng lại chỉ mục");
+            self.reindex::<E>().await?;
+        }
+        Ok(count)
+    }
+
+    /// Phiên bản bố cục chỉ mục đã áp dụng cho `E`; store chưa ghi phiên bản nào được coi là 0
+    pub async fn layout<E: Entity>(&self) -> Result<u32, Error> {
+        let value = self.handle.get(LAYOUTS, E::NAME.as_bytes().to_vec()).await?;
+        Ok(value.and_then(|v| v.try_into().ok()).map(u32::from_be_bytes).unwrap_or(0))
+    }
+}
+
+/// Giải mã một cặp của tree cũ thành `E`, chỉ khi cặp đó đúng là do `E` ghi ra
+fn legacy<E: Entity>(key: &[u8], value: &[u8]) -> Option<E>
+where E::Key: serde::Serialize {
+    let entity: E = bincode::deserialize(value).ok()?;
+    let exact = bincode::serialize(&entity).ok()? == value
+        && bincode::serialize(&entity.key()).ok()? == key;
+    exact.then_some(entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Id, Query, Storage};
+    use serde::{Deserialize, Serialize};
+    use tempfile::tempdir;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Thing {
+        id: Id,
+        name: String,
+    }
+
+    impl Entity for Thing {
+        const NAME: &'static str = "things";
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index {
+            let mut index = self.name.clone().into_bytes();
+            index.extend_from_slice(self.id.as_bytes());
+            index
+        }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    #[tokio::test]
+    async fn upgrade() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        // Ghi theo bố cục cũ: tree mặc định, khóa và giá trị bincode, không chỉ mục
+        let things: Vec<_> = (0..700).map(|i| Thing { id: Id::new_v4(), name: format!("t{:03}", i) }).collect();
+        for thing in &things {
+            let key = bincode::serialize(&thing.id).unwrap();
+            store.handle.insert(key, bincode::serialize(thing).unwrap()).await.unwrap();
+        }
+        // Bản ghi của loại khác nằm chung tree thì không bị đụng tới
+        store.handle.insert(b"other".to_vec(), b"value".to_vec()).await.unwrap();
+        // Bản ghi đã có ở bố cục mới thắng bản ghi cũ cùng khóa
+        let newer = Thing { name: "newer".to_string(), ..things[0].clone() };
+        store.insert(newer.clone()).await.unwrap();
+
+        assert_eq!(store.upgrade::<Thing>().await.unwrap(), 699);
+        assert_eq!(store.fetch::<Thing>(things[1].id).await.unwrap(), Some(things[1].clone()));
+        assert_eq!(store.fetch::<Thing>(newer.id).await.unwrap(), Some(newer));
+        assert_eq!(store.query::<Thing>(Query { limit: usize::MAX, ..Default::default() }).await.unwrap().count(), 700);
+        assert!(store.check::<Thing>().await.unwrap().clean());
+
+        let rest = store.handle.scan(LEGACY, Vec::new(), None, usize::MAX).await.unwrap();
+        assert_eq!(rest, vec![(b"other".to_vec(), b"value".to_vec())]);
+        assert_eq!(store.upgrade::<Thing>().await.unwrap(), 0);
+    }
+
+    /// Cùng tree với `Thing` nhưng chỉ mục theo bố cục mới: thêm tiền tố phiên bản
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Layout {
+        id: Id,
+        name: String,
+    }
+
+    impl Entity for Layout {
+        const NAME: &'static str = "things";
+        const LAYOUT: u32 = 1;
+        type Key = Id;
+        type Index = Vec<u8>;
+        type Summary = String;
+
+        fn key(&self) -> Self::Key { self.id }
+        fn index(&self) -> Self::Index {
+            let mut index = b"v1:".to_vec();
+            index.extend_from_slice(self.name.as_bytes());
+            index.extend_from_slice(self.id.as_bytes());
+            index
+        }
+        fn summary(&self) -> Self::Summary { self.name.clone() }
+    }
+
+    #[tokio::test]
+    async fn layout() {
+        let dir = tempdir().unwrap();
+        let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+        // Chỉ mục ghi bởi phiên bản trước, khi `index()` chưa có tiền tố
+        for i in 0..10 {
+            store.insert(Thing { id: Id::new_v4(), name: format!("t{}", i) }).await.unwrap();
+        }
+        let found = |store: Sled| async move {
+            let query = Query { prefix: b"v1:t".to_vec(), limit: usize::MAX, ..Default::default() };
+            store.query::<Layout>(query).await.unwrap().count()
+        };
+        assert_eq!(found(store.clone()).await, 0);
+        assert_eq!(store.layout::<Layout>().await.unwrap(), 0);
+
+        assert_eq!(store.upgrade::<Layout>().await.unwrap(), 0);
+        assert_eq!(found(store.clone()).await, 10);
+        assert_eq!(store.layout::<Layout>().await.unwrap(), 1);
+        assert!(store.check::<Layout>().await.unwrap().clean());
+
+        // Bố cục đã khớp thì không dựng lại lần nữa
+        let reindex = store.handle.metrics().get("things.reindex").count();
+        store.upgrade::<Layout>().await.unwrap();
+        assert_eq!(store.handle.metrics().get("things.reindex").count(), reindex);
+    }
+}
@@ -1,6 +1,7 @@
 //! Module quản lý các bản ghi kiến trúc thông qua `architecture` crate.
 
 use repository::error::Fault;
+use repository::storage::import::Row;
 use repository::{Error, Storage};
 use architecture::{self, Entry}; // Chỉ import Arch, không import Summary hay đổi tên
 use shared;
@@ -126,18 +127,13 @@ pub async fn list<S: Storage>(
 ) -> Result<Box<dyn Iterator<Item = Result<architecture::Summary, repository::Error>> + Send>, repository::Error> {
     info!(r#type = ?r#type, context = ?context, module = ?module, limit = limit, "Đang thực hiện architecture list query");
     
-    let mut prefix = Vec::new();
-    if let Some(type_str) = r#type {
-        let kind = architecture::Kind::try_from(type_str)?;
-        prefix.push((&kind).into());
-        if let Some(ctx_str) = context {
-            prefix.extend_from_slice(ctx_str.as_bytes());
-            prefix.push(0); // Dấu phân cách
-            if let Some(mod_str) = module {
-                prefix.extend_from_slice(mod_str.as_bytes());
-            }
+    let prefix = match r#type {
+        Some(type_str) => {
+            let kind = architecture::Kind::try_from(type_str)?;
+            architecture::prefix(&kind, context.as_deref(), module.as_deref())
         }
-    }
+        None => Vec::new(),
+    };
     
     info!(prefix_len = prefix.len(), "Query prefix: {:?}", prefix);
     
@@ -152,3 +148,42 @@ pub async fn list<S: Storage>(
     result
 }
 
+
+
+/// Chuyển một dòng của `architecture.csv` thành bản ghi kiến trúc, dùng cho `knowledge import architecture`.
+/// Cột: Context, Module, Type, Name, Responsibility, Dependency, PerformanceNote, NamingRationale.
+/// Khóa là `context:module:type:name` nên cột ID không được dùng.
+/// Thời điểm tạo lấy từ đồng hồ của `store`.
+pub fn parse<S: Storage>(store: &S, row: &Row) -> Result<Entry, Error> {
+    let command = Add {
+        context: row.text("Context"),
+        module: row.text("Module"),
+        r#type: row.text("Type"),
+        name: row.text("Name"),
+        responsibility: row.text("Responsibility"),
+        dependency: row.text("Dependency"),
+        performance: row.text("PerformanceNote"),
+        naming: row.text("NamingRationale"),
+        prompt: String::new(),
+        created: store.now(),
+    };
+    command.validate().map_err(Error::Validation)?;
+    Ok(Entry {
+        id: repository::Id::new_v4(),
+        context: command.context,
+        module: command.module,
+        r#type: Kind::try_from(command.r#type)?,
+        name: command.name,
+        responsibility: command.responsibility,
+        dependency: command.dependency,
+        performance: command.performance,
+        naming: command.naming,
+        prompt: command.prompt,
+        created: command.created,
+    })
+}
+
+/// Khi upsert, giữ id và thời điểm tạo của bản ghi đã lưu; tệp chỉ cập nhật nội dung.
+pub fn merge(_row: &Row, stored: Entry, entry: Entry) -> Entry {
+    Entry { id: stored.id, created: stored.created, ..entry }
+}
@@ -0,0 +1,17 @@
+//! Module nhập các tệp CSV nguồn (`todo.csv`, `memories.csv`, `architecture.csv`) vào store (`knowledge import`).
+
+use repository::storage::import::{import, Merge, Options, Outcome, Row};
+use repository::{Error, Sled};
+
+use crate::{architecture, memories, task};
+
+/// Nhập tệp `path` vào miền `kind` (task, memories hoặc architecture).
+pub async fn load(store: &Sled, kind: &str, path: &str, options: Options) -> Result<Outcome, Error> {
+    let file = std::fs::File::open(path)?;
+    match kind.to_lowercase().as_str() {
+        "task" | "todo" => import(store, file, &Merge(|row: &Row| task::parse(store, row), task::merge), options).await,
+        "memories" => import(store, file, &Merge(|row: &Row| memories::parse(store, row), memories::merge), options).await,
+        "architecture" => import(store, file, &Merge(|row: &Row| architecture::parse(store, row), architecture::merge), options).await,
+        other => Err(Error::Parse(format!("Không hỗ trợ nhập loại '{}' (task, memories, architecture).", other))),
+    }
+}
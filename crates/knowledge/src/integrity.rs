@@ -0,0 +1,62 @@
+//! Module kiểm tra tính nhất quán giữa dữ liệu và chỉ mục của mọi loại bản ghi (`check-db`).
+
+use repository::storage::check::Report;
+use repository::{Entity, Error, Sled};
+
+/// Kiểm tra một loại bản ghi, dựng lại chỉ mục (cả chỉ mục ngược và nhật ký thay đổi) nếu `repair`
+async fn one<E: Entity>(store: &Sled, repair: bool) -> Result<Report, Error> {
+    if repair {
+        store.refresh::<E>().await?;
+        store.track::<E>().await?;
+        store.repair::<E>().await
+    } else {
+        store.check::<E>().await
+    }
+}
+
+/// Kiểm tra (và sửa nếu `repair`) chỉ mục của kiến trúc, bộ nhớ và công việc.
+pub async fn scan(store: &Sled, repair: bool) -> Result<Vec<Report>, Error> {
+    Ok(vec![
+        one::<architecture::Entry>(store, repair).await?,
+        one::<memories::Entry>(store, repair).await?,
+        one::<task::Entry>(store, repair).await?,
+    ])
+}
+
+/// Chuyển bản ghi ghi bởi phiên bản cũ (tree mặc định) sang bố cục hiện tại, trả về số bản ghi đã chuyển.
+/// Chạy mỗi lần khởi động; khi không còn gì để chuyển thì chỉ tốn một lần quét tree cũ.
+pub async fn upgrade(store: &Sled) -> Result<usize, Error> {
+    Ok(store.upgrade::<architecture::Entry>().await?
+        + store.upgrade::<memories::Entry>().await?
+        + store.upgrade::<task::Entry>().await?)
+}
+
+/// Dọn bia mộ quá thời gian giữ khỏi nhật ký thay đổi, trả về số bia mộ đã dọn.
+/// Chạy mỗi lần khởi động để nhật ký không lớn mãi theo số lần xóa.
+pub async fn expire(store: &Sled) -> Result<usize, Error> {
+    Ok(store.expire::<architecture::Entry>().await?
+        + store.expire::<memories::Entry>().await?
+        + store.expire::<task::Entry>().await?)
+}
+
+/// In báo cáo, kèm vài khóa lỗi đầu tiên của mỗi loại dưới dạng hex.
+pub fn show(reports: &[Report]) {
+    for report in reports {
+        let mark = if report.clean() { "✅" } else { "❌" };
+        println!("{} {}", mark, report);
+        for (label, keys) in [
+            ("mồ côi", &report.orphans),
+            ("thiếu chỉ mục", &report.missing),
+            ("không giải mã được", &report.broken),
+            ("lệch tóm tắt", &report.stale),
+        ] {
+            for key in keys.iter().take(5) {
+                let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+                println!("    • {}: {}", label, hex);
+            }
+            if keys.len() > 5 {
+                println!("    • {}: ... và {} khóa khác", label, keys.len() - 5);
+            }
+        }
+    }
+}
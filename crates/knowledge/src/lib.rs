@@ -5,6 +5,10 @@ pub mod architecture;   // Module quản lý kiến trúc
 pub mod memories;    // Module quản lý bộ nhớ
 pub mod task;   // Module quản lý công việc (todo)
 pub mod display; // Module chứa các tiện ích hiển thị
+pub mod integrity; // Module kiểm tra dữ liệu và chỉ mục (check-db)
+pub mod stats; // Module hiển thị thống kê store
+pub mod search; // Module tìm kiếm toàn văn
+pub mod import; // Module nhập tệp CSV
 
 // Tái xuất các kiểu dữ liệu và lỗi chung cần thiết cho các module con
 pub use repository::{Sled, Error, Id, Query, Storage, Key};
\ No newline at end of file
@@ -2,13 +2,17 @@
 // Binary crate là điểm vào trung tâm cho hệ thống tri thức.
 
 use clap::{Parser, Subcommand};
-use repository::{self, Sled, Id, Error};
+use repository::{self, Sled, Id, Error, Predicate};
 use tracing::info;
 
 // Import các submodule mới với tên đơn từ
 use knowledge::{architecture, memories, task};
 use knowledge::task::Status;
 use knowledge::display;
+use knowledge::integrity;
+use knowledge::stats;
+use knowledge::search;
+use knowledge::import;
 use shared::Showable;
 use shared::interaction::Interaction;
 
@@ -45,13 +49,52 @@ enum Commands {
         #[command(subcommand)]
         command: Task, // ĐÃ ĐỔI TÊN TỪ TaskCmd THÀNH Task
     },
-    /// Hiển thị số liệu thống kê hiệu suất của kho lưu trữ
-    Stats,
+    /// Hiển thị số liệu thống kê dung lượng và hiệu suất của kho lưu trữ
+    Stats {
+        /// Xuất dạng JSON thay vì bảng
+        #[arg(long)]
+        json: bool,
+        /// Theo dõi liên tục: in cửa sổ 1m/5m/15m mỗi N giây (mặc định 10) cho tới khi Ctrl-C
+        #[arg(long, value_name = "GIÂY", num_args = 0..=1, default_missing_value = "10")]
+        watch: Option<u64>,
+    },
+    /// Tìm kiếm toàn văn trên kiến trúc, bộ nhớ và công việc
+    Search {
+        /// Các từ cần tìm (không phân biệt hoa thường và dấu)
+        #[arg(required = true)]
+        terms: Vec<String>,
+        /// Số lượng tối đa hiển thị
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+        /// Dựng lại chỉ mục ngược trước khi tìm (cho dữ liệu ghi trước khi có tìm kiếm toàn văn)
+        #[arg(long)]
+        refresh: bool,
+    },
+    /// Nhập bản ghi từ tệp CSV (task, memories, architecture)
+    Import {
+        /// Loại bản ghi: task, memories hoặc architecture
+        kind: String,
+        /// Đường dẫn tệp CSV có dòng tiêu đề
+        file: String,
+        /// Ghi đè bản ghi đã có cùng khóa
+        #[arg(long)]
+        upsert: bool,
+        /// Chỉ kiểm tra, không ghi gì
+        #[arg(long)]
+        dry: bool,
+    },
     /// Phân tích mã nguồn để kiểm tra vi phạm quy tắc đặt tên
     Check {
         /// Đường dẫn đến file hoặc thư mục cần kiểm tra
         path: String,
     },
+    /// Kiểm tra tính nhất quán giữa dữ liệu và chỉ mục
+    #[command(name = "check-db")]
+    Integrity {
+        /// Dựng lại chỉ mục cho các lỗi phát hiện được
+        #[arg(long)]
+        repair: bool,
+    },
     // Lệnh cho Director để khởi tạo các luồng nghiệp vụ (sẽ được implement sau)
     // Direct {
     //     #[command(subcommand)]
@@ -152,6 +195,9 @@ enum Memories { // ĐÃ ĐỔI TÊN
         /// Lọc theo loại (ví dụ: 'Decision', 'Analysis')
         #[arg(long)]
         r#type: Option<String>,
+        /// Lọc theo module
+        #[arg(long)]
+        module: Option<String>,
         /// Số lượng tối đa hiển thị
         #[arg(short, long, default_value = "10")]
         limit: usize,
@@ -198,6 +244,14 @@ enum Task { // ĐÃ ĐỔI TÊN
         #[arg(long, conflicts_with = "done")]
         pending: bool,
 
+        /// Lọc theo module
+        #[arg(long)]
+        module: Option<String>,
+
+        /// Lọc theo người được giao
+        #[arg(long)]
+        assignee: Option<String>,
+
         /// Số lượng tối đa hiển thị
         #[arg(short, long, default_value = "10")]
         limit: usize,
@@ -335,6 +389,14 @@ async fn main() -> Result<(), repository::Error> {
 
     let cli = Cli::parse();
     let store = Sled::new(&cli.path)?;
+    let moved = integrity::upgrade(&store).await?;
+    if moved > 0 {
+        info!("Đã chuyển {} bản ghi từ bố cục lưu trữ cũ", moved);
+    }
+    let expired = integrity::expire(&store).await?;
+    if expired > 0 {
+        info!("Đã dọn {} bia mộ quá hạn khỏi nhật ký thay đổi", expired);
+    }
 
     match cli.command {
         Commands::Architecture { command } => match command {
@@ -359,7 +421,7 @@ async fn main() -> Result<(), repository::Error> {
                     performance,
                     naming,
                     prompt,
-                    created: repository::now(),
+                    created: repository::Storage::now(&store),
                 };
                 let interaction = Interaction::new(command);
                 let entry = architecture::add(&store, interaction).await?;
@@ -429,7 +491,7 @@ async fn main() -> Result<(), repository::Error> {
                     description,
                     decision,
                     rationale,
-                    created: repository::now(),
+                    created: repository::Storage::now(&store),
                 };
                 let interaction = Interaction::new(command);
                 let entry = memories::add(&store, interaction).await?;
@@ -453,8 +515,8 @@ async fn main() -> Result<(), repository::Error> {
                     }
                 }
             }
-            Memories::List { r#type, limit } => { // Cập nhật tên enum
-                let result = memories::list(&store, r#type, limit).await?;
+            Memories::List { r#type, module, limit } => { // Cập nhật tên enum
+                let result = memories::list(&store, r#type, module, limit).await?;
                 display::show(result)?;
             }
         },
@@ -504,9 +566,16 @@ async fn main() -> Result<(), repository::Error> {
                 let task = task::del(&store, id).await?;
                 println!("Đã xóa công việc: [{}], {}", task.id, task.task);
             }
-            Task::List { done, pending: _, limit } => {
-                // Sử dụng hàm `filter` từ shared để tạo query
-                let query = shared::filter(done, None, limit);
+            Task::List { done, pending, module, assignee, limit } => {
+                let status = if done { Status::Done } else if pending { Status::Pending } else { Status::Open };
+                let mut predicate = Predicate::eq("status", status);
+                if let Some(module) = module {
+                    predicate = predicate.and(Predicate::eq("module", module));
+                }
+                if let Some(assignee) = assignee {
+                    predicate = predicate.and(Predicate::eq("assignee", assignee));
+                }
+                let query = repository::Query { limit, ..Default::default() }.filter(predicate);
 
                 let results = task::list(&store, query).await?;
                 if results.is_empty() {
@@ -530,43 +599,24 @@ async fn main() -> Result<(), repository::Error> {
                 println!("Đã thay đổi công việc: [{}], {}", task.id, task.task);
             }
         },
-        Commands::Stats => {
-            #[cfg(feature = "metrics")]
-            {
-                use repository::metric::Registry;
-                
-                println!("=== THỐNG KÊ HIỆU SUẤT KHO LƯU TRỮ ===");
-                println!();
-                
-                // Tạo registry mới và hiển thị thông báo
-                let _registry = Registry::new();
-                println!("📊 TỔNG QUAN:");
-                println!("  • Metrics registry đã được khởi tạo");
-                println!("  • Chạy các thao tác để xem metrics thực tế");
-                println!();
-                
-                println!("🔧 HƯỚNG DẪN:");
-                println!("  • Thêm task: knowledge task add 'task name'");
-                println!("  • Liệt kê task: knowledge task list");
-                println!("  • Hoàn thành task: knowledge task done <id>");
-                println!("  • Xóa task: knowledge task del --id <id>");
-                println!();
-                
-                println!("📈 METRICS SẼ HIỂN THỊ:");
-                println!("  • Insert: Số lần thêm dữ liệu");
-                println!("  • Fetch: Số lần lấy dữ liệu");
-                println!("  • Update: Số lần cập nhật dữ liệu");
-                println!("  • Delete: Số lần xóa dữ liệu");
-                println!("  • Query: Số lần truy vấn dữ liệu");
-                println!("  • Mass: Số lần thao tác hàng loạt");
-                println!("  • Keys: Số lần lấy danh sách keys");
-            }
-            
-            #[cfg(not(feature = "metrics"))]
-            {
-                println!("Tính năng thống kê hiệu suất sẽ được cập nhật sau khi refactor actor pattern hoàn chỉnh.");
-                println!("Để bật metrics, hãy chạy với flag: --features metrics");
+        Commands::Stats { json, watch: Some(every) } => {
+            stats::watch(&store, every, json).await?;
+        }
+        Commands::Stats { json, watch: None } => {
+            stats::show(&store, json).await?;
+        }
+        Commands::Import { kind, file, upsert, dry } => {
+            let options = repository::storage::import::Options { upsert, dry };
+            let outcome = import::load(&store, &kind, &file, options).await?;
+            println!("{}", outcome);
+        }
+        Commands::Search { terms, limit, refresh } => {
+            if refresh {
+                let count = search::refresh(&store).await?;
+                println!("Đã dựng lại chỉ mục tìm kiếm ({} mục).", count);
             }
+            let hits = search::find(&store, &terms.join(" "), limit).await?;
+            search::show(&hits);
         }
         Commands::Check { path } => {
             println!("Bắt đầu kiểm tra quy tắc đặt tên cho: {}", path);
@@ -653,6 +703,17 @@ allowed_multi_word = [
                 println!("✅ Không tìm thấy vi phạm quy tắc đặt tên!");
             }
         }
+        Commands::Integrity { repair } => {
+            let reports = integrity::scan(&store, repair).await?;
+            integrity::show(&reports);
+            if reports.iter().all(|r| r.clean()) {
+                println!("Dữ liệu và chỉ mục nhất quán.");
+            } else if repair {
+                println!("Đã dựng lại chỉ mục; bản ghi không giải mã được cần xử lý thủ công.");
+            } else {
+                println!("Chạy lại với --repair để dựng lại chỉ mục.");
+            }
+        }
         // Commands::Direct { command } => {
         //     // Logic cho Director sẽ được thêm vào đây
         // }
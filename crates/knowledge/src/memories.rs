@@ -1,7 +1,8 @@
 //! Module quản lý các bản ghi bộ nhớ thông qua `memories` crate.
 
 use repository::error::Fault;
-use repository::{Error, Id, Storage};
+use repository::storage::import::{identity, Row};
+use repository::{Entity, Error, Id, Predicate, Storage};
 pub use memories::{Entry, Kind, Summary};
 use shared;
 use shared::interaction::Command;
@@ -103,9 +104,10 @@ pub async fn get<S: Storage>(store: &S, id: Id) -> Result<Option<Entry>, Error>
 pub async fn list<S: Storage>(
     store: &S,
     kind: Option<String>,
+    module: Option<String>,
     limit: usize,
 ) -> Result<Box<dyn Iterator<Item = Result<memories::Summary, repository::Error>> + Send>, repository::Error> {
-    info!(kind = ?kind, limit = limit, "Đang thực hiện memories list query");
+    info!(kind = ?kind, module = ?module, limit = limit, "Đang thực hiện memories list query");
     
     let prefix = match kind {
         Some(s) => {
@@ -117,7 +119,10 @@ pub async fn list<S: Storage>(
     
     info!(prefix_len = prefix.len(), "Query prefix: {:?}", prefix);
     
-    let query = shared::query(prefix, None::<Vec<u8>>, limit);
+    let mut query = shared::query(prefix, None::<Vec<u8>>, limit);
+    if let Some(module) = module {
+        query = query.filter(Predicate::eq("module", module));
+    }
     let result = memories::query(store, query).await;
     
     match &result {
@@ -128,3 +133,54 @@ pub async fn list<S: Storage>(
     result
 }
 
+
+
+/// Chuyển một dòng của `memories.csv` thành bản ghi bộ nhớ, dùng cho `knowledge import memories`.
+/// Cột: ID, Type, Context, Module, Subject, Description, Decision, Rationale, Timestamp (RFC 3339, tùy chọn).
+/// Thời điểm tạo lấy từ đồng hồ của `store` khi dòng không có `Timestamp`.
+pub fn parse<S: Storage>(store: &S, row: &Row) -> Result<Entry, Error> {
+    let command = Add {
+        r#type: row.text("Type"),
+        context: row.text("Context"),
+        module: row.text("Module"),
+        subject: row.text("Subject"),
+        description: row.text("Description"),
+        decision: row.text("Decision"),
+        rationale: row.text("Rationale"),
+        created: match row.get("Timestamp") {
+            Some(raw) => timestamp(raw)?,
+            None => store.now(),
+        },
+    };
+    command.validate().map_err(Error::Validation)?;
+    Ok(Entry {
+        id: identity(Entry::NAME, row.require("ID")?),
+        r#type: Kind::try_from(command.r#type)?,
+        context: command.context,
+        module: command.module,
+        subject: command.subject,
+        description: command.description,
+        decision: command.decision,
+        rationale: command.rationale,
+        created: command.created,
+    })
+}
+
+/// Khi upsert mà dòng không có cột Timestamp, giữ thời điểm tạo đã lưu để khóa chỉ mục và thứ tự không đổi.
+pub fn merge(row: &Row, stored: Entry, entry: Entry) -> Entry {
+    match row.get("Timestamp") {
+        Some(_) => entry,
+        None => Entry { created: stored.created, ..entry },
+    }
+}
+
+/// Đổi chuỗi RFC 3339 (ví dụ `2024-06-09T00:00:00Z`) thành nano giây Unix
+fn timestamp(raw: &str) -> Result<u128, Error> {
+    let fault = |message: &str| Error::Validation(vec![Fault {
+        field: "Timestamp".to_string(),
+        message: message.to_string(),
+    }]);
+    let time = chrono::DateTime::parse_from_rfc3339(raw).map_err(|_| fault("Thời điểm phải theo RFC 3339, ví dụ 2024-06-09T00:00:00Z."))?;
+    let nanos = time.timestamp_nanos_opt().ok_or_else(|| fault("Thời điểm nằm ngoài khoảng biểu diễn được."))?;
+    u128::try_from(nanos).map_err(|_| fault("Thời điểm không được trước năm 1970."))
+}
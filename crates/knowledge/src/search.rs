@@ -0,0 +1,48 @@
+//! Module tìm kiếm toàn văn trên kiến trúc, bộ nhớ và công việc (`knowledge search`).
+
+use repository::storage::search::Hit;
+use repository::{Entity, Error, Sled};
+use shared::Showable;
+
+/// Một kết quả kèm nhãn miền của nó
+pub type Found = (&'static str, Hit<Box<dyn Showable>>);
+
+/// Tìm trong một loại bản ghi, gắn nhãn miền vào từng kết quả
+async fn one<E: Entity>(store: &Sled, label: &'static str, text: &str, limit: usize)
+    -> Result<Vec<Found>, Error>
+where E::Summary: Showable + 'static {
+    let hits = store.search::<E>(text, limit).await?;
+    Ok(hits.into_iter()
+        .map(|hit| (label, Hit { score: hit.score, summary: Box::new(hit.summary) as Box<dyn Showable> }))
+        .collect())
+}
+
+/// Tìm trong cả ba miền, trả về tối đa `limit` kết quả xếp theo điểm giảm dần.
+pub async fn find(store: &Sled, text: &str, limit: usize) -> Result<Vec<Found>, Error> {
+    let mut hits = one::<architecture::Entry>(store, "architecture", text, limit).await?;
+    hits.extend(one::<memories::Entry>(store, "memories", text, limit).await?);
+    hits.extend(one::<task::Entry>(store, "task", text, limit).await?);
+    hits.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Dựng lại chỉ mục ngược của cả ba miền từ dữ liệu, trả về số cặp (từ, bản ghi) đã ghi.
+/// Bản ghi có từ trước khi có tìm kiếm toàn văn chỉ tìm thấy được sau bước này.
+pub async fn refresh(store: &Sled) -> Result<usize, Error> {
+    Ok(store.refresh::<architecture::Entry>().await?
+        + store.refresh::<memories::Entry>().await?
+        + store.refresh::<task::Entry>().await?)
+}
+
+/// In kết quả tìm kiếm kèm miền và điểm
+pub fn show(hits: &[Found]) {
+    if hits.is_empty() {
+        println!("Không tìm thấy kết quả nào.");
+        return;
+    }
+    for (label, hit) in hits {
+        print!("{:<12} {:>6.2}  ", label, hit.score);
+        hit.summary.show();
+    }
+}
@@ -0,0 +1,99 @@
+//! Module hiển thị thống kê store (`knowledge stats`): dung lượng từng thực thể và metric đang chạy.
+
+use repository::storage::stats::Stats;
+use repository::metric::{Frame, Window};
+use repository::router::{Request, Router};
+use repository::{Error, Registry, Sled, Storage};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Lấy thống kê store cùng metric của actor rồi in dạng bảng hoặc JSON.
+pub async fn show(store: &Sled, json: bool) -> Result<(), Error> {
+    let stats = store.stats().await?;
+    let metrics = store.handle.metrics();
+    if json {
+        println!("{}", encode(&stats, &metrics)?);
+    } else {
+        println!("{}", stats);
+        println!();
+        println!(
+            "{:<20} {:>10} {:>10} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "Metric", "Thành công", "Thất bại", "TB (ns)", "p50", "p90", "p99", "max"
+        );
+        for (name, sample) in metrics.snapshot().operations {
+            println!(
+                "{:<20} {:>10} {:>10} {:>12} {:>12} {:>12} {:>12} {:>12}",
+                name, sample.count, sample.fail, sample.average,
+                sample.p50, sample.p90, sample.p99, sample.max
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Ghép thống kê store và ảnh chụp metric thành một tài liệu JSON
+fn encode(stats: &Stats, metrics: &Registry) -> Result<String, Error> {
+    let doc = serde_json::json!({ "store": stats, "metrics": metrics.snapshot() });
+    Ok(serde_json::to_string_pretty(&doc)?)
+}
+
+/// Theo dõi liên tục store mà CLI đang mở, dừng khi Ctrl-C.
+/// Registry của actor được `tick` định kỳ (`Registry::watch`); mỗi `every` giây đọc `/metrics/windows`
+/// và `/metrics/history` từ router gắn registry đó rồi in các cửa sổ 1m/5m/15m.
+pub async fn watch(store: &Sled, every: u64, json: bool) -> Result<(), Error> {
+    let metrics = store.handle.metrics();
+    let router = Router::new().registry(metrics.clone());
+    router.init().await.map_err(|e| Error::Parse(e.to_string()))?;
+    let ticker = metrics.watch();
+    let mut interval = tokio::time::interval(Duration::from_secs(every.max(1)));
+    let res = loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+        let (windows, history) = match poll(&router).await {
+            Ok(res) => res,
+            Err(e) => break Err(e),
+        };
+        if json {
+            println!("{}", serde_json::json!({ "windows": windows, "history": history.len() }));
+        } else {
+            println!("{}", table(&windows));
+            println!("Lịch sử: {} ảnh chụp", history.len());
+        }
+    };
+    ticker.abort();
+    res
+}
+
+/// Đọc cửa sổ trượt và vòng lịch sử qua các route `/metrics` của router
+async fn poll(router: &Router) -> Result<(Vec<Window>, Vec<Frame>), Error> {
+    let windows = serde_json::from_slice(&fetch(router, "/metrics/windows").await?)?;
+    let history = serde_json::from_slice(&fetch(router, "/metrics/history").await?)?;
+    Ok((windows, history))
+}
+
+/// Gọi một route GET của router, trả về thân response
+async fn fetch(router: &Router, path: &str) -> Result<Vec<u8>, Error> {
+    let request = Request { path: path.to_string(), method: "GET".to_string(), headers: HashMap::new(), body: Vec::new() };
+    let response = router.route(request).await.map_err(|e| Error::Parse(format!("{}: {}", path, e)))?;
+    Ok(response.body)
+}
+
+/// Bảng cửa sổ: mỗi dòng một thao tác trong một cửa sổ
+fn table(windows: &[Window]) -> String {
+    let mut lines = vec![format!(
+        "{:<20} {:>6} {:>10} {:>10} {:>8} {:>12} {:>12} {:>12}",
+        "Metric", "Cửa sổ", "Số lần", "Lần/giây", "Lỗi %", "p50", "p99", "max"
+    )];
+    for window in windows {
+        for (name, pace) in &window.operations {
+            lines.push(format!(
+                "{:<20} {:>6} {:>10} {:>10.2} {:>8.2} {:>12} {:>12} {:>12}",
+                name, format!("{}m", window.span / 60), pace.total, pace.throughput,
+                pace.errors * 100.0, pace.p50, pace.p99, pace.max
+            ));
+        }
+    }
+    lines.join("\n")
+}
@@ -1,7 +1,8 @@
 //! Module quản lý các bản ghi công việc (todo) thông qua `todo` crate.
 
 use repository::error::Fault;
-use repository::{Error, Id, Query, Storage};
+use repository::storage::import::{identity, Row};
+use repository::{Entity, Error, Id, Query, Storage};
 pub use task::{Entry, Patch, Priority, Status, Summary};
 use task;
 use shared::interaction::Command;
@@ -122,4 +123,38 @@ pub async fn list<S: Storage>(
 /// Thay đổi một công việc.
 pub async fn change<S: Storage>(store: &S, id: Id, patch: Patch) -> Result<Entry, Error> {
     task::change(store, id, patch).await
-}
\ No newline at end of file
+}
+
+/// Chuyển một dòng của `todo.csv` thành công việc, dùng cho `knowledge import task`.
+/// Cột: ID, Context, Module, Task, Priority, Status, Assignee, Due, Notes.
+/// Thời điểm tạo lấy từ đồng hồ của `store`.
+pub fn parse<S: Storage>(store: &S, row: &Row) -> Result<Entry, Error> {
+    let command = Add {
+        context: row.text("Context"),
+        module: row.text("Module"),
+        task: row.text("Task"),
+        priority: Priority::try_from(row.get("Priority").unwrap_or("Medium").to_string())?,
+        status: Status::try_from(row.get("Status").unwrap_or("Open").to_string())?,
+        assignee: row.text("Assignee"),
+        due: row.text("Due"),
+        notes: row.text("Notes"),
+    };
+    command.validate().map_err(Error::Validation)?;
+    Ok(Entry {
+        id: identity(Entry::NAME, row.require("ID")?),
+        context: command.context,
+        module: command.module,
+        task: command.task,
+        priority: command.priority,
+        status: command.status,
+        assignee: command.assignee,
+        due: command.due,
+        notes: command.notes,
+        created: store.now(),
+    })
+}
+
+/// Khi upsert, giữ thời điểm tạo đã lưu (`todo.csv` không có cột này) để khóa chỉ mục và thứ tự không đổi.
+pub fn merge(_row: &Row, stored: Entry, entry: Entry) -> Entry {
+    Entry { created: stored.created, ..entry }
+}
@@ -2,7 +2,8 @@
 //! Dữ liệu được lưu trữ thông qua `repository::Storage` để tăng hiệu suất.
 
 use serde::{Deserialize, Serialize};
-use repository::{error::Fault, now, Entity, Error, Id, Key, Query, Storage};
+use repository::{error::Fault, Decoder, Entity, Error, Id, Key, Query, Storage};
+use repository::storage::id::Scheme;
 use shared::{Showable, Filterable};
 use tracing::{info, warn};
 
@@ -65,6 +66,7 @@ pub struct Entry {
 
 impl Entity for Entry {
     const NAME: &'static str = "memories"; // Tên tree trong Sled
+    const SCHEME: Scheme = Scheme::Ordered; // Khóa tăng dần theo thời gian tạo
     type Key = Id; // Key là ID duy nhất
     type Index = Vec<u8>; // Index để sắp xếp/truy vấn
     type Summary = Summary;
@@ -81,14 +83,39 @@ impl Entity for Entry {
         key.build()
     }
 
+    fn locate(index: &[u8]) -> Option<Self::Key> {
+        // type + time, rồi id
+        let mut parts = Decoder::new(index);
+        parts.byte().ok()?;
+        parts.time().ok()?;
+        parts.id().ok()
+    }
+
     fn summary(&self) -> Self::Summary {
         Summary {
             id: self.id,
             r#type: self.r#type.clone(),
             subject: self.subject.clone(),
             created: self.created,
+            context: self.context.clone(),
+            module: self.module.clone(),
         }
     }
+
+    fn text(&self) -> Vec<String> {
+        vec![
+            self.subject.clone(),
+            self.description.clone(),
+            self.decision.clone(),
+            self.rationale.clone(),
+            self.context.clone(),
+            self.module.clone(),
+        ]
+    }
+
+    fn created(&self) -> Option<u128> {
+        Some(self.created)
+    }
 }
 
 impl Filterable for Entry {
@@ -113,6 +140,8 @@ pub struct Summary {
     pub r#type: Kind,
     pub subject: String,
     pub created: u128,
+    pub context: String,
+    pub module: String,
 }
 
 // Triển khai Showable cho Summary của memories
@@ -149,8 +178,9 @@ pub async fn add<S: Storage>(
         }]));
     }
     
+    let created = store.now();
     let entry = Entry {
-        id: Id::new_v4(),
+        id: store.generate::<Entry>(created),
         r#type: kind, // Sử dụng enum đã được xác thực
         context,
         module,
@@ -158,7 +188,7 @@ pub async fn add<S: Storage>(
         description,
         decision,
         rationale,
-        created: now(),
+        created,
     };
     let result = entry.clone();
     store.insert(entry).await?;
@@ -198,7 +228,9 @@ mod tests {
     use repository::sled::Sled; // Sử dụng Sled làm backend test
     use tempfile::tempdir;
     use tokio::runtime::Runtime;
-    // use repository::now; // Import `now` chỉ trong scope test
+    use repository::storage::time::Manual;
+    use std::sync::Arc;
+    use std::time::Duration;
 
     fn memory() -> Sled {
         let dir = tempdir().unwrap();
@@ -227,6 +259,7 @@ mod tests {
             assert_eq!(found.subject, "Naming");
             assert_eq!(found.id, added.id);
             assert_eq!(found.r#type, Kind::Decision);
+            assert_eq!(Entry::locate(&found.index()), Some(found.id));
         });
     }
 
@@ -235,8 +268,11 @@ mod tests {
     fn list() {
         let rt = Runtime::new().unwrap();
         rt.block_on(async {
-            let store = memory();
+            // Clock chỉnh tay: mỗi bản ghi cách nhau đúng một giây, không phụ thuộc tốc độ máy
+            let clock = Arc::new(Manual::new(1_000_000_000_000_000_000));
+            let store = memory().clock(clock.clone());
             for i in 0..5 {
+                clock.advance(Duration::from_secs(1));
                 let r#type = if i % 2 == 0 { "Decision" } else { "Analysis" }.to_string();
                 add(
                     &store,
@@ -250,12 +286,13 @@ mod tests {
                 ).await.unwrap();
             }
 
-            let items = query(&store, Query { prefix: Vec::new(), after: None, limit: 10 }).await.unwrap();
+            let items = query(&store, Query { prefix: Vec::new(), after: None, limit: 10, filter: None }).await.unwrap();
             let mut summaries: Vec<_> = items.collect::<Result<Vec<_>, _>>().unwrap();
             assert_eq!(summaries.len(), 5);
-            summaries.sort_by(|a, b| b.created.cmp(&a.created));
+            summaries.sort_by_key(|s| std::cmp::Reverse(s.created));
             assert_eq!(summaries[0].subject, "Subject4");
             assert_eq!(summaries[4].subject, "Subject0");
+            assert_eq!(summaries[0].created, 1_000_000_005_000_000_000);
             // Kiểm tra đúng loại
             assert_eq!(summaries[0].r#type, Kind::Decision);
             assert_eq!(summaries[1].r#type, Kind::Analysis);
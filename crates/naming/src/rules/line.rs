@@ -163,10 +163,10 @@ fn check(alias: &str) -> Option<(String, &'static str)> {
     if name.contains('_') {
         return Some((name, "AliasSnakeCase"));
     }
-    if name.chars().next().map(|c| c.is_lowercase()).unwrap_or(false) {
-        if name.chars().any(|c| c.is_uppercase()) {
-            return Some((name, "AliasCamelCase"));
-        }
+    if name.chars().next().map(|c| c.is_lowercase()).unwrap_or(false)
+        && name.chars().any(|c| c.is_uppercase())
+    {
+        return Some((name, "AliasCamelCase"));
     }
     if text::hub(&name) > 1 {
         return Some((name, "AliasPascalCase"));
@@ -0,0 +1,133 @@
+//! Test của crate trước khi trở thành mặt tiền của kernel, chạy lại qua các đường dẫn `repository::...`.
+
+use crate::actor::Actorable;
+use crate::storage::Storage;
+use crate::{Entity, Id, Query, Sled};
+use serde::{Deserialize, Serialize};
+use tempfile::tempdir;
+
+fn memory() -> (tempfile::TempDir, Sled) {
+    let dir = tempdir().unwrap();
+    let store = Sled::new(dir.path().to_str().unwrap()).unwrap();
+    (dir, store)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Thing {
+    id: Id,
+    name: String,
+    value: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Brief {
+    id: Id,
+    name: String,
+}
+
+impl Entity for Thing {
+    const NAME: &'static str = "things";
+    type Key = Id;
+    type Index = Vec<u8>;
+    type Summary = Brief;
+
+    fn key(&self) -> Self::Key { self.id }
+    fn index(&self) -> Self::Index {
+        let mut index = format!("idx_{}", self.value).into_bytes();
+        index.extend_from_slice(self.id.as_bytes());
+        index
+    }
+    fn summary(&self) -> Self::Summary {
+        Brief { id: self.id, name: self.name.clone() }
+    }
+}
+
+#[tokio::test]
+async fn crud() {
+    let (_dir, store) = memory();
+    let item = Thing { id: Id::new_v4(), name: "Test".to_string(), value: 42 };
+    // Insert
+    store.insert(item.clone()).await.unwrap();
+    // Fetch
+    let fetched = store.fetch::<Thing>(item.id).await.unwrap().unwrap();
+    assert_eq!(item, fetched);
+    // Update
+    let updated = Thing { value: 100, ..item.clone() };
+    store.insert(updated.clone()).await.unwrap();
+    let fetched = store.fetch::<Thing>(item.id).await.unwrap().unwrap();
+    assert_eq!(updated, fetched);
+    // Delete
+    let deleted = store.delete::<Thing>(item.id).await.unwrap();
+    assert_eq!(updated, deleted);
+    // Verify deletion
+    assert!(store.fetch::<Thing>(item.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn bulk() {
+    let (_dir, store) = memory();
+    let things: Vec<_> = (0..100).map(|i| Thing {
+        id: Id::new_v4(),
+        name: format!("Thing {}", i),
+        value: i,
+    }).collect();
+    store.mass(Box::new(things.clone().into_iter())).await.unwrap();
+    for item in &things {
+        let fetched = store.fetch::<Thing>(item.id).await.unwrap().unwrap();
+        assert_eq!(*item, fetched);
+    }
+}
+
+#[tokio::test]
+async fn update() {
+    let (_dir, store) = memory();
+    let item = Thing { id: Id::new_v4(), name: "Counter".to_string(), value: 0 };
+    store.insert(item.clone()).await.unwrap();
+
+    // Cập nhật đồng thời trên cùng một khóa: không lần nào bị Conflict, không mất lần nào
+    let mut tasks = vec![];
+    for _ in 0..10 {
+        let store = store.clone();
+        tasks.push(tokio::spawn(async move {
+            for _ in 0..10 {
+                store.update::<Thing, _>(item.id, |mut t| { t.value += 1; t }).await.unwrap();
+            }
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(store.fetch::<Thing>(item.id).await.unwrap().unwrap().value, 100);
+    assert!(store.check::<Thing>().await.unwrap().clean());
+}
+
+#[tokio::test]
+async fn upgrade() {
+    let (_dir, store) = memory();
+    // Bản ghi do phiên bản cũ ghi vào tree mặc định vẫn đọc được sau khi nâng cấp
+    let item = Thing { id: Id::new_v4(), name: "Legacy".to_string(), value: 7 };
+    let key = bincode::serialize(&item.id).unwrap();
+    store.handle.insert(key, bincode::serialize(&item).unwrap()).await.unwrap();
+    assert!(store.fetch::<Thing>(item.id).await.unwrap().is_none());
+
+    assert_eq!(store.upgrade::<Thing>().await.unwrap(), 1);
+    assert_eq!(store.fetch::<Thing>(item.id).await.unwrap(), Some(item));
+    let names: Vec<_> = store.query::<Thing>(Query::default()).await.unwrap().map(|b| b.unwrap().name).collect();
+    assert_eq!(names, vec!["Legacy".to_string()]);
+}
+
+#[tokio::test]
+async fn metrics() {
+    let (_dir, store) = memory();
+    let item = Thing { id: Id::new_v4(), name: "Metric".to_string(), value: 1 };
+    store.insert(item.clone()).await.unwrap();
+    store.fetch::<Thing>(item.id).await.unwrap();
+    store.update::<Thing, _>(item.id, |mut t| { t.value = 2; t }).await.unwrap();
+    store.delete::<Thing>(item.id).await.unwrap();
+
+    let stats = store.handle.metrics().stats();
+    for name in ["many", "apply", "get", "modify"] {
+        assert!(stats.contains(name), "thiếu metric {}: {}", name, stats);
+    }
+    assert!(store.handle.metrics().get("modify").count() > 0);
+}
@@ -26,7 +26,7 @@ pub fn filter(done: bool, after: Option<(u128, Id)>, limit: usize) -> Query<Vec<
         key.id(id);
         key.clone().build()
     });
-    Query { prefix, after, limit }
+    Query { prefix, after, limit, filter: None }
 }
 
 /// Hàm tiện ích tạo Query cho mọi domain, nhận vào prefix, after, limit.
@@ -39,5 +39,6 @@ where
         prefix: prefix.into(),
         after: after.map(|a| a.into()),
         limit,
+        filter: None,
     }
 }
@@ -1,7 +1,8 @@
 //! Triển khai Entity cho mô hình Task, sử dụng enum để tăng cường an toàn và hiệu suất.
 
 use serde::{Deserialize, Serialize};
-use repository::{error::Fault, Entity, Error, Id, Key, now, Query, Storage};
+use repository::{error::Fault, Decoder, Entity, Error, Id, Key, Predicate, Query, Storage};
+use repository::storage::id::Scheme;
 use shared::Showable;
 use tracing::{info, instrument, warn};
 use std::convert::TryFrom;
@@ -93,6 +94,8 @@ pub struct Entry {
 
 impl Entity for Entry {
     const NAME: &'static str = "tasks";
+    const SCHEME: Scheme = Scheme::Ordered; // Khóa tăng dần theo thời gian tạo
+    const LAYOUT: u32 = 1; // 1: Summary trong chỉ mục mang thêm context, module, assignee
     type Key = Id;
     type Index = Vec<u8>;
     type Summary = Summary;
@@ -116,8 +119,40 @@ impl Entity for Entry {
             priority: self.priority.clone(),
             status: self.status.clone(),
             task: self.task.clone(),
+            context: self.context.clone(),
+            module: self.module.clone(),
+            assignee: self.assignee.clone(),
         }
     }
+
+    fn text(&self) -> Vec<String> {
+        vec![self.task.clone(), self.notes.clone(), self.context.clone(), self.module.clone(), self.assignee.clone()]
+    }
+
+    fn created(&self) -> Option<u128> {
+        Some(self.created)
+    }
+
+    fn prefix(predicate: &Predicate) -> Vec<u8> {
+        // Chỉ mục bắt đầu bằng status rồi priority, nên chỉ dùng priority khi đã cố định status
+        let mut prefix = Vec::new();
+        if let Some(status) = predicate.pin::<Status>("status") {
+            prefix.push((&status).into());
+            if let Some(priority) = predicate.pin::<Priority>("priority") {
+                prefix.push((&priority).into());
+            }
+        }
+        prefix
+    }
+
+    fn locate(index: &[u8]) -> Option<Self::Key> {
+        // status + priority + time, rồi id
+        let mut parts = Decoder::new(index);
+        parts.byte().ok()?;
+        parts.byte().ok()?;
+        parts.time().ok()?;
+        parts.id().ok()
+    }
 }
 
 /// Một bản tóm tắt của `Entry` để hiển thị trong danh sách.
@@ -127,6 +162,9 @@ pub struct Summary {
     pub priority: Priority,
     pub status: Status,
     pub task: String,
+    pub context: String,
+    pub module: String,
+    pub assignee: String,
 }
 
 impl Showable for Summary {
@@ -171,8 +209,9 @@ pub async fn add<S: Storage>(
         }]));
     }
     
+    let created = store.now();
     let task = Entry {
-        id: Id::new_v4(),
+        id: store.generate::<Entry>(created),
         context,
         module,
         task: desc,
@@ -181,7 +220,7 @@ pub async fn add<S: Storage>(
         assignee,
         due,
         notes,
-        created: now(),
+        created,
     };
     
     let result = task.clone();
@@ -275,10 +314,32 @@ mod tests {
 
             let found = find(&store, added.id).await.unwrap();
             assert_eq!(added, found);
+            assert_eq!(Entry::locate(&added.index()), Some(added.id));
             assert_eq!(found.priority, Priority::High);
         });
     }
     
+    #[test]
+    // Khóa UUIDv7: tăng dần theo thứ tự thêm, đọc lại được thời điểm tạo
+    fn ordered() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let clock = std::sync::Arc::new(repository::storage::time::Manual::new(1_700_000_000_000_000_000));
+            let store = memory().clock(clock.clone());
+            let mut ids = Vec::new();
+            for i in 0..20 {
+                clock.advance(std::time::Duration::from_millis(1));
+                let added = add(&store, "".into(), "".into(), format!("Task {}", i), Priority::Low, Status::Open, "".into(), "".into(), "".into()).await.unwrap();
+                assert_eq!(added.created, 1_700_000_000_000_000_000 + (i + 1) * 1_000_000);
+                assert_eq!(repository::storage::id::time(&added.id), Some(added.created));
+                ids.push(added.id);
+            }
+            let mut sorted = ids.clone();
+            sorted.sort();
+            assert_eq!(ids, sorted);
+        });
+    }
+
     #[test]
     // Kiểm tra cập nhật trạng thái task (gốc: change_status)
     fn update() {
@@ -308,25 +369,18 @@ mod tests {
             add(&store, "".into(), "".into(), "High Done".into(), Priority::High, Status::Done, "".into(), "".into(), "".into()).await.unwrap();
 
             // Query for Open tasks
-            let open = vec![(&Status::Open).into()];
-            let val = shared::query(open, None::<Vec<u8>>, 10);
-            let results: Vec<_> = query(&store, val).await.unwrap().collect::<Result<_,_>>().unwrap();
-            let mut result: Vec<_> = results.into_iter().filter(|t| t.status == Status::Open).collect();
+            let open = Query { limit: 10, ..Default::default() }.filter(Predicate::eq("status", Status::Open));
+            let mut result: Vec<_> = query(&store, open).await.unwrap().collect::<Result<_,_>>().unwrap();
             result.sort_by_key(|t| match t.priority { Priority::High => 0, Priority::Medium => 1, Priority::Low => 2 });
-            println!("DEBUG: Query Open tasks, got {} results:", result.len());
-            for t in &result {
-                println!("  - task: {} | status: {:?} | priority: {:?}", t.task, t.status, t.priority);
-            }
             assert_eq!(result.len(), 2);
             // Check sorting: High priority should come first
             assert_eq!(result[0].task, "High Open");
             assert_eq!(result[1].task, "Med Open");
 
             // Query for Open, High-Priority tasks
-            let high = vec![(&Status::Open).into(), (&Priority::High).into()];
-            let val = shared::query(high, None::<Vec<u8>>, 10);
-            let results: Vec<_> = query(&store, val).await.unwrap().collect::<Result<_,_>>().unwrap();
-            let output: Vec<_> = results.into_iter().filter(|t| t.status == Status::Open && t.priority == Priority::High).collect();
+            let high = Predicate::eq("status", Status::Open).and(Predicate::eq("priority", Priority::High));
+            let val = Query { limit: 10, ..Default::default() }.filter(high);
+            let output: Vec<_> = query(&store, val).await.unwrap().collect::<Result<_,_>>().unwrap();
             assert_eq!(output.len(), 1);
             assert_eq!(output[0].task, "High Open");
         });